@@ -0,0 +1,218 @@
+use bevy::{
+    log::{Level, LogPlugin},
+    prelude::*,
+};
+use bevy_rapier3d::prelude::*;
+use boxxed::prelude::*;
+
+fn main() {
+    App::new()
+        .add_plugins(
+            DefaultPlugins
+                .set(WindowPlugin {
+                    window: WindowDescriptor {
+                        width: 1280.,
+                        height: 720.,
+                        scale_factor_override: Some(1.),
+                        title: "boxxed".to_string(),
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                })
+                .set(AssetPlugin {
+                    watch_for_changes: true,
+                    ..Default::default()
+                })
+                .set(LogPlugin {
+                    level: Level::INFO,
+                    filter: "wgpu=error,bevy_render=info,bevy_ecs=error".to_string(),
+                }),
+        )
+        .add_plugin(RapierPhysicsPlugin::<NoUserData>::default())
+        .add_plugin(RapierDebugRenderPlugin::default())
+        .add_plugin(DebugCameraPlugin::default().with_config_asset("tuning/debug.camera.ron"))
+        .add_plugin(CharacterControllerPlugin::default().with_tuning("tuning/player.character.ron"))
+        .add_plugin(BindingsPlugin::default().with_asset("input/default.bindings.ron"))
+        .add_plugin(InputContextPlugin)
+        .add_plugin(ConsolePlugin::default())
+        .add_plugin(InputDisplayPlugin)
+        .add_plugin(PausePlugin::default())
+        .add_plugin(TimeScalePlugin::default())
+        .add_plugin(RumblePlugin)
+        .add_plugin(RespawnPlugin)
+        .add_plugin(BoundsPlugin)
+        .add_plugin(TriggerPlugin)
+        .add_plugin(CheckpointPlugin)
+        .add_plugin(InteractionPlugin)
+        .add_plugin(CarryPlugin)
+        .add_plugin(PushPlugin)
+        .add_plugin(SwitchPlugin)
+        .add_plugin(DoorPlugin)
+        .add_plugin(PlatformPlugin)
+        .add_plugin(OneWayPlatformPlugin)
+        .add_plugin(SurfacePlugin)
+        .add_plugin(LevelPlugin)
+        .add_plugin(LevelReloadPlugin)
+        .add_plugin(LevelFlowPlugin::default())
+        .add_plugin(LightingPlugin::default().with_preset_asset("lighting/demo.lighting.ron"))
+        .add_plugin(SkyboxPlugin)
+        .add_plugin(LevelEditorPlugin::default())
+        .add_plugin(GizmoPlugin)
+        .add_plugin(TestPlaygroundPlugin::at(Vec3::new(-150.0, 0.0, 0.0)))
+        .add_plugin(BreakablePlugin)
+        .add_plugin(HitscanPlugin)
+        .add_plugin(RagdollPlugin::default())
+        .add_plugin(ObjectSpawnerPlugin)
+        .add_plugin(WaterPlugin)
+        .add_plugin(ExplosionPlugin)
+        .add_startup_system(build_map)
+        .add_system(toggle_ragdoll)
+        .add_system(spawn_debug_objects)
+        .add_system(debug_explosion)
+        .add_system(generate_debug_level)
+        .add_system(load_debug_level)
+        .run()
+}
+
+fn build_map(mut commands: Commands) {
+    commands.spawn((
+        Collider::cuboid(100.0, 1.0, 100.0),
+        Surface::new(SurfaceKind::Stone),
+    ));
+    commands.spawn(KillPlane { height: -50.0 });
+    commands.spawn((
+        PlayerSpawn,
+        RespawnPoint,
+        TransformBundle::from(Transform::from_xyz(0.0, 4.0, 0.0)),
+    ));
+    commands.spawn(SpotLightBundle::default());
+
+    // Pool with a raft to stand on
+    commands.spawn((
+        WaterVolume::default(),
+        Collider::cuboid(4.0, 1.0, 4.0),
+        TransformBundle::from(Transform::from_xyz(12.0, 2.0, 0.0)),
+    ));
+    commands.spawn((
+        Buoyant::default(),
+        RigidBody::Dynamic,
+        Velocity::default(),
+        Collider::cuboid(1.0, 0.25, 1.0),
+        TransformBundle::from(Transform::from_xyz(12.0, 3.5, 0.0)),
+    ));
+
+    // Ledge to jump up through
+    commands.spawn((
+        OneWayPlatform,
+        Collider::cuboid(3.0, 0.1, 3.0),
+        TransformBundle::from(Transform::from_xyz(0.0, 4.0, -12.0)),
+    ));
+
+    // Checkpoint past the ledge
+    commands.spawn((
+        Checkpoint::new(1),
+        Collider::cuboid(2.0, 2.0, 2.0),
+        TransformBundle::from(Transform::from_xyz(0.0, 7.0, -12.0)),
+    ));
+
+    // Turntable
+    commands.spawn((
+        RotatingPlatform::around_y(0.5),
+        Collider::cuboid(3.0, 0.25, 3.0),
+        TransformBundle::from(Transform::from_xyz(-12.0, 1.25, 0.0)),
+    ));
+}
+
+/// Knocks characters over and back up with `K`
+fn toggle_ragdoll(
+    keys: Res<Input<KeyCode>>,
+    characters: Query<Entity, With<CharacterMovementController>>,
+    mut requests: EventWriter<RagdollRequest>,
+) {
+    if keys.just_pressed(KeyCode::K) {
+        requests.send_batch(characters.iter().map(RagdollRequest::Toggle));
+    }
+}
+
+/// Spawns a box, sphere, ramp or wall in front of the camera with `1` to `4`, outside the editor
+fn spawn_debug_objects(
+    keys: Res<Input<KeyCode>>,
+    cameras: Query<(&Camera, &GlobalTransform), With<Camera3d>>,
+    state: Res<State<CameraState>>,
+    mut spawns: EventWriter<SpawnObject>,
+) {
+    if *state.current() == CameraState::Editor {
+        return;
+    }
+    let shape = match () {
+        _ if keys.just_pressed(KeyCode::Key1) => PrimitiveShape::Box,
+        _ if keys.just_pressed(KeyCode::Key2) => PrimitiveShape::Sphere,
+        _ if keys.just_pressed(KeyCode::Key3) => PrimitiveShape::Ramp,
+        _ if keys.just_pressed(KeyCode::Key4) => PrimitiveShape::Wall,
+        _ => return,
+    };
+    let Some((_, camera)) = cameras.iter().find(|(camera, _)| camera.is_active) else {
+        return;
+    };
+
+    spawns.send(SpawnObject::in_front_of(shape, camera, 5.0));
+}
+
+/// Blows up the spot in front of the camera with `X`
+fn debug_explosion(
+    keys: Res<Input<KeyCode>>,
+    cameras: Query<(&Camera, &GlobalTransform), With<Camera3d>>,
+    mut explosions: EventWriter<Explode>,
+) {
+    if !keys.just_pressed(KeyCode::X) {
+        return;
+    }
+    let Some((_, camera)) = cameras.iter().find(|(camera, _)| camera.is_active) else {
+        return;
+    };
+
+    explosions.send(Explode {
+        center: camera.translation() + camera.forward() * 5.0,
+        radius: 4.0,
+        force: 500.0,
+    });
+}
+
+/// Root of the level made by `generate_debug_level`
+#[derive(Component)]
+struct GeneratedLevelRoot;
+
+/// Replaces the procedural level off to the side with the next seed on `G`
+fn generate_debug_level(
+    mut commands: Commands,
+    keys: Res<Input<KeyCode>>,
+    roots: Query<Entity, With<GeneratedLevelRoot>>,
+    mut seed: Local<u64>,
+) {
+    if !keys.just_pressed(KeyCode::G) {
+        return;
+    }
+    for root in roots.iter() {
+        commands.entity(root).despawn_recursive();
+    }
+
+    *seed += 1;
+    let root = commands
+        .spawn((
+            GeneratedLevelRoot,
+            TransformBundle::from(Transform::from_xyz(0.0, 0.0, 200.0)),
+            VisibilityBundle::default(),
+        ))
+        .id();
+    commands.add(GenerateLevel {
+        root,
+        settings: GeneratorSettings::new(*seed),
+    });
+}
+
+/// Switches to the sample level with `L`
+fn load_debug_level(keys: Res<Input<KeyCode>>, mut requests: EventWriter<LoadLevel>) {
+    if keys.just_pressed(KeyCode::L) {
+        requests.send(LoadLevel::new("sample").with_spawn("sample"));
+    }
+}
@@ -0,0 +1,174 @@
+use crate::prelude::*;
+use bevy::prelude::*;
+use leafwing_input_manager::prelude::*;
+use CharacterState::*;
+
+/// Everything an ability may look at while deciding what to do this step
+pub struct AbilityContext<'a> {
+    pub entity: Entity,
+    pub state: CharacterState,
+    pub settings: &'a CharacterSettings,
+    pub actions: &'a ActionState<CharacterActions>,
+    /// Movement force in character local space
+    pub movement: Vec3,
+    pub grounded: bool,
+    /// Seconds spent in the current state
+    pub time_in_state: f32,
+}
+
+/// A piece of character behaviour plugged into the force and state pipeline
+pub trait CharacterAbility: Send + Sync + 'static {
+    fn name(&self) -> &'static str;
+
+    /// Local space force added to the character actions this step
+    fn force(&mut self, _ctx: &AbilityContext) -> Vec3 {
+        Vec3::ZERO
+    }
+
+    /// Speed tier the character should approach, if this ability drives it
+    fn speed(&self, _ctx: &AbilityContext) -> Option<f32> {
+        None
+    }
+
+    /// State to switch to, checked along with the transitions of the current state
+    fn transition(&mut self, _ctx: &TransitionContext) -> Option<CharacterState> {
+        None
+    }
+}
+
+/// Abilities of a character, evaluated in insertion order
+///
+/// Later abilities win when several want to change the state.
+#[derive(Component, Default)]
+pub struct CharacterAbilities(Vec<Box<dyn CharacterAbility>>);
+
+impl CharacterAbilities {
+    /// Sprint, crouch, slide and jump, which takes precedence over the others
+    pub fn standard() -> Self {
+        CharacterAbilities::default()
+            .with(SprintAbility)
+            .with(CrouchAbility)
+            .with(SlideAbility)
+            .with(JumpAbility)
+    }
+
+    pub fn with(mut self, ability: impl CharacterAbility) -> Self {
+        self.push(ability);
+        self
+    }
+
+    pub fn push(&mut self, ability: impl CharacterAbility) {
+        self.0.push(Box::new(ability));
+    }
+
+    pub fn remove(&mut self, name: &str) {
+        self.0.retain(|ability| ability.name() != name);
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.0.iter().any(|ability| ability.name() == name)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &dyn CharacterAbility> {
+        self.0.iter().map(|ability| ability.as_ref())
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut Box<dyn CharacterAbility>> {
+        self.0.iter_mut()
+    }
+}
+
+pub struct JumpAbility;
+impl CharacterAbility for JumpAbility {
+    fn name(&self) -> &'static str {
+        "jump"
+    }
+
+    fn force(&mut self, ctx: &AbilityContext) -> Vec3 {
+        match ctx.state {
+            CharacterState::Jump => Vec3::new(0., ctx.settings.jump_velocity(), 0.),
+            _ => Vec3::ZERO,
+        }
+    }
+
+    fn transition(&mut self, ctx: &TransitionContext) -> Option<CharacterState> {
+        match ctx.state {
+            Idle | Walk | Run | Slide => ctx.buffered(CharacterActions::Jump).then_some(Jump),
+            Jump => (ctx.physics.effective_translation.y < 0.0).then_some(Fall),
+            _ => None,
+        }
+    }
+}
+
+pub struct SprintAbility;
+impl CharacterAbility for SprintAbility {
+    fn name(&self) -> &'static str {
+        "sprint"
+    }
+
+    fn speed(&self, ctx: &AbilityContext) -> Option<f32> {
+        (ctx.state == CharacterState::Run).then_some(ctx.settings.speed.run)
+    }
+
+    fn transition(&mut self, ctx: &TransitionContext) -> Option<CharacterState> {
+        match ctx.state {
+            Walk => ctx.actions.pressed(CharacterActions::Sprint).then_some(Run),
+            Run => ctx
+                .actions
+                .just_released(CharacterActions::Sprint)
+                .then_some(Walk),
+            _ => None,
+        }
+    }
+}
+
+pub struct CrouchAbility;
+impl CharacterAbility for CrouchAbility {
+    fn name(&self) -> &'static str {
+        "crouch"
+    }
+
+    fn speed(&self, ctx: &AbilityContext) -> Option<f32> {
+        (ctx.state == CharacterState::Crouch).then_some(ctx.settings.speed.crouch)
+    }
+
+    fn transition(&mut self, ctx: &TransitionContext) -> Option<CharacterState> {
+        match ctx.state {
+            Idle | Walk => ctx.buffered(CharacterActions::Crouch).then_some(Crouch),
+            // Tapping crouch while running slides instead
+            Run => ctx.held(CharacterActions::Crouch).then_some(Crouch),
+            Crouch => ctx
+                .actions
+                .just_released(CharacterActions::Crouch)
+                .then(|| ctx.locomotion()),
+            _ => None,
+        }
+    }
+}
+
+pub struct SlideAbility;
+impl CharacterAbility for SlideAbility {
+    fn name(&self) -> &'static str {
+        "slide"
+    }
+
+    fn force(&mut self, ctx: &AbilityContext) -> Vec3 {
+        match ctx.state {
+            CharacterState::Slide => ctx.movement,
+            _ => Vec3::ZERO,
+        }
+    }
+
+    fn speed(&self, ctx: &AbilityContext) -> Option<f32> {
+        (ctx.state == CharacterState::Slide).then_some(ctx.settings.speed.slide)
+    }
+
+    fn transition(&mut self, ctx: &TransitionContext) -> Option<CharacterState> {
+        match ctx.state {
+            Run => ctx.tapped(CharacterActions::Crouch).then_some(Slide),
+            Slide if ctx.actions.just_released(CharacterActions::Crouch) => Some(Run),
+            Slide => (ctx.time_in_state >= ctx.settings.slide_duration).then(|| ctx.crouched()),
+            _ => None,
+        }
+    }
+}
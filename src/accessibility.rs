@@ -0,0 +1,175 @@
+use crate::prelude::*;
+use bevy::{prelude::*, utils::HashSet};
+use bevy_rapier3d::prelude::*;
+use leafwing_input_manager::{buttonlike::ButtonState, plugin::ToggleActions, prelude::*};
+use serde::{Deserialize, Serialize};
+
+/// Distance past the capsule a ledge is noticed at by auto jump, in meters
+const AUTO_JUMP_REACH: f32 = 0.4;
+
+/// Assists for players who can't hold or combine inputs, off by default
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AccessibilitySettings {
+    /// Jump when running into a ledge too high to step onto but low enough to jump on
+    pub auto_jump: bool,
+    /// Hold actions one press switches on and the next one off, e.g. sprint and crouch
+    pub toggle_actions: Vec<CharacterActions>,
+    /// Sprint whenever moving, so running needs no second input held
+    pub auto_sprint: bool,
+}
+
+/// Actions the accessibility assists of a character are holding down
+#[derive(Component, Debug, Default, Clone, PartialEq)]
+pub struct InputAssists {
+    /// Toggle actions currently switched on
+    toggled: HashSet<CharacterActions>,
+    /// Actions held on a device last frame, to tell new presses from assisted holds
+    physical: HashSet<CharacterActions>,
+    /// Actions an assist held last frame
+    held: HashSet<CharacterActions>,
+}
+
+impl InputAssists {
+    pub fn toggled(&self, action: CharacterActions) -> bool {
+        self.toggled.contains(&action)
+    }
+}
+
+/// Whether a ledge the character could jump onto blocks the way it moves in
+fn ledge_ahead(
+    rapier: &RapierContext,
+    entity: Entity,
+    transform: &Transform,
+    settings: &CharacterSettings,
+    height: &CharacterHeight,
+    movement: Vec3,
+) -> bool {
+    let direction = transform.rotation * movement;
+    let direction = Vec3::new(direction.x, 0.0, direction.z).normalize_or_zero();
+    if direction == Vec3::ZERO {
+        return false;
+    }
+
+    let feet = transform.translation
+        - Vec3::Y * (settings.segment_half_height(height.current()) + settings.radius());
+    let step = settings.autostep_height();
+    let reach = settings.radius() + AUTO_JUMP_REACH;
+    let filter = QueryFilter::default()
+        .exclude_rigid_body(entity)
+        .exclude_sensors();
+
+    // Too high to step onto, but with room above it at jump height
+    let low = feet + Vec3::Y * (step + 0.05);
+    let high = feet + Vec3::Y * settings.jump_height;
+    let blocked = rapier
+        .cast_ray(low, direction, reach, true, filter)
+        .is_some();
+    blocked
+        && rapier
+            .cast_ray(high, direction, reach, true, filter)
+            .is_none()
+}
+
+/// Overrides the device state of `action` with whether an assist holds it
+fn set_held(
+    actions: &mut ActionState<CharacterActions>,
+    action: CharacterActions,
+    held: bool,
+    was_held: bool,
+) {
+    let mut data = actions.action_data(action).clone();
+    data.state = match (held, was_held) {
+        (true, true) => ButtonState::Pressed,
+        (true, false) => ButtonState::JustPressed,
+        (false, true) => ButtonState::JustReleased,
+        (false, false) => ButtonState::Released,
+    };
+    actions.set_action_data(action, data);
+}
+
+/// Applies `InputSettings::accessibility` to the action states of device controlled characters
+#[allow(clippy::type_complexity)]
+pub fn apply_input_assists(
+    mut q: Query<
+        (
+            Entity,
+            &mut InputAssists,
+            &mut ActionState<CharacterActions>,
+            &ActionState<CharacterMovement>,
+            &CharacterInput,
+            &CharacterMovementController,
+            &CharacterHeight,
+            &Transform,
+        ),
+        Without<CharacterInputDriver>,
+    >,
+    settings: Res<InputSettings>,
+    toggle: Option<Res<ToggleActions<CharacterActions>>>,
+    rapier: Res<RapierContext>,
+) {
+    let settings = &settings.accessibility;
+    let enabled = toggle.map_or(true, |toggle| toggle.enabled);
+
+    for (entity, mut assists, mut actions, movement, input, character, height, transform) in
+        q.iter_mut()
+    {
+        let assists = assists.as_mut();
+        let physical: HashSet<CharacterActions> = CharacterActions::variants()
+            .filter(|action| actions.pressed(*action))
+            .collect();
+
+        // Nothing stays held while another context has the input
+        if !enabled {
+            assists.toggled.clear();
+            assists.held.clear();
+            assists.physical = physical;
+            continue;
+        }
+
+        for action in physical.difference(&assists.physical) {
+            if settings.toggle_actions.contains(action) && !assists.toggled.remove(action) {
+                assists.toggled.insert(*action);
+            }
+        }
+        assists
+            .toggled
+            .retain(|action| settings.toggle_actions.contains(action));
+
+        let moving = !movement.get_pressed().is_empty();
+        // Movement of the last frame, this one's is read after the assists
+        let ledge = settings.auto_jump
+            && character.grounded()
+            && ledge_ahead(
+                &rapier,
+                entity,
+                transform,
+                character.settings(),
+                height,
+                input.movement,
+            );
+
+        for action in CharacterActions::variants() {
+            let held = match action {
+                _ if settings.toggle_actions.contains(&action) => assists.toggled(action),
+                CharacterActions::Sprint if settings.auto_sprint => {
+                    moving || physical.contains(&action)
+                }
+                CharacterActions::Jump if ledge => true,
+                // Left to the device
+                _ => {
+                    assists.held.remove(&action);
+                    continue;
+                }
+            };
+            let was_held = assists.held.contains(&action);
+            set_held(&mut actions, action, held, was_held);
+            match held {
+                true => assists.held.insert(action),
+                false => assists.held.remove(&action),
+            };
+        }
+
+        assists.physical = physical;
+    }
+}
@@ -0,0 +1,91 @@
+use crate::prelude::*;
+use bevy::{prelude::*, render::camera::RenderTarget, window::WindowId};
+use leafwing_input_manager::prelude::*;
+
+/// Camera rendering to the primary window
+///
+/// Set it to switch cameras, `None` falls back to the first debug camera. Only full screen
+/// cameras take turns; cameras with a viewport or another target keep rendering.
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ActiveCamera(pub Option<Entity>);
+
+/// Debug camera receiving camera input, kept in sync with `ActiveCamera`
+#[derive(Component, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ControlledCamera;
+
+/// Whether the camera takes turns rendering to the whole primary window
+fn switchable(camera: &Camera) -> bool {
+    camera.viewport.is_none() && camera.target == RenderTarget::Window(WindowId::primary())
+}
+
+pub fn switch_active_camera(
+    mut commands: Commands,
+    mut cameras: Query<(Entity, &mut Camera, Option<&ControlledCamera>), Without<SplitScreenView>>,
+    debug_cameras: Query<(Entity, &ActionState<CameraAction>), With<DebugCamera>>,
+    mut active: ResMut<ActiveCamera>,
+    mut messages: EventWriter<HudMessage>,
+    split: Option<Res<SplitScreen>>,
+) {
+    let mut candidates: Vec<Entity> = cameras
+        .iter()
+        .filter(|(_, camera, _)| switchable(camera))
+        .map(|(entity, ..)| entity)
+        .collect();
+    candidates.sort();
+
+    let current = active
+        .0
+        .filter(|entity| candidates.contains(entity))
+        .or_else(|| {
+            let mut debug = debug_cameras.iter().map(|(entity, _)| entity);
+            debug.find(|entity| candidates.contains(entity))
+        })
+        .or_else(|| candidates.first().copied());
+
+    // Every debug camera reads the keyboard, even while another camera is active
+    let next = debug_cameras
+        .iter()
+        .any(|(_, actions)| actions.just_pressed(CameraAction::NextCamera));
+    let current = match (current, next) {
+        (Some(current), true) => {
+            let index = candidates
+                .iter()
+                .position(|entity| *entity == current)
+                .unwrap();
+            let index = (index + 1) % candidates.len();
+            messages.send(HudMessage::new(format!(
+                "Camera {}/{}",
+                index + 1,
+                candidates.len()
+            )));
+            Some(candidates[index])
+        }
+        (current, _) => current,
+    };
+
+    if active.0 != current {
+        active.0 = current;
+    }
+
+    // Split-screen views take over the window, remembering the camera to return to
+    let split = split.map_or(false, |split| split.enabled);
+    let current = current.filter(|_| !split);
+
+    for (entity, mut camera, controlled) in cameras.iter_mut() {
+        let is_current = current == Some(entity);
+        if switchable(&camera) && camera.is_active != is_current {
+            camera.is_active = is_current;
+        }
+
+        let controls = is_current && debug_cameras.contains(entity);
+        match (controls, controlled.is_some()) {
+            (true, false) => {
+                commands.entity(entity).insert(ControlledCamera);
+            }
+            (false, true) => {
+                commands.entity(entity).remove::<ControlledCamera>();
+            }
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,54 @@
+use bevy::{
+    asset::{AssetLoader, LoadContext, LoadedAsset},
+    reflect::TypeUuid,
+    utils::BoxedFuture,
+};
+use serde::de::DeserializeOwned;
+use std::marker::PhantomData;
+
+/// Loads any deserializable asset from a RON file with the given extensions
+pub struct RonAssetLoader<T> {
+    extensions: &'static [&'static str],
+    upgrade: Option<fn(&[u8], &mut T)>,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T> RonAssetLoader<T> {
+    pub fn new(extensions: &'static [&'static str]) -> Self {
+        RonAssetLoader {
+            extensions,
+            upgrade: None,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Runs `upgrade` with the file each asset was read from, to carry over keys it no longer has
+    pub fn with_upgrade(mut self, upgrade: fn(&[u8], &mut T)) -> Self {
+        self.upgrade = Some(upgrade);
+        self
+    }
+}
+
+impl<T> AssetLoader for RonAssetLoader<T>
+where
+    T: TypeUuid + DeserializeOwned + Send + Sync + 'static,
+{
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), anyhow::Error>> {
+        Box::pin(async move {
+            let mut asset = ron::de::from_bytes::<T>(bytes)?;
+            if let Some(upgrade) = self.upgrade {
+                upgrade(bytes, &mut asset);
+            }
+            load_context.set_default_asset(LoadedAsset::new(asset));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        self.extensions
+    }
+}
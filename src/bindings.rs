@@ -0,0 +1,710 @@
+use crate::prelude::*;
+use bevy::{input::keyboard::KeyboardInput, prelude::*, reflect::TypeUuid};
+use leafwing_input_manager::{axislike::AxisType, prelude::*, user_input::InputKind};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Stick deflection that counts as pressing a direction
+pub const STICK_THRESHOLD: f32 = 0.1;
+
+/// Loads the default bindings from a `.bindings.ron` asset and saves changed ones per user
+///
+/// Without an asset the built-in `Bindings::default()` are used. Rebinds are written to a file in
+/// the user's config directory, which is loaded instead of the asset once it exists. Keys are
+/// bound by their position on a QWERTY keyboard and moved to the same spot on the detected
+/// `KeyboardLayout`.
+pub struct BindingsPlugin {
+    path: Option<String>,
+    user_file: Option<PathBuf>,
+    detect_layout: bool,
+}
+
+impl Default for BindingsPlugin {
+    fn default() -> Self {
+        BindingsPlugin {
+            path: None,
+            user_file: user_config_dir().map(|dir| dir.join("input.bindings.ron")),
+            detect_layout: true,
+        }
+    }
+}
+
+impl BindingsPlugin {
+    /// Asset path of the bindings, reapplied whenever the file changes
+    pub fn with_asset(mut self, path: impl Into<String>) -> Self {
+        self.path = Some(path.into());
+        self
+    }
+
+    /// File rebinds are saved to and loaded from, in the user's config directory by default
+    pub fn with_user_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.user_file = Some(path.into());
+        self
+    }
+
+    /// Whether the keyboard layout is guessed from the first key presses
+    pub fn with_layout_detection(mut self, enabled: bool) -> Self {
+        self.detect_layout = enabled;
+        self
+    }
+}
+
+impl Plugin for BindingsPlugin {
+    fn build(&self, app: &mut App) {
+        if let Some(path) = &self.path {
+            app.insert_resource(BindingsPath(path.clone()));
+        }
+        if let Some(path) = &self.user_file {
+            app.insert_resource(UserBindingsPath(path.clone()));
+        }
+        if self.path.is_some() || self.user_file.is_some() {
+            app.add_startup_system(load_bindings);
+        }
+
+        if self.detect_layout {
+            app.add_system(detect_keyboard_layout.before(apply_keyboard_layout));
+        }
+
+        app.add_asset::<Bindings>()
+            .add_asset_loader(RonAssetLoader::<Bindings>::new(&["bindings.ron"]))
+            .init_resource::<Bindings>()
+            .init_resource::<KeyboardLayout>()
+            .add_event::<SaveBindings>()
+            .add_event::<ExportActionManifest>()
+            .add_system(apply_bindings)
+            .add_system(apply_keyboard_layout.after(apply_bindings))
+            .add_system(save_on_rebind::<CharacterMovement>)
+            .add_system(save_on_rebind::<CharacterActions>)
+            .add_system(save_bindings.after(apply_bindings))
+            .add_system(export_action_manifest.after(save_bindings));
+
+        #[cfg(feature = "debug_camera")]
+        app.add_system(save_on_rebind::<CameraAction>)
+            .add_system(save_on_rebind::<CameraMovement>);
+    }
+}
+
+/// Input an action can be bound to in a bindings file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum BindingInput {
+    Key(KeyCode),
+    Mouse(MouseButton),
+    MouseMotion,
+    MouseWheel,
+    GamepadButton(GamepadButtonType),
+    /// Pressed past the threshold, negative thresholds for the negative half
+    GamepadAxis(GamepadAxisType, f32),
+    LeftStick,
+    RightStick,
+    /// All held at once, e.g. `Chord([Key(LControl), Key(S)])`
+    Chord(Vec<BindingInput>),
+}
+
+/// Binding leafwing has no input for, e.g. a chord inside a chord
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnsupportedBinding(pub BindingInput);
+
+impl std::fmt::Display for UnsupportedBinding {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "unsupported binding {:?}", self.0)
+    }
+}
+
+impl std::error::Error for UnsupportedBinding {}
+
+impl TryFrom<BindingInput> for UserInput {
+    type Error = UnsupportedBinding;
+
+    fn try_from(input: BindingInput) -> Result<Self, Self::Error> {
+        let converted = match &input {
+            // Chords don't nest
+            BindingInput::Chord(inputs) => {
+                let kinds = inputs.iter().cloned().map(BindingInput::input_kind);
+                kinds.collect::<Option<Vec<_>>>().map(UserInput::chord)
+            }
+            single => single.clone().input_kind().map(UserInput::Single),
+        };
+        converted.ok_or(UnsupportedBinding(input))
+    }
+}
+
+impl BindingInput {
+    /// Single input this stands for, `None` for chords
+    pub fn input_kind(self) -> Option<InputKind> {
+        let kind = match self {
+            BindingInput::Key(key) => key.into(),
+            BindingInput::Mouse(button) => button.into(),
+            BindingInput::MouseMotion => DualAxis::mouse_motion().into(),
+            BindingInput::MouseWheel => DualAxis::mouse_wheel().into(),
+            BindingInput::GamepadButton(button) => button.into(),
+            BindingInput::GamepadAxis(axis, threshold) if threshold < 0.0 => {
+                SingleAxis::negative_only(axis, threshold).into()
+            }
+            BindingInput::GamepadAxis(axis, threshold) => {
+                SingleAxis::positive_only(axis, threshold).into()
+            }
+            BindingInput::LeftStick => DualAxis::left_stick().into(),
+            BindingInput::RightStick => DualAxis::right_stick().into(),
+            BindingInput::Chord(_) => return None,
+        };
+        Some(kind)
+    }
+
+    /// Same physical key on `layout` as on QWERTY, and back again
+    pub fn remap(&self, layout: KeyboardLayout) -> Self {
+        match self {
+            BindingInput::Key(key) => BindingInput::Key(layout.remap(*key)),
+            BindingInput::Chord(inputs) => {
+                BindingInput::Chord(inputs.iter().map(|input| input.remap(layout)).collect())
+            }
+            input => input.clone(),
+        }
+    }
+
+    /// Inverse of the `UserInput` conversion, `None` for inputs a file can't describe
+    pub fn from_user_input(input: &UserInput) -> Option<Self> {
+        match input {
+            UserInput::Single(kind) => BindingInput::from_input_kind(kind),
+            UserInput::Chord(kinds) => {
+                let inputs = kinds.iter().map(BindingInput::from_input_kind);
+                inputs.collect::<Option<_>>().map(BindingInput::Chord)
+            }
+            _ => None,
+        }
+    }
+
+    fn from_input_kind(kind: &InputKind) -> Option<Self> {
+        match kind {
+            InputKind::Keyboard(key) => Some(BindingInput::Key(*key)),
+            InputKind::Mouse(button) => Some(BindingInput::Mouse(*button)),
+            InputKind::GamepadButton(button) => Some(BindingInput::GamepadButton(*button)),
+            InputKind::SingleAxis(axis) => {
+                let AxisType::Gamepad(axis_type) = axis.axis_type else {
+                    return None;
+                };
+
+                if *axis == SingleAxis::positive_only(axis_type, axis.positive_low) {
+                    Some(BindingInput::GamepadAxis(axis_type, axis.positive_low))
+                } else if *axis == SingleAxis::negative_only(axis_type, axis.negative_low) {
+                    Some(BindingInput::GamepadAxis(axis_type, axis.negative_low))
+                } else {
+                    None
+                }
+            }
+            InputKind::DualAxis(axis) if *axis == DualAxis::mouse_motion() => {
+                Some(BindingInput::MouseMotion)
+            }
+            InputKind::DualAxis(axis) if *axis == DualAxis::mouse_wheel() => {
+                Some(BindingInput::MouseWheel)
+            }
+            InputKind::DualAxis(axis) if *axis == DualAxis::left_stick() => {
+                Some(BindingInput::LeftStick)
+            }
+            InputKind::DualAxis(axis) if *axis == DualAxis::right_stick() => {
+                Some(BindingInput::RightStick)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Default bindings of the player and the debug camera, also loadable as a `.bindings.ron` asset
+#[derive(Resource, TypeUuid, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[uuid = "d3e1a7c4-58b2-4f09-a6e3-1b9c7f24d580"]
+#[serde(default)]
+pub struct Bindings {
+    pub character_movement: Vec<(CharacterMovement, BindingInput)>,
+    pub character_actions: Vec<(CharacterActions, BindingInput)>,
+    #[cfg(feature = "debug_camera")]
+    pub camera_actions: Vec<(CameraAction, BindingInput)>,
+    #[cfg(feature = "debug_camera")]
+    pub camera_movement: Vec<(CameraMovement, BindingInput)>,
+}
+
+fn key(key: KeyCode) -> BindingInput {
+    BindingInput::Key(key)
+}
+
+fn gamepad(button: GamepadButtonType) -> BindingInput {
+    BindingInput::GamepadButton(button)
+}
+
+fn stick(axis: GamepadAxisType, threshold: f32) -> BindingInput {
+    BindingInput::GamepadAxis(axis, threshold)
+}
+
+impl Default for Bindings {
+    fn default() -> Self {
+        use GamepadAxisType::*;
+        use GamepadButtonType::*;
+
+        Bindings {
+            character_movement: vec![
+                (CharacterMovement::Forward, key(KeyCode::W)),
+                (CharacterMovement::Left, key(KeyCode::A)),
+                (CharacterMovement::Back, key(KeyCode::S)),
+                (CharacterMovement::Right, key(KeyCode::D)),
+                (CharacterMovement::Move, BindingInput::LeftStick),
+            ],
+            character_actions: vec![
+                (CharacterActions::Jump, key(KeyCode::Space)),
+                (CharacterActions::Crouch, key(KeyCode::LControl)),
+                (CharacterActions::Sprint, key(KeyCode::LShift)),
+                (CharacterActions::Interact, key(KeyCode::E)),
+                (CharacterActions::Throw, key(KeyCode::G)),
+                (
+                    CharacterActions::Fire,
+                    BindingInput::Mouse(MouseButton::Left),
+                ),
+                (CharacterActions::Jump, gamepad(South)),
+                (CharacterActions::Crouch, gamepad(East)),
+                (CharacterActions::Sprint, gamepad(LeftThumb)),
+                (CharacterActions::Interact, gamepad(West)),
+                (CharacterActions::Throw, gamepad(LeftTrigger)),
+                (CharacterActions::Fire, gamepad(RightTrigger2)),
+            ],
+            #[cfg(feature = "debug_camera")]
+            camera_actions: vec![
+                (CameraAction::Look, BindingInput::RightStick),
+                (CameraAction::Pan, BindingInput::MouseMotion),
+                (CameraAction::Zoom, BindingInput::MouseWheel),
+                (
+                    CameraAction::MoveTrigger,
+                    BindingInput::Mouse(MouseButton::Right),
+                ),
+                (
+                    CameraAction::PanTrigger,
+                    BindingInput::Mouse(MouseButton::Middle),
+                ),
+                (CameraAction::SensTrigger, key(KeyCode::LShift)),
+                (CameraAction::FreeFloatToggle, key(KeyCode::C)),
+                (CameraAction::ViewToggle, key(KeyCode::V)),
+                (CameraAction::CursorToggle, key(KeyCode::Escape)),
+                (CameraAction::ProjectionToggle, key(KeyCode::O)),
+                // Picks what's under the cursor like `Focus`, the left mouse button is `Fire`
+                (CameraAction::Select, key(KeyCode::Q)),
+                (CameraAction::Focus, key(KeyCode::F)),
+                (CameraAction::RollLeft, key(KeyCode::Z)),
+                (CameraAction::RollRight, key(KeyCode::X)),
+                (CameraAction::RollReset, key(KeyCode::R)),
+                (CameraAction::NextCamera, key(KeyCode::Tab)),
+                (CameraAction::SpectatorToggle, key(KeyCode::F8)),
+                (CameraAction::TrackToggle, key(KeyCode::T)),
+                (CameraAction::ViewReset, key(KeyCode::Home)),
+                (CameraAction::SensTrigger, gamepad(RightThumb)),
+                (CameraAction::FreeFloatToggle, gamepad(Select)),
+                (CameraAction::ViewToggle, gamepad(North)),
+                (CameraAction::Focus, gamepad(DPadUp)),
+                (CameraAction::NextCamera, gamepad(RightTrigger)),
+            ],
+            #[cfg(feature = "debug_camera")]
+            camera_movement: vec![
+                (CameraMovement::Forward, key(KeyCode::W)),
+                (CameraMovement::Left, key(KeyCode::A)),
+                (CameraMovement::Back, key(KeyCode::S)),
+                (CameraMovement::Right, key(KeyCode::D)),
+                (CameraMovement::Up, key(KeyCode::Space)),
+                (CameraMovement::Down, key(KeyCode::LControl)),
+                (CameraMovement::Forward, stick(LeftStickY, STICK_THRESHOLD)),
+                (CameraMovement::Back, stick(LeftStickY, -STICK_THRESHOLD)),
+                (CameraMovement::Right, stick(LeftStickX, STICK_THRESHOLD)),
+                (CameraMovement::Left, stick(LeftStickX, -STICK_THRESHOLD)),
+                (CameraMovement::Up, gamepad(RightTrigger2)),
+                (CameraMovement::Down, gamepad(LeftTrigger2)),
+            ],
+        }
+    }
+}
+
+impl Bindings {
+    pub fn character_keymap(&self) -> CharacterKeymap {
+        CharacterKeymap {
+            movement: input_map(&self.character_movement),
+            actions: input_map(&self.character_actions),
+        }
+    }
+
+    #[cfg(feature = "debug_camera")]
+    pub fn camera_action_map(&self) -> InputMap<CameraAction> {
+        input_map(&self.camera_actions)
+    }
+
+    #[cfg(feature = "debug_camera")]
+    pub fn camera_movement_map(&self) -> InputMap<CameraMovement> {
+        input_map(&self.camera_movement)
+    }
+
+    /// Bindings with keys moved to their QWERTY position on `layout`, and back again
+    pub fn for_layout(&self, layout: KeyboardLayout) -> Bindings {
+        fn remap<A: Actionlike>(
+            bindings: &[(A, BindingInput)],
+            layout: KeyboardLayout,
+        ) -> Vec<(A, BindingInput)> {
+            let remap = |(action, input): &(A, BindingInput)| (action.clone(), input.remap(layout));
+            bindings.iter().map(remap).collect()
+        }
+
+        Bindings {
+            character_movement: remap(&self.character_movement, layout),
+            character_actions: remap(&self.character_actions, layout),
+            #[cfg(feature = "debug_camera")]
+            camera_actions: remap(&self.camera_actions, layout),
+            #[cfg(feature = "debug_camera")]
+            camera_movement: remap(&self.camera_movement, layout),
+        }
+    }
+
+    /// Writes the bindings as RON to a file, creating its directory if needed
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), anyhow::Error> {
+        let ron = ron::ser::to_string_pretty(self, Default::default())?;
+        if let Some(dir) = path.as_ref().parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(path, ron)?;
+        Ok(())
+    }
+
+    /// Reads bindings from a RON file outside the asset folder
+    pub fn load(path: impl AsRef<Path>) -> Result<Bindings, anyhow::Error> {
+        let ron = std::fs::read_to_string(path)?;
+        Ok(ron::from_str(&ron)?)
+    }
+}
+
+pub fn input_map<A: Actionlike>(bindings: &[(A, BindingInput)]) -> InputMap<A> {
+    let mut map = InputMap::default();
+    for (action, input) in bindings.iter() {
+        match UserInput::try_from(input.clone()) {
+            Ok(input) => {
+                map.insert(input, action.clone());
+            }
+            Err(error) => warn!("Skipping binding: {error}"),
+        }
+    }
+    map
+}
+
+/// Bindings of an input map, skipping inputs a file can't describe
+pub fn map_bindings<A: Actionlike>(map: &InputMap<A>) -> Vec<(A, BindingInput)> {
+    let mut bindings = Vec::new();
+    for action in A::variants() {
+        for input in map.get(action.clone()).iter() {
+            match BindingInput::from_user_input(input) {
+                Some(input) => bindings.push((action.clone(), input)),
+                None => warn!("Can't save binding {input:?} of {action:?}"),
+            }
+        }
+    }
+    bindings
+}
+
+/// Handle to the bindings asset the plugin keeps in sync
+#[derive(Resource, Deref)]
+pub struct BindingsHandle(pub Handle<Bindings>);
+
+#[derive(Resource)]
+struct BindingsPath(String);
+
+#[derive(Resource)]
+struct UserBindingsPath(PathBuf);
+
+/// Writes the bindings of the first player and the controlled debug camera to the user file
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SaveBindings;
+
+/// Per-user config directory of the crate, e.g. `~/.config/boxxed` on Linux
+pub fn user_config_dir() -> Option<PathBuf> {
+    let base = if cfg!(target_os = "windows") {
+        std::env::var_os("APPDATA").map(PathBuf::from)
+    } else if cfg!(target_os = "macos") {
+        std::env::var_os("HOME").map(|home| Path::new(&home).join("Library/Application Support"))
+    } else {
+        std::env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| Path::new(&home).join(".config")))
+    };
+    base.map(|base| base.join("boxxed"))
+}
+
+/// Prefers the user's saved bindings, falling back to the shipped asset
+fn load_bindings(
+    mut commands: Commands,
+    path: Option<Res<BindingsPath>>,
+    user: Option<Res<UserBindingsPath>>,
+    asset_server: Res<AssetServer>,
+    mut assets: ResMut<Assets<Bindings>>,
+) {
+    if let Some(user) = user.filter(|user| user.0.exists()) {
+        match Bindings::load(&user.0) {
+            Ok(saved) => {
+                info!("Loading bindings from {}", user.0.display());
+                commands.insert_resource(BindingsHandle(assets.add(saved)));
+                return;
+            }
+            Err(error) => warn!("Failed to load {}: {error}", user.0.display()),
+        }
+    }
+
+    if let Some(path) = path {
+        commands.insert_resource(BindingsHandle(asset_server.load(path.0.as_str())));
+    }
+}
+
+/// Replaces `map` unless it was changed away from the `previous` defaults
+fn reapply<A: Actionlike>(map: &mut InputMap<A>, previous: &InputMap<A>, next: InputMap<A>) {
+    if *map == *previous {
+        *map = next;
+    }
+}
+
+type CharacterMaps<'w, 's> = Query<
+    'w,
+    's,
+    (
+        &'static mut InputMap<CharacterMovement>,
+        &'static mut InputMap<CharacterActions>,
+    ),
+>;
+
+#[cfg(feature = "debug_camera")]
+type CameraMaps<'w, 's> = Query<
+    'w,
+    's,
+    (
+        &'static mut InputMap<CameraAction>,
+        &'static mut InputMap<CameraMovement>,
+    ),
+>;
+
+/// Moves every map still on the `previous` defaults over to the `next` ones
+///
+/// Maps differing from the old defaults belong to other players or were rebound.
+fn replace_character_maps(
+    characters: &mut CharacterMaps,
+    keymap: Option<&mut CharacterKeymap>,
+    previous: &Bindings,
+    next: &Bindings,
+) {
+    let (previous, next) = (previous.character_keymap(), next.character_keymap());
+    for (mut movement, mut actions) in characters.iter_mut() {
+        reapply(&mut movement, &previous.movement, next.movement.clone());
+        reapply(&mut actions, &previous.actions, next.actions.clone());
+    }
+    if let Some(keymap) = keymap {
+        *keymap = next;
+    }
+}
+
+#[cfg(feature = "debug_camera")]
+fn replace_camera_maps(cameras: &mut CameraMaps, previous: &Bindings, next: &Bindings) {
+    let (previous_actions, previous_movement) =
+        (previous.camera_action_map(), previous.camera_movement_map());
+    let (actions, movement) = (next.camera_action_map(), next.camera_movement_map());
+    for (mut camera_actions, mut camera_movement) in cameras.iter_mut() {
+        reapply(&mut camera_actions, &previous_actions, actions.clone());
+        reapply(&mut camera_movement, &previous_movement, movement.clone());
+    }
+}
+
+fn apply_bindings(
+    mut events: EventReader<AssetEvent<Bindings>>,
+    mut characters: CharacterMaps,
+    #[cfg(feature = "debug_camera")] mut cameras: CameraMaps,
+    mut bindings: ResMut<Bindings>,
+    mut keymap: Option<ResMut<CharacterKeymap>>,
+    layout: Res<KeyboardLayout>,
+    assets: Res<Assets<Bindings>>,
+    handle: Option<Res<BindingsHandle>>,
+) {
+    let Some(handle) = handle else { return };
+
+    for event in events.iter() {
+        let loaded = match event {
+            AssetEvent::Created { handle } | AssetEvent::Modified { handle } => handle,
+            AssetEvent::Removed { .. } => continue,
+        };
+
+        if *loaded != handle.0 {
+            continue;
+        }
+
+        let Some(loaded) = assets.get(loaded) else {
+            continue;
+        };
+        info!("Applying bindings");
+
+        let previous = bindings.for_layout(*layout);
+        let next = loaded.for_layout(*layout);
+        replace_character_maps(&mut characters, keymap.as_deref_mut(), &previous, &next);
+        #[cfg(feature = "debug_camera")]
+        replace_camera_maps(&mut cameras, &previous, &next);
+
+        *bindings = loaded.clone();
+    }
+}
+
+/// Physical arrangement of the letter keys
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum KeyboardLayout {
+    #[default]
+    Qwerty,
+    /// French, `A` and `Q` as well as `W` and `Z` swapped
+    Azerty,
+    /// German, `Y` and `Z` swapped
+    Qwertz,
+}
+
+impl KeyboardLayout {
+    /// Key at the position `key` has on QWERTY, and back again
+    pub fn remap(&self, key: KeyCode) -> KeyCode {
+        use KeyCode::*;
+
+        match (self, key) {
+            (KeyboardLayout::Azerty, A) => Q,
+            (KeyboardLayout::Azerty, Q) => A,
+            (KeyboardLayout::Azerty, W) => Z,
+            (KeyboardLayout::Azerty, Z) => W,
+            (KeyboardLayout::Qwertz, Y) => Z,
+            (KeyboardLayout::Qwertz, Z) => Y,
+            _ => key,
+        }
+    }
+}
+
+/// Scancodes of the QWERTY `Q`, `W`, `A`, `Y` and `Z` positions
+#[cfg(target_os = "macos")]
+const LETTER_SCANCODES: [u32; 5] = [12, 13, 0, 16, 6];
+#[cfg(not(target_os = "macos"))]
+const LETTER_SCANCODES: [u32; 5] = [16, 17, 30, 21, 44];
+
+/// Layout a key press gives away, if any
+fn layout_of(scan_code: u32, key: KeyCode) -> Option<KeyboardLayout> {
+    let [q, w, a, y, z] = LETTER_SCANCODES;
+
+    match key {
+        KeyCode::A if scan_code == q => Some(KeyboardLayout::Azerty),
+        KeyCode::Z if scan_code == w => Some(KeyboardLayout::Azerty),
+        KeyCode::Q if scan_code == a => Some(KeyboardLayout::Azerty),
+        KeyCode::W if scan_code == z => Some(KeyboardLayout::Azerty),
+        KeyCode::Z if scan_code == y => Some(KeyboardLayout::Qwertz),
+        KeyCode::Y if scan_code == z => Some(KeyboardLayout::Qwertz),
+        KeyCode::Z if scan_code == z => Some(KeyboardLayout::Qwerty),
+        KeyCode::W if scan_code == w => Some(KeyboardLayout::Qwerty),
+        _ => None,
+    }
+}
+
+fn detect_keyboard_layout(
+    mut events: EventReader<KeyboardInput>,
+    mut layout: ResMut<KeyboardLayout>,
+) {
+    for event in events.iter() {
+        let Some(key) = event.key_code else { continue };
+        let Some(detected) = layout_of(event.scan_code, key) else {
+            continue;
+        };
+
+        // QWERTZ shares `W` with QWERTY, only `Y` and `Z` tell them apart
+        let ambiguous = key == KeyCode::W && *layout == KeyboardLayout::Qwertz;
+        if *layout != detected && !ambiguous {
+            info!("Detected {detected:?} keyboard layout");
+            *layout = detected;
+        }
+    }
+}
+
+/// Moves the bindings to the same physical keys whenever the layout changes
+fn apply_keyboard_layout(
+    mut characters: CharacterMaps,
+    #[cfg(feature = "debug_camera")] mut cameras: CameraMaps,
+    mut keymap: Option<ResMut<CharacterKeymap>>,
+    mut applied: Local<KeyboardLayout>,
+    bindings: Res<Bindings>,
+    layout: Res<KeyboardLayout>,
+) {
+    if *layout == *applied {
+        return;
+    }
+
+    let previous = bindings.for_layout(*applied);
+    let next = bindings.for_layout(*layout);
+    replace_character_maps(&mut characters, keymap.as_deref_mut(), &previous, &next);
+    #[cfg(feature = "debug_camera")]
+    replace_camera_maps(&mut cameras, &previous, &next);
+
+    *applied = *layout;
+}
+
+fn save_on_rebind<A: Actionlike>(
+    mut outcomes: EventReader<RebindOutcome<A>>,
+    mut save: EventWriter<SaveBindings>,
+) {
+    let bound = outcomes
+        .iter()
+        .filter(|outcome| matches!(outcome, RebindOutcome::Bound { .. }))
+        .count();
+    if bound > 0 {
+        save.send(SaveBindings);
+    }
+}
+
+/// Takes the bindings from the first player and the controlled debug camera
+#[allow(clippy::type_complexity)]
+fn save_bindings(
+    mut events: EventReader<SaveBindings>,
+    characters: Query<
+        (
+            Entity,
+            &InputMap<CharacterMovement>,
+            &InputMap<CharacterActions>,
+        ),
+        (
+            With<CharacterMovementController>,
+            Without<CharacterInputDriver>,
+        ),
+    >,
+    #[cfg(feature = "debug_camera")] views: Query<&SplitScreenView>,
+    #[cfg(feature = "debug_camera")] cameras: Query<
+        (&InputMap<CameraAction>, &InputMap<CameraMovement>),
+        With<ControlledCamera>,
+    >,
+    mut bindings: ResMut<Bindings>,
+    layout: Res<KeyboardLayout>,
+    path: Option<Res<UserBindingsPath>>,
+) {
+    if events.iter().count() == 0 {
+        return;
+    }
+
+    // Maps hold the keys of the current layout, the file their QWERTY positions
+    let mut saved = bindings.for_layout(*layout);
+    // With split screen the leftmost view belongs to player one, otherwise the oldest player
+    let first = characters.iter().min_by_key(|(entity, ..)| *entity);
+    #[cfg(feature = "debug_camera")]
+    let first = views
+        .iter()
+        .min_by_key(|view| view.index)
+        .and_then(|view| characters.get(view.player).ok())
+        .or(first);
+    if let Some((_, movement, actions)) = first {
+        saved.character_movement = map_bindings(movement);
+        saved.character_actions = map_bindings(actions);
+    }
+    #[cfg(feature = "debug_camera")]
+    if let Ok((actions, movement)) = cameras.get_single() {
+        saved.camera_actions = map_bindings(actions);
+        saved.camera_movement = map_bindings(movement);
+    }
+    *bindings = saved.for_layout(*layout);
+
+    let Some(path) = path else {
+        warn!("No user file to save bindings to");
+        return;
+    };
+
+    // The shipped asset stays untouched, the user file takes over on the next start
+    match bindings.save(&path.0) {
+        Ok(()) => info!("Saved bindings to {}", path.0.display()),
+        Err(error) => error!("Failed to save bindings: {error}"),
+    }
+}
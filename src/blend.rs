@@ -0,0 +1,117 @@
+use crate::prelude::*;
+use bevy::{prelude::*, render::camera::Projection};
+
+/// Eases the camera from where it was to where the new state puts it on every state change
+///
+/// While blending it owns the final camera transform and field of view; the state systems
+/// keep running underneath and only their result is blended towards.
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct CameraBlend {
+    /// Seconds, zero snaps
+    pub duration: f32,
+    elapsed: f32,
+    state: Option<CameraState>,
+    from: Option<(GlobalTransform, Option<f32>)>,
+    applied_fov: f32,
+}
+
+impl Default for CameraBlend {
+    fn default() -> Self {
+        CameraBlend {
+            duration: 0.35,
+            elapsed: 0.0,
+            state: None,
+            from: None,
+            applied_fov: 0.0,
+        }
+    }
+}
+
+impl CameraBlend {
+    pub fn blending(&self) -> bool {
+        self.from.is_some()
+    }
+}
+
+pub fn start_camera_blend(
+    mut q: Query<(&mut CameraBlend, &GlobalTransform, &Projection)>,
+    state: Res<State<CameraState>>,
+) {
+    let current = *state.current();
+
+    for (mut blend, transform, projection) in q.iter_mut() {
+        let previous = blend.state.replace(current);
+        if previous.is_none() || previous == Some(current) || blend.duration <= 0.0 {
+            continue;
+        }
+
+        // Blending from wherever the last blend got to
+        let fov = match projection {
+            Projection::Perspective(perspective) => Some(perspective.fov),
+            Projection::Orthographic(_) => None,
+        };
+        blend.from = Some((*transform, fov));
+        blend.elapsed = 0.0;
+    }
+}
+
+/// Takes last frame's field of view offset back out, so zoom and kicks keep their own value
+pub fn remove_blend_fov(mut q: Query<(&mut CameraBlend, &mut Projection)>) {
+    for (mut blend, mut projection) in q.iter_mut() {
+        if let Projection::Perspective(perspective) = projection.as_mut() {
+            perspective.fov -= blend.applied_fov;
+        }
+        blend.applied_fov = 0.0;
+    }
+}
+
+pub fn blend_camera(
+    mut q: Query<(
+        &mut CameraBlend,
+        &mut Transform,
+        &mut Projection,
+        Option<&Parent>,
+    )>,
+    parents: Query<&GlobalTransform>,
+    time: Res<Time>,
+) {
+    for (mut blend, mut transform, mut projection, parent) in q.iter_mut() {
+        let Some((from, from_fov)) = blend.from else {
+            continue;
+        };
+
+        blend.elapsed += time.delta_seconds();
+        let t = (blend.elapsed / blend.duration).clamp(0.0, 1.0);
+        let t = t * t * (3.0 - 2.0 * t);
+        if t >= 1.0 {
+            blend.from = None;
+        }
+
+        // Blend in world space, attached cameras only know their local transform
+        let parent = parent.and_then(|parent| parents.get(parent.get()).ok());
+        let target = match parent {
+            Some(parent) => parent.mul_transform(*transform).compute_transform(),
+            None => *transform,
+        };
+        let from = from.compute_transform();
+        let blended = Transform {
+            translation: from.translation.lerp(target.translation, t),
+            rotation: from.rotation.slerp(target.rotation, t),
+            scale: target.scale,
+        };
+
+        *transform = match parent {
+            Some(parent) => {
+                Transform::from_matrix(parent.compute_matrix().inverse() * blended.compute_matrix())
+            }
+            None => blended,
+        };
+
+        let Some(from_fov) = from_fov else { continue };
+        if let Projection::Perspective(perspective) = projection.as_mut() {
+            let fov = from_fov + (perspective.fov - from_fov) * t;
+            blend.applied_fov = fov - perspective.fov;
+            perspective.fov = fov;
+        }
+    }
+}
@@ -0,0 +1,151 @@
+use crate::prelude::*;
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+/// Brings back characters that left the `WorldBounds` or ended up with a broken transform
+///
+/// They're put where they last stood on the ground and an `OutOfBounds` event is sent.
+pub struct BoundsPlugin;
+
+impl Plugin for BoundsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<WorldBounds>()
+            .add_event::<OutOfBounds>()
+            .add_system(recover_out_of_bounds.after(update_grounded));
+    }
+}
+
+/// Box characters have to stay in, its bottom being the world floor
+#[derive(Resource, Debug, Clone, Copy, PartialEq)]
+pub struct WorldBounds {
+    pub min: Vec3,
+    pub max: Vec3,
+}
+
+impl Default for WorldBounds {
+    fn default() -> Self {
+        WorldBounds {
+            min: Vec3::new(-10_000.0, -1_000.0, -10_000.0),
+            max: Vec3::new(10_000.0, 10_000.0, 10_000.0),
+        }
+    }
+}
+
+impl WorldBounds {
+    pub fn new(min: Vec3, max: Vec3) -> Self {
+        WorldBounds { min, max }
+    }
+
+    fn check(&self, transform: &Transform, velocity: &Velocity) -> Option<OutOfBoundsReason> {
+        let position = transform.translation;
+        let finite = position.is_finite()
+            && transform.rotation.is_finite()
+            && velocity.linvel.is_finite()
+            && velocity.angvel.is_finite();
+
+        if !finite {
+            Some(OutOfBoundsReason::NonFinite)
+        } else if position.y < self.min.y {
+            Some(OutOfBoundsReason::BelowFloor)
+        } else if position.cmplt(self.min).any() || position.cmpgt(self.max).any() {
+            Some(OutOfBoundsReason::OutsideBounds)
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutOfBoundsReason {
+    /// Position, rotation or velocity turned NaN or infinite
+    NonFinite,
+    BelowFloor,
+    OutsideBounds,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OutOfBounds {
+    pub entity: Entity,
+    pub reason: OutOfBoundsReason,
+    /// Where the character was found, possibly not finite
+    pub position: Vec3,
+    pub recovered_at: Vec3,
+}
+
+/// Where the character last stood on the ground inside the bounds
+#[derive(Component, Debug, Default, Clone, Copy, PartialEq)]
+pub struct LastValidPosition(Option<Vec3>);
+
+impl LastValidPosition {
+    pub fn get(&self) -> Option<Vec3> {
+        self.0
+    }
+}
+
+#[allow(clippy::type_complexity)]
+pub fn recover_out_of_bounds(
+    mut characters: Query<(
+        Entity,
+        &mut Transform,
+        &mut Velocity,
+        &mut LastValidPosition,
+        &mut CharacterMovementController,
+        &mut CharacterSpeed,
+        &mut CharacterState,
+        &mut CharacterInterpolation,
+        &mut ExternalForces,
+        Option<&mut KinematicCharacterController>,
+    )>,
+    mut events: EventWriter<OutOfBounds>,
+    bounds: Res<WorldBounds>,
+) {
+    for components in characters.iter_mut() {
+        let (
+            entity,
+            mut transform,
+            mut velocity,
+            mut last_valid,
+            mut character,
+            mut speed,
+            mut state,
+            mut interpolation,
+            mut external,
+            controller,
+        ) = components;
+
+        let Some(reason) = bounds.check(&transform, &velocity) else {
+            if character.grounded() {
+                last_valid.0 = Some(transform.translation);
+            }
+            continue;
+        };
+
+        let position = transform.translation;
+        let recovered_at = last_valid.0.unwrap_or(Vec3::ZERO);
+
+        if !transform.rotation.is_finite() {
+            transform.rotation = Quat::IDENTITY;
+        }
+        transform.translation = recovered_at;
+        character.reset();
+        speed.0 = character.settings().speed.base;
+        *velocity = Velocity::zero();
+        *state = CharacterState::Idle;
+        interpolation.reset(recovered_at);
+        if !external.0.is_finite() {
+            external.0 = Vec3::ZERO;
+        }
+        // Movement queued from the broken position would carry the character right back
+        if let Some(mut controller) = controller {
+            controller.translation = None;
+        }
+
+        warn!("{entity:?} out of bounds ({reason:?}) at {position}, recovered at {recovered_at}");
+        events.send(OutOfBounds {
+            entity,
+            reason,
+            position,
+            recovered_at,
+        });
+    }
+}
@@ -0,0 +1,251 @@
+use crate::prelude::*;
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+/// Breaks `Breakable` bodies into debris once enough `Impact`s wore them down
+///
+/// Impacts come from contact forces and character landings, other systems like explosions can
+/// send their own.
+pub struct BreakablePlugin;
+
+impl Plugin for BreakablePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<Impact>()
+            .add_event::<BoxBroken>()
+            .add_system(setup_breakables)
+            .add_system_set(
+                SystemSet::new()
+                    .with_run_criteria(simulation_running)
+                    .with_system(detect_contact_impacts)
+                    .with_system(detect_landing_impacts.after(update_player_state))
+                    .with_system(
+                        break_boxes
+                            .after(detect_contact_impacts)
+                            .after(detect_landing_impacts),
+                    )
+                    .with_system(despawn_debris),
+            );
+    }
+}
+
+/// Body that loses health to strong impacts and shatters into debris at none left
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct Breakable {
+    pub health: f32,
+    /// Impulse absorbed by every impact, weaker ones do no damage, in kilogram meters per second
+    pub min_impulse: f32,
+    /// Debris along every axis of the box
+    pub pieces: u32,
+    /// Seconds until the debris disappears
+    pub debris_lifetime: f32,
+}
+
+impl Default for Breakable {
+    fn default() -> Self {
+        Breakable {
+            health: 600.0,
+            min_impulse: 150.0,
+            pieces: 2,
+            debris_lifetime: 5.0,
+        }
+    }
+}
+
+impl Breakable {
+    pub fn new(health: f32) -> Self {
+        Breakable {
+            health,
+            ..Default::default()
+        }
+    }
+
+    pub fn with_min_impulse(mut self, min_impulse: f32) -> Self {
+        self.min_impulse = min_impulse;
+        self
+    }
+}
+
+/// Piece of a broken box
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct Debris {
+    /// Seconds left until it's despawned
+    pub remaining: f32,
+}
+
+/// Something hit `target` with `impulse`, in kilogram meters per second
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Impact {
+    pub target: Entity,
+    pub impulse: f32,
+}
+
+/// A breakable was destroyed, e.g. for sounds and effects
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BoxBroken {
+    pub entity: Entity,
+    pub translation: Vec3,
+}
+
+fn setup_breakables(
+    mut commands: Commands,
+    q: Query<(Entity, &Breakable, Option<&ActiveEvents>), Added<Breakable>>,
+    rapier: Res<RapierContext>,
+) {
+    for (entity, breakable, events) in q.iter() {
+        // Rapier reports forces, the impulse of a step is what breaks boxes
+        let dt = rapier.integration_parameters.dt;
+        let events = events.copied().unwrap_or_default() | ActiveEvents::CONTACT_FORCE_EVENTS;
+        commands
+            .entity(entity)
+            .insert(events)
+            .insert(ContactForceEventThreshold(breakable.min_impulse / dt));
+    }
+}
+
+pub fn detect_contact_impacts(
+    mut contacts: EventReader<ContactForceEvent>,
+    mut impacts: EventWriter<Impact>,
+    breakables: Query<(), With<Breakable>>,
+    rapier: Res<RapierContext>,
+) {
+    let dt = rapier.integration_parameters.dt;
+
+    for contact in contacts.iter() {
+        let impulse = contact.total_force_magnitude * dt;
+        for collider in [contact.collider1, contact.collider2] {
+            // Colliders may be children of the body they belong to
+            let target = rapier.collider_parent(collider).unwrap_or(collider);
+            if breakables.contains(target) {
+                impacts.send(Impact { target, impulse });
+            }
+        }
+    }
+}
+
+/// The kinematic character controller lands softly, so its fall speed is what hits the ground
+pub fn detect_landing_impacts(
+    mut changes: EventReader<CharacterStateChanged>,
+    mut impacts: EventWriter<Impact>,
+    characters: Query<(&CharacterMovementController, &Grounded)>,
+    breakables: Query<(), With<Breakable>>,
+    rapier: Res<RapierContext>,
+) {
+    for change in changes.iter() {
+        let grounded = change.to.group() == CharacterStateGroup::Grounded;
+        if change.from != CharacterState::Fall || !grounded {
+            continue;
+        }
+        let Ok((character, ground)) = characters.get(change.entity) else {
+            continue;
+        };
+        let Some(collider) = ground.ground() else {
+            continue;
+        };
+
+        let target = rapier.collider_parent(collider).unwrap_or(collider);
+        if breakables.contains(target) {
+            // Gravity is still the fall velocity until the next character step clears it
+            let speed = (-character.forces().gravity().y).max(0.0);
+            let impulse = character.settings().mass * speed;
+            impacts.send(Impact { target, impulse });
+        }
+    }
+}
+
+#[allow(clippy::type_complexity)]
+pub fn break_boxes(
+    mut commands: Commands,
+    mut impacts: EventReader<Impact>,
+    mut boxes: Query<(
+        &mut Breakable,
+        &Transform,
+        Option<&Collider>,
+        Option<&Velocity>,
+        Option<&Handle<StandardMaterial>>,
+    )>,
+    mut broken: EventWriter<BoxBroken>,
+    mut meshes: ResMut<Assets<Mesh>>,
+) {
+    for &Impact { target, impulse } in impacts.iter() {
+        let Ok((mut breakable, transform, shape, velocity, material)) = boxes.get_mut(target)
+        else {
+            continue;
+        };
+        // Already broken by an earlier impact this frame
+        if breakable.health <= 0.0 {
+            continue;
+        }
+
+        breakable.health -= (impulse - breakable.min_impulse).max(0.0);
+        if breakable.health > 0.0 {
+            continue;
+        }
+
+        let half_extents = shape
+            .and_then(|shape| shape.as_cuboid())
+            .map_or(Vec3::splat(0.5), |cuboid| cuboid.half_extents())
+            * transform.scale;
+        let pieces = breakable.pieces.max(1);
+        let piece = half_extents / pieces as f32;
+        let velocity = velocity.copied().unwrap_or_default();
+        let mesh = material.map(|_| {
+            let size = piece * 2.0;
+            meshes.add(Mesh::from(shape::Box::new(size.x, size.y, size.z)))
+        });
+
+        for x in 0..pieces {
+            for y in 0..pieces {
+                for z in 0..pieces {
+                    let cell = Vec3::new(x as f32, y as f32, z as f32) * 2.0 + 1.0;
+                    let offset = transform.rotation * (cell * piece - half_extents);
+                    let translation = transform.translation + offset;
+                    let pose = transform
+                        .with_translation(translation)
+                        .with_scale(Vec3::ONE);
+
+                    // Pieces fly apart from the middle of the box
+                    let linvel = velocity.linvel + offset.normalize_or_zero() * 2.0;
+                    let mut debris = commands.spawn((
+                        RigidBody::Dynamic,
+                        Collider::cuboid(piece.x, piece.y, piece.z),
+                        Velocity {
+                            linvel,
+                            angvel: velocity.angvel,
+                        },
+                        Debris {
+                            remaining: breakable.debris_lifetime,
+                        },
+                    ));
+                    match (&mesh, material) {
+                        (Some(mesh), Some(material)) => debris.insert(PbrBundle {
+                            mesh: mesh.clone(),
+                            material: material.clone(),
+                            transform: pose,
+                            ..Default::default()
+                        }),
+                        _ => debris.insert(TransformBundle::from(pose)),
+                    };
+                }
+            }
+        }
+
+        commands.entity(target).despawn_recursive();
+        broken.send(BoxBroken {
+            entity: target,
+            translation: transform.translation,
+        });
+    }
+}
+
+fn despawn_debris(
+    mut commands: Commands,
+    mut debris: Query<(Entity, &mut Debris)>,
+    time: Res<Time>,
+) {
+    for (entity, mut debris) in debris.iter_mut() {
+        debris.remaining -= time.delta_seconds();
+        if debris.remaining <= 0.0 {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
@@ -0,0 +1,106 @@
+use crate::prelude::*;
+use bevy::{prelude::*, utils::HashMap};
+use leafwing_input_manager::{plugin::InputManagerSystem, prelude::*};
+use std::marker::PhantomData;
+
+/// Records presses of `A` into every `ActionBuffer<A>`
+pub struct ActionBufferPlugin<A: Actionlike> {
+    _marker: PhantomData<A>,
+}
+
+impl<A: Actionlike> Default for ActionBufferPlugin<A> {
+    fn default() -> Self {
+        ActionBufferPlugin {
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<A: Actionlike> Plugin for ActionBufferPlugin<A> {
+    fn build(&self, app: &mut App) {
+        init_simulation_time(app);
+        app.add_system_to_stage(
+            CoreStage::PreUpdate,
+            record_action_buffer::<A>
+                .label(ActionBufferSystem)
+                .after(InputManagerSystem::Update),
+        );
+    }
+}
+
+/// Systems writing action states must run before this to have their presses buffered
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, SystemLabel)]
+pub struct ActionBufferSystem;
+
+/// Remembers presses for a while, so "pressed within the last N seconds" can be asked
+///
+/// Only actions given a window are buffered.
+#[derive(Component, Debug, Clone, PartialEq)]
+pub struct ActionBuffer<A: Actionlike> {
+    windows: HashMap<A, f32>,
+    /// `SimulationTime` of the last unconsumed press
+    pressed_at: HashMap<A, f32>,
+    now: f32,
+}
+
+impl<A: Actionlike> Default for ActionBuffer<A> {
+    fn default() -> Self {
+        ActionBuffer {
+            windows: HashMap::default(),
+            pressed_at: HashMap::default(),
+            now: 0.0,
+        }
+    }
+}
+
+impl<A: Actionlike> ActionBuffer<A> {
+    /// Keeps presses of `action` for `seconds`
+    pub fn with_window(mut self, action: A, seconds: f32) -> Self {
+        self.windows.insert(action, seconds);
+        self
+    }
+
+    pub fn window(&self, action: &A) -> Option<f32> {
+        self.windows.get(action).copied()
+    }
+
+    /// Whether `action` was pressed within its window and not consumed since
+    pub fn buffered(&self, action: &A) -> bool {
+        match (self.pressed_at.get(action), self.window(action)) {
+            (Some(pressed_at), Some(window)) => self.now - pressed_at <= window,
+            _ => false,
+        }
+    }
+
+    /// Forgets the last press, so it only triggers one thing
+    pub fn consume(&mut self, action: &A) -> bool {
+        let buffered = self.buffered(action);
+        self.pressed_at.remove(action);
+        buffered
+    }
+
+    pub fn clear(&mut self) {
+        self.pressed_at.clear();
+    }
+}
+
+pub fn record_action_buffer<A: Actionlike>(
+    mut q: Query<(&mut ActionBuffer<A>, &ActionState<A>)>,
+    time: Res<SimulationTime>,
+) {
+    let now = time.elapsed_seconds();
+
+    for (mut buffer, actions) in q.iter_mut() {
+        buffer.now = now;
+
+        let pressed: Vec<A> = buffer
+            .windows
+            .keys()
+            .filter(|action| actions.just_pressed((*action).clone()))
+            .cloned()
+            .collect();
+        for action in pressed {
+            buffer.pressed_at.insert(action, now);
+        }
+    }
+}
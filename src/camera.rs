@@ -1,14 +1,84 @@
-use bevy::{prelude::*, render::camera::Projection};
-use leafwing_input_manager::prelude::*;
-use std::fmt::Debug;
+use crate::prelude::*;
+use bevy::{
+    prelude::*,
+    render::camera::{Projection, ScalingMode},
+    transform::TransformSystem,
+};
+use leafwing_input_manager::{plugin::InputManagerSystem, prelude::*};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    f32::consts::{PI, TAU},
+    fmt::Debug,
+};
+
+/// How far input moves, turns and zooms the camera
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Sensitivity {
+    pub move_sens: f32,
+    pub look_sens: f32,
+    pub zoom_sens: f32,
+}
+
+impl Default for Sensitivity {
+    fn default() -> Self {
+        Sensitivity {
+            move_sens: 0.005,
+            look_sens: 0.005,
+            zoom_sens: 0.1,
+        }
+    }
+}
 
 #[derive(Component)]
 pub struct DebugCamera {
     pub focus: Vec3,
     pub radius: f32,
-    pub move_sens: f32,
-    pub look_sens: f32,
-    pub zoom_sens: f32,
+    /// Used in states without a profile of their own
+    pub sensitivity: Sensitivity,
+    pub profiles: HashMap<CameraState, Sensitivity>,
+    /// Multiplier on `move_sens` and the fly speed while `CameraAction::SensTrigger` is held
+    pub sens_boost: f32,
+    current: Sensitivity,
+    /// Base free-float speed, in meters per second
+    pub fly_speed: f32,
+    /// Scroll wheel limits of `fly_speed`
+    pub min_fly_speed: f32,
+    pub max_fly_speed: f32,
+    /// Base speed gained per second of holding movement
+    pub fly_ramp: f32,
+    /// Upper bound of the ramp, as a multiple of base speed
+    pub max_fly_ramp: f32,
+    fly_held: f32,
+    /// Coast to a stop instead of halting as soon as movement is released
+    pub fly_inertia: bool,
+    /// How fast the fly velocity reaches the held speed, per second
+    pub fly_acceleration: f32,
+    /// How fast the fly velocity dies down once released, per second
+    pub fly_drag: f32,
+    fly_velocity: Vec3,
+    /// Field of view limits of the free-float zoom, in degrees
+    pub min_fov: f32,
+    pub max_fov: f32,
+    /// Focus distance limits of the dolly zoom
+    pub min_radius: f32,
+    pub max_radius: f32,
+    /// Radians, around the global up axis
+    pub yaw: f32,
+    /// Radians, clamped to `max_pitch`
+    pub pitch: f32,
+    /// Degrees
+    pub max_pitch: f32,
+    /// Radians, around the view axis
+    pub roll: f32,
+    /// Degrees per second
+    pub roll_speed: f32,
+    /// How fast the camera catches up with its goal position, per second
+    pub position_damping: f32,
+    /// How fast the camera catches up with its goal rotation, per second
+    pub rotation_damping: f32,
+    /// Field of view restored when leaving orthographic projection, radians
+    pub perspective_fov: f32,
     pub upside_down: bool,
 }
 
@@ -17,51 +87,326 @@ impl Default for DebugCamera {
         DebugCamera {
             focus: Vec3::ZERO,
             radius: 5.0,
-            move_sens: 0.005,
-            look_sens: 0.005,
-            zoom_sens: 0.1,
+            sensitivity: Default::default(),
+            // Slower in the editor for precise placement
+            profiles: HashMap::from([(
+                CameraState::Editor,
+                Sensitivity {
+                    move_sens: 0.0025,
+                    look_sens: 0.003,
+                    zoom_sens: 0.05,
+                },
+            )]),
+            sens_boost: 5.0,
+            current: Default::default(),
+            fly_speed: 5.0,
+            min_fly_speed: 0.25,
+            max_fly_speed: 200.0,
+            fly_ramp: 1.0,
+            max_fly_ramp: 4.0,
+            fly_held: 0.0,
+            fly_inertia: true,
+            fly_acceleration: 10.0,
+            fly_drag: 5.0,
+            fly_velocity: Vec3::ZERO,
+            min_fov: 15.0,
+            max_fov: 110.0,
+            min_radius: 0.5,
+            max_radius: 500.0,
+            yaw: 0.0,
+            pitch: 0.0,
+            max_pitch: 89.0,
+            roll: 0.0,
+            roll_speed: 90.0,
+            position_damping: 20.0,
+            rotation_damping: 30.0,
+            perspective_fov: PerspectiveProjection::default().fov,
             upside_down: false,
         }
     }
 }
 
-pub struct DebugCameraPlugin;
+impl DebugCamera {
+    /// Sensitivity of the current state, boost included
+    pub fn move_sens(&self) -> f32 {
+        self.current.move_sens
+    }
+
+    pub fn look_sens(&self) -> f32 {
+        self.current.look_sens
+    }
+
+    pub fn zoom_sens(&self) -> f32 {
+        self.current.zoom_sens
+    }
+
+    pub fn profile(&self, state: CameraState) -> Sensitivity {
+        self.profiles
+            .get(&state)
+            .copied()
+            .unwrap_or(self.sensitivity)
+    }
+
+    /// Rotation built from yaw, pitch and roll
+    pub fn rotation(&self) -> Quat {
+        Quat::from_euler(EulerRot::YXZ, self.yaw, self.pitch, self.roll)
+    }
+
+    /// Takes over yaw and pitch of `rotation`, leveling the roll
+    pub fn set_rotation(&mut self, rotation: Quat) {
+        let (yaw, pitch, _) = rotation.to_euler(EulerRot::YXZ);
+        let max_pitch = self.max_pitch.to_radians();
+        self.yaw = yaw;
+        self.pitch = pitch.clamp(-max_pitch, max_pitch);
+        self.roll = 0.0;
+    }
+}
+
+pub struct DebugCameraPlugin {
+    default_spawn: bool,
+    config: CameraConfig,
+    config_path: Option<String>,
+}
+
+impl Default for DebugCameraPlugin {
+    fn default() -> Self {
+        DebugCameraPlugin {
+            default_spawn: true,
+            config: Default::default(),
+            config_path: None,
+        }
+    }
+}
+
+impl DebugCameraPlugin {
+    /// Whether the plugin spawns a camera on startup, disable to insert
+    /// `DebugCameraBundle` on a camera of your own
+    pub fn with_default_spawn(mut self, enabled: bool) -> Self {
+        self.default_spawn = enabled;
+        self
+    }
+
+    /// Settings and bindings of the spawned camera, available as a resource
+    pub fn with_config(mut self, config: CameraConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Load the config from a `.camera.ron` asset, reapplied whenever it changes
+    pub fn with_config_asset(mut self, path: impl Into<String>) -> Self {
+        self.config_path = Some(path.into());
+        self
+    }
+}
+
 impl Plugin for DebugCameraPlugin {
     fn build(&self, app: &mut App) {
-        app.add_startup_system(spawn_camera)
-            .add_state(CameraState::FreeFloat)
+        init_simulation_time(app);
+        if let Some(path) = &self.config_path {
+            app.insert_resource(CameraConfigPath(path.clone()))
+                .add_startup_system(load_camera_config);
+        }
+
+        if self.default_spawn {
+            app.add_startup_system(spawn_camera);
+        }
+
+        app.add_asset::<CameraConfig>()
+            .add_asset_loader(RonAssetLoader::<CameraConfig>::new(&["camera.ron"]))
+            .insert_resource(self.config.clone())
+            .init_resource::<Bindings>()
+            .add_system(apply_camera_config)
+            .add_state(self.config.default_state)
             .add_plugin(InputManagerPlugin::<CameraAction>::default())
             .add_plugin(InputManagerPlugin::<CameraMovement>::default())
+            .add_plugin(RebindPlugin::<CameraAction>::default())
+            .add_plugin(RebindPlugin::<CameraMovement>::default())
+            .init_resource::<ActiveCamera>()
+            .add_system_to_stage(
+                CoreStage::PreUpdate,
+                switch_active_camera.after(InputManagerSystem::Update),
+            )
+            .add_system_to_stage(
+                CoreStage::PreUpdate,
+                update_camera_sensitivity.after(InputManagerSystem::Update),
+            )
+            .init_resource::<InputSettings>()
+            .add_system_to_stage(
+                CoreStage::PreUpdate,
+                update_look_input.after(InputManagerSystem::Update),
+            )
             .add_system(update_camera_state)
+            .add_system(update_camera_projection)
+            .add_system(reset_camera_view.after(update_camera_zoom))
             .add_system_set(
                 SystemSet::on_update(CameraState::Locked).with_system(update_camera_pos),
             )
-            .add_system_set(SystemSet::on_update(CameraState::Fps).with_system(update_camera_rot))
+            .add_system_set(SystemSet::on_enter(CameraState::Fps).with_system(attach_fps_rig))
+            .add_system_set(SystemSet::on_update(CameraState::Fps).with_system(update_fps_rig))
+            .add_system_set(SystemSet::on_exit(CameraState::Fps).with_system(detach_fps_rig))
+            // Cinematics are pushed on top, rigs let go of the camera meanwhile
+            .add_system_set(SystemSet::on_pause(CameraState::Fps).with_system(detach_fps_rig))
+            .add_system_set(SystemSet::on_resume(CameraState::Fps).with_system(attach_fps_rig))
+            .add_system_set(
+                SystemSet::on_enter(CameraState::ThirdPerson).with_system(attach_third_person_rig),
+            )
+            .add_system_set(
+                SystemSet::on_update(CameraState::ThirdPerson).with_system(update_third_person_rig),
+            )
+            .add_system_set(
+                SystemSet::on_exit(CameraState::ThirdPerson).with_system(detach_third_person_rig),
+            )
+            .add_system_set(
+                SystemSet::on_pause(CameraState::ThirdPerson).with_system(detach_third_person_rig),
+            )
+            .add_system_set(
+                SystemSet::on_resume(CameraState::ThirdPerson).with_system(attach_third_person_rig),
+            )
             .add_system_set(
                 SystemSet::on_update(CameraState::Editor)
+                    .with_system(select_under_cursor)
+                    .with_system(focus_selection)
+                    .with_system(update_fly_speed)
+                    .with_system(update_camera_dolly)
                     .with_system(update_camera_rot)
                     .with_system(update_camera_pos)
                     .with_system(update_camera_pan),
             )
+            .add_system_set(SystemSet::on_enter(CameraState::Orbit).with_system(enter_orbit))
+            .add_system_set(
+                SystemSet::on_update(CameraState::Orbit)
+                    .with_system(select_under_cursor)
+                    .with_system(focus_selection)
+                    .with_system(update_camera_dolly)
+                    .with_system(update_camera_orbit.after(update_camera_dolly)),
+            )
+            .add_system_set(SystemSet::on_exit(CameraState::Orbit).with_system(exit_orbit))
             .add_system_set(
                 SystemSet::on_update(CameraState::FreeFloat)
+                    .with_system(focus_selection)
+                    .with_system(update_fly_speed)
                     .with_system(update_camera_zoom)
                     .with_system(update_camera_pos)
-                    .with_system(update_camera_rot)
+                    .with_system(update_camera_roll)
+                    .with_system(update_camera_rot.after(update_camera_roll))
                     .with_system(update_camera_pan),
+            )
+            .init_resource::<Spectating>()
+            .add_system(toggle_spectator.after(update_camera_state))
+            .add_system_set(
+                SystemSet::on_enter(CameraState::Spectator).with_system(freeze_player_input),
+            )
+            .add_system_set(
+                SystemSet::on_update(CameraState::Spectator)
+                    .with_system(update_fly_speed)
+                    .with_system(update_camera_pos)
+                    .with_system(update_camera_roll)
+                    .with_system(update_camera_rot.after(update_camera_roll)),
+            )
+            .add_system_set(
+                SystemSet::on_exit(CameraState::Spectator).with_system(unfreeze_player_input),
+            )
+            .add_event::<CameraShake>()
+            .add_system(receive_camera_shake)
+            .add_system(shake_on_landing)
+            .add_system(update_fov_kick.after(update_camera_zoom))
+            .add_system(toggle_camera_tracking)
+            .add_system(
+                update_camera_tracking
+                    .after(update_camera_pos)
+                    .after(update_camera_rot)
+                    .after(update_camera_orbit),
+            )
+            .add_system(setup_picture_in_picture)
+            .add_system(cleanup_picture_in_picture)
+            .add_event::<DollyZoom>()
+            .add_system(start_dolly_zoom)
+            .add_system(update_dolly_zoom.after(update_camera_zoom))
+            .add_event::<PlayCameraPath>()
+            .add_event::<CameraPathStarted>()
+            .add_event::<CameraPathFinished>()
+            .add_system(start_camera_paths)
+            .add_system_set(
+                SystemSet::on_update(CameraState::Cinematic).with_system(play_camera_paths),
+            )
+            .add_system_set(
+                SystemSet::on_exit(CameraState::Cinematic).with_system(stop_camera_paths),
+            )
+            .add_event::<HudMessage>()
+            .add_startup_system(spawn_hud)
+            .add_system(update_hud)
+            .init_resource::<EditorSelection>()
+            .init_resource::<HeadBobSettings>()
+            .init_resource::<CursorGrab>()
+            .add_system(update_cursor_grab.after(update_camera_state))
+            .add_system(start_camera_blend.after(update_camera_state))
+            .add_system_to_stage(CoreStage::PreUpdate, remove_blend_fov)
+            .add_system_to_stage(CoreStage::PreUpdate, remove_camera_shake)
+            .add_system_to_stage(CoreStage::PreUpdate, remove_head_bob)
+            .add_system_to_stage(
+                CoreStage::PostUpdate,
+                damp_camera.before(TransformSystem::TransformPropagate),
+            )
+            .add_system_to_stage(
+                CoreStage::PostUpdate,
+                blend_camera
+                    .after(damp_camera)
+                    .before(TransformSystem::TransformPropagate),
+            )
+            .add_system_to_stage(
+                CoreStage::PostUpdate,
+                apply_camera_shake
+                    .after(blend_camera)
+                    .after(CharacterInterpolationSystem)
+                    .before(TransformSystem::TransformPropagate),
+            )
+            .add_system_to_stage(
+                CoreStage::PostUpdate,
+                apply_head_bob
+                    .after(blend_camera)
+                    .after(CharacterInterpolationSystem)
+                    .before(TransformSystem::TransformPropagate),
             );
     }
 }
 
-#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Resource)]
+struct CameraConfigPath(String);
+
+fn load_camera_config(
+    mut commands: Commands,
+    path: Res<CameraConfigPath>,
+    asset_server: Res<AssetServer>,
+) {
+    commands.insert_resource(CameraConfigHandle(asset_server.load(path.0.as_str())));
+}
+
+/// Transform the camera eases towards, written by the camera state systems
+///
+/// Set `DebugCamera` damping to `f32::INFINITY` to follow it without smoothing.
+#[derive(Component, Debug, Default, Clone, Copy, PartialEq, Deref, DerefMut)]
+pub struct CameraGoal(pub Transform);
+
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum CameraState {
-    FreeFloat, // Tranlation, Rotation
-    Locked,    // Transltaion only
-    Fps,       // Rotation only
-    Editor,    // Trigger to move
+    FreeFloat,   // Tranlation, Rotation
+    Locked,      // Transltaion only
+    Fps,         // Attached to a character
+    ThirdPerson, // Orbiting a character
+    Cinematic,   // Following a camera path
+    Orbit,       // Rotating around the focus point
+    Editor,      // Trigger to move
+    Spectator,   // Flying around while players are frozen
 }
 
-#[derive(Actionlike, Clone, Debug, Copy, PartialEq, Eq)]
+impl CameraState {
+    /// States where the camera flies without holding a button
+    pub fn flies(&self) -> bool {
+        matches!(self, CameraState::FreeFloat | CameraState::Spectator)
+    }
+}
+
+#[derive(Actionlike, Clone, Debug, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum CameraMovement {
     Left,
     Right,
@@ -84,136 +429,578 @@ impl CameraMovement {
     }
 }
 
-#[derive(Actionlike, Clone, Debug, Copy, PartialEq, Eq)]
+#[derive(Actionlike, Clone, Debug, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum CameraAction {
     Rotate,
+    /// Turn rate from a stick
+    Look,
     MoveTrigger,
     Pan,
     PanTrigger,
     Zoom,
     SensTrigger,
     FreeFloatToggle,
+    ViewToggle,
+    CursorToggle,
+    ProjectionToggle,
+    Select,
+    Focus,
+    RollLeft,
+    RollRight,
+    RollReset,
+    NextCamera,
+    SpectatorToggle,
+    TrackToggle,
+    ViewReset,
+}
+
+/// Everything the debug camera systems need, inserted next to a `Camera3dBundle`
+#[derive(Bundle)]
+pub struct DebugCameraBundle {
+    pub camera: DebugCamera,
+    pub goal: CameraGoal,
+    pub shaker: CameraShaker,
+    pub fov_kick: FovKick,
+    pub head_bob: HeadBob,
+    pub blend: CameraBlend,
+    pub fps_rig: FpsCameraRig,
+    pub third_person_rig: ThirdPersonCameraRig,
+    pub tracking: CameraTracking,
+    pub look: LookInput,
+    pub actions_input: InputManagerBundle<CameraAction>,
+    pub movement_input: InputManagerBundle<CameraMovement>,
+}
+
+impl DebugCameraBundle {
+    /// Camera starting at `transform`, which should match the camera's own transform
+    pub fn new(config: &CameraConfig, bindings: &Bindings, transform: Transform) -> Self {
+        let mut camera = DebugCamera::default();
+        camera.set_rotation(transform.rotation);
+        config.apply_to(&mut camera);
+
+        DebugCameraBundle {
+            camera,
+            goal: CameraGoal(transform),
+            shaker: Default::default(),
+            fov_kick: Default::default(),
+            head_bob: Default::default(),
+            blend: Default::default(),
+            fps_rig: Default::default(),
+            third_person_rig: Default::default(),
+            tracking: Default::default(),
+            look: Default::default(),
+            actions_input: InputManagerBundle {
+                input_map: bindings.camera_action_map(),
+                action_state: ActionState::default(),
+            },
+            movement_input: InputManagerBundle {
+                input_map: bindings.camera_movement_map(),
+                action_state: ActionState::default(),
+            },
+        }
+    }
+
+    /// Orbits and dollies around `focus`
+    pub fn with_focus(mut self, focus: Vec3) -> Self {
+        self.camera.focus = focus;
+        self.camera.radius = self.goal.translation.distance(focus);
+        self
+    }
 }
 
-fn spawn_camera(mut commands: Commands) {
+fn spawn_camera(mut commands: Commands, config: Res<CameraConfig>, bindings: Res<Bindings>) {
     let translation = Vec3::new(-2.0, 2.5, 5.0);
-    let radius = translation.length();
+    let transform = Transform::from_translation(translation).looking_at(Vec3::ZERO, Vec3::Y);
 
     commands
         .spawn(Camera3dBundle {
-            transform: Transform::from_translation(translation).looking_at(Vec3::ZERO, Vec3::Y),
+            transform,
             ..Default::default()
         })
-        .insert(DebugCamera {
-            radius,
-            ..Default::default()
-        })
-        .insert(InputManagerBundle::<CameraAction> {
-            input_map: InputMap::default()
-                .insert(DualAxis::mouse_motion(), CameraAction::Pan)
-                .insert(DualAxis::mouse_wheel(), CameraAction::Zoom)
-                .insert(MouseButton::Right, CameraAction::MoveTrigger)
-                .insert(MouseButton::Middle, CameraAction::PanTrigger)
-                .insert(KeyCode::LShift, CameraAction::SensTrigger)
-                .insert(KeyCode::C, CameraAction::FreeFloatToggle)
-                .build(),
-            action_state: ActionState::default(),
-        })
-        .insert(InputManagerBundle::<CameraMovement> {
-            input_map: InputMap::default()
-                .insert(KeyCode::W, CameraMovement::Forward)
-                .insert(KeyCode::A, CameraMovement::Left)
-                .insert(KeyCode::S, CameraMovement::Back)
-                .insert(KeyCode::D, CameraMovement::Right)
-                .insert(KeyCode::Space, CameraMovement::Up)
-                .insert(KeyCode::LControl, CameraMovement::Down)
-                .build(),
-            action_state: ActionState::default(),
-        });
+        .insert(DebugCameraBundle::new(&config, &bindings, transform).with_focus(Vec3::ZERO));
 }
 
-fn update_camera_state(
+/// Picks the profile of the current state, recomputed every frame so it can't drift
+fn update_camera_sensitivity(
     mut q: Query<(&mut DebugCamera, &ActionState<CameraAction>)>,
-    mut state: ResMut<State<CameraState>>,
+    state: Res<State<CameraState>>,
 ) {
-    let (mut camera, actions) = q.single_mut();
+    for (mut camera, actions) in q.iter_mut() {
+        let mut current = camera.profile(*state.current());
+        if actions.pressed(CameraAction::SensTrigger) {
+            current.move_sens *= camera.sens_boost;
+        }
+        camera.current = current;
+    }
+}
 
-    if actions.just_pressed(CameraAction::SensTrigger) {
-        camera.move_sens *= 5.0;
-    };
+fn update_camera_state(
+    q: Query<&ActionState<CameraAction>, With<ControlledCamera>>,
+    mut state: ResMut<State<CameraState>>,
+) {
+    let Ok(actions) = q.get_single() else { return };
 
-    if actions.just_released(CameraAction::SensTrigger) {
-        camera.move_sens *= 0.2;
+    if actions.just_pressed(CameraAction::FreeFloatToggle) {
+        let next = match *state.current() {
+            CameraState::FreeFloat => CameraState::Editor,
+            CameraState::Editor => CameraState::Orbit,
+            _ => CameraState::FreeFloat,
+        };
+        if let Err(error) = state.set(next) {
+            warn!("Failed to switch camera state: {error:?}");
+        }
     };
 
-    if actions.just_pressed(CameraAction::FreeFloatToggle) {
-        match *state.current() {
-            CameraState::FreeFloat => state.set(CameraState::Editor).unwrap(),
-            _ => state.set(CameraState::FreeFloat).unwrap(),
+    // Cycle through the character views and back out
+    if actions.just_pressed(CameraAction::ViewToggle) {
+        let next = match *state.current() {
+            CameraState::Fps => CameraState::ThirdPerson,
+            CameraState::ThirdPerson => CameraState::FreeFloat,
+            _ => CameraState::Fps,
         };
+        if let Err(error) = state.set(next) {
+            warn!("Failed to switch camera state: {error:?}");
+        }
     };
 }
 
-fn update_camera_pan(mut q: Query<(&mut Transform, &DebugCamera, &ActionState<CameraAction>)>) {
-    let (mut transform, camera, actions) = q.single_mut();
-    let pan = actions.axis_pair(CameraAction::Pan).unwrap();
+fn update_camera_pan(
+    mut q: Query<
+        (&mut CameraGoal, &DebugCamera, &ActionState<CameraAction>),
+        With<ControlledCamera>,
+    >,
+) {
+    let Ok((mut goal, camera, actions)) = q.get_single_mut() else {
+        return;
+    };
+    let Some(pan) = actions.axis_pair(CameraAction::Pan) else {
+        return;
+    };
 
     if actions.pressed(CameraAction::PanTrigger) {
-        let dx = transform.rotation * Vec3::X * camera.move_sens * pan.x();
-        let dy = transform.rotation * Vec3::Y * camera.move_sens * pan.y();
-        transform.translation = transform.translation - dx + dy;
+        let dx = goal.rotation * Vec3::X * camera.move_sens() * pan.x();
+        let dy = goal.rotation * Vec3::Y * camera.move_sens() * pan.y();
+        goal.translation = goal.translation - dx + dy;
     }
 }
 
-fn update_camera_zoom(mut q: Query<(&mut Projection, &DebugCamera, &ActionState<CameraAction>)>) {
-    let (mut projection, camera, actions) = q.single_mut();
-    let zoom = actions.axis_pair(CameraAction::Zoom).unwrap();
-    if zoom.length_squared() == 0.0 {
+fn update_camera_zoom(
+    mut q: Query<
+        (&mut Projection, &DebugCamera, &ActionState<CameraAction>),
+        With<ControlledCamera>,
+    >,
+) {
+    let Ok((mut projection, camera, actions)) = q.get_single_mut() else {
+        return;
+    };
+    let Some(zoom) = actions.axis_pair(CameraAction::Zoom) else {
+        return;
+    };
+
+    // Scrolling while flying adjusts the fly speed instead
+    if zoom.length_squared() == 0.0 || actions.pressed(CameraAction::MoveTrigger) {
         return;
     }
 
-    if let Projection::Perspective(projection) = projection.as_mut() {
-        projection.fov += -zoom.y() * camera.look_sens;
+    match projection.as_mut() {
+        Projection::Perspective(projection) => {
+            let fov = projection.fov - zoom.y() * camera.look_sens();
+            projection.fov = fov.clamp(camera.min_fov.to_radians(), camera.max_fov.to_radians());
+        }
+        Projection::Orthographic(projection) => zoom_orthographic(projection, camera, zoom.y()),
+    }
+}
+
+/// Puts field of view, roll and fly speed back to their defaults
+fn reset_camera_view(
+    mut q: Query<
+        (
+            &mut CameraGoal,
+            &mut DebugCamera,
+            &mut Projection,
+            Option<&FovKick>,
+            &ActionState<CameraAction>,
+        ),
+        With<ControlledCamera>,
+    >,
+    mut messages: EventWriter<HudMessage>,
+) {
+    let defaults = DebugCamera::default();
+
+    for (mut goal, mut camera, mut projection, kick, actions) in q.iter_mut() {
+        if !actions.just_pressed(CameraAction::ViewReset) {
+            continue;
+        }
+
+        camera.perspective_fov = defaults.perspective_fov;
+        if let Projection::Perspective(perspective) = projection.as_mut() {
+            // Running characters keep their kick on top
+            let kick = kick.map_or(0.0, |kick| kick.current().to_radians());
+            perspective.fov = defaults.perspective_fov + kick;
+        }
+
+        // Roll only exists while flying, where yaw and pitch are current too
+        if camera.roll != 0.0 {
+            camera.roll = 0.0;
+            goal.rotation = camera.rotation();
+        }
+        camera.fly_speed = defaults.fly_speed;
+        camera.fly_held = 0.0;
+        messages.send(HudMessage::new("View reset"));
+    }
+}
+
+fn zoom_orthographic(projection: &mut OrthographicProjection, camera: &DebugCamera, zoom: f32) {
+    // Same limits as dollying a perspective camera would reach
+    let half_fov = (camera.perspective_fov / 2.).tan();
+    let scale = projection.scale * (1.0 - zoom * camera.zoom_sens());
+    projection.scale = scale.clamp(camera.min_radius * half_fov, camera.max_radius * half_fov);
+}
+
+/// Moves towards or away from the focus point, faster the further away it is
+fn update_camera_dolly(
+    mut q: Query<
+        (
+            &mut CameraGoal,
+            &mut DebugCamera,
+            &mut Projection,
+            &ActionState<CameraAction>,
+        ),
+        With<ControlledCamera>,
+    >,
+) {
+    for (mut goal, mut camera, mut projection, actions) in q.iter_mut() {
+        let Some(zoom) = actions.axis_pair(CameraAction::Zoom) else {
+            continue;
+        };
+        if zoom.y() == 0.0 || actions.pressed(CameraAction::MoveTrigger) {
+            continue;
+        }
+
+        // Moving an orthographic camera doesn't change what it sees
+        if let Projection::Orthographic(projection) = projection.as_mut() {
+            zoom_orthographic(projection, &camera, zoom.y());
+            continue;
+        }
+
+        let forward = goal.forward();
+        let focus = goal.translation + forward * camera.radius;
+        let radius = camera.radius * (1.0 - zoom.y() * camera.zoom_sens());
+
+        camera.radius = radius.clamp(camera.min_radius, camera.max_radius);
+        camera.focus = focus;
+        goal.translation = focus - forward * camera.radius;
+    }
+}
+
+/// Switches between perspective and orthographic, keeping the focus plane the same size
+fn update_camera_projection(
+    mut q: Query<
+        (
+            &mut Projection,
+            &mut DebugCamera,
+            &ActionState<CameraAction>,
+        ),
+        With<ControlledCamera>,
+    >,
+) {
+    for (mut projection, mut camera, actions) in q.iter_mut() {
+        if !actions.just_pressed(CameraAction::ProjectionToggle) {
+            continue;
+        }
+
+        *projection = match projection.as_ref() {
+            Projection::Perspective(perspective) => {
+                camera.perspective_fov = perspective.fov;
+
+                // Half the height visible at the focus distance
+                let half_height = camera.radius * (perspective.fov / 2.).tan();
+                Projection::Orthographic(OrthographicProjection {
+                    scale: half_height,
+                    scaling_mode: ScalingMode::FixedVertical(2.0),
+                    ..Default::default()
+                })
+            }
+            Projection::Orthographic(_) => Projection::Perspective(PerspectiveProjection {
+                fov: camera.perspective_fov,
+                ..Default::default()
+            }),
+        };
     }
 }
 
 fn update_camera_rot(
-    mut q: Query<(&mut Transform, &DebugCamera, &ActionState<CameraAction>)>,
+    mut q: Query<
+        (
+            &mut CameraGoal,
+            &mut DebugCamera,
+            &LookInput,
+            &ActionState<CameraAction>,
+        ),
+        With<ControlledCamera>,
+    >,
     state: Res<State<CameraState>>,
 ) {
-    let (mut transform, camera, actions) = q.single_mut();
-    let motion = actions.axis_pair(CameraAction::Pan).unwrap();
+    let Ok((mut goal, mut camera, look, actions)) = q.get_single_mut() else {
+        return;
+    };
+    let motion = look.delta();
     let triggered = actions.pressed(CameraAction::MoveTrigger);
 
-    if *state.current() == CameraState::FreeFloat || triggered {
-        transform.rotation =
-            Quat::from_rotation_y(-motion.x() * camera.look_sens) * transform.rotation;
-        transform.rotation *= Quat::from_rotation_x(-motion.y() * camera.look_sens);
+    if state.current().flies() || triggered {
+        let max_pitch = camera.max_pitch.to_radians();
+        camera.yaw -= motion.x * camera.look_sens();
+        camera.pitch = (camera.pitch - motion.y * camera.look_sens()).clamp(-max_pitch, max_pitch);
+
+        // Rebuilt from the angles every frame so yaw and pitch can't leak into roll
+        goal.rotation = camera.rotation();
+    }
+}
+
+fn update_camera_roll(
+    mut q: Query<
+        (
+            &mut CameraGoal,
+            &mut DebugCamera,
+            &ActionState<CameraAction>,
+        ),
+        With<ControlledCamera>,
+    >,
+    time: Res<Time>,
+) {
+    for (mut goal, mut camera, actions) in q.iter_mut() {
+        if actions.just_pressed(CameraAction::RollReset) {
+            camera.roll = 0.0;
+        }
+
+        let direction = match (
+            actions.pressed(CameraAction::RollLeft),
+            actions.pressed(CameraAction::RollRight),
+        ) {
+            (true, false) => 1.0,
+            (false, true) => -1.0,
+            _ => 0.0,
+        };
+        camera.roll += direction * camera.roll_speed.to_radians() * time.delta_seconds();
+        camera.roll = (camera.roll + PI).rem_euclid(TAU) - PI;
+
+        goal.rotation = camera.rotation();
+    }
+}
+
+/// Scroll while holding the move trigger to change the base fly speed
+fn update_fly_speed(
+    mut q: Query<(&mut DebugCamera, &ActionState<CameraAction>), With<ControlledCamera>>,
+    mut messages: EventWriter<HudMessage>,
+) {
+    for (mut camera, actions) in q.iter_mut() {
+        let Some(zoom) = actions.axis_pair(CameraAction::Zoom) else {
+            continue;
+        };
+        if zoom.y() == 0.0 || !actions.pressed(CameraAction::MoveTrigger) {
+            continue;
+        }
+
+        // Multiplicative steps cover small rooms and whole levels alike
+        let speed = camera.fly_speed * 1.25_f32.powf(zoom.y());
+        camera.fly_speed = speed.clamp(camera.min_fly_speed, camera.max_fly_speed);
+        messages.send(HudMessage::new(format!(
+            "Fly speed {:.2} m/s",
+            camera.fly_speed
+        )));
     }
 }
 
 fn update_camera_pos(
+    mut q: Query<
+        (
+            &mut CameraGoal,
+            &mut DebugCamera,
+            &ActionState<CameraMovement>,
+            &ActionState<CameraAction>,
+        ),
+        With<ControlledCamera>,
+    >,
+    state: Res<State<CameraState>>,
+    time: Res<Time>,
+) {
+    let Ok((mut goal, mut camera, movement, actions)) = q.get_single_mut() else {
+        return;
+    };
+    let triggered = actions.pressed(CameraAction::MoveTrigger);
+
+    let direction = movement
+        .get_pressed()
+        .iter()
+        .map(|movement| match movement {
+            // Apply up and down movements on global axis
+            CameraMovement::Up | CameraMovement::Down => movement.into_vec(),
+            _ => goal.rotation * movement.into_vec(),
+        })
+        .sum::<Vec3>();
+
+    let dt = time.delta_seconds();
+    let flying = state.current().flies() || triggered;
+    let target = match flying && direction != Vec3::ZERO {
+        true => {
+            // Accelerate the longer movement is held
+            camera.fly_held += dt;
+            let ramp = (1.0 + camera.fly_ramp * camera.fly_held).min(camera.max_fly_ramp);
+            let boost = match actions.pressed(CameraAction::SensTrigger) {
+                true => camera.sens_boost,
+                false => 1.0,
+            };
+            direction.normalize() * camera.fly_speed * ramp * boost
+        }
+        false => {
+            camera.fly_held = 0.0;
+            Vec3::ZERO
+        }
+    };
+
+    camera.fly_velocity = match camera.fly_inertia {
+        true => {
+            let rate = match target == Vec3::ZERO {
+                true => camera.fly_drag,
+                false => camera.fly_acceleration,
+            };
+            let velocity = camera.fly_velocity.lerp(target, 1.0 - (-rate * dt).exp());
+
+            // The exponential approach never quite reaches a standstill
+            match velocity.length_squared() < 1e-4 && target == Vec3::ZERO {
+                true => Vec3::ZERO,
+                false => velocity,
+            }
+        }
+        false => target,
+    };
+    goal.translation += camera.fly_velocity * dt;
+}
+
+/// Eases cameras towards their goal, independent of the frame rate
+fn damp_camera(
     mut q: Query<(
         &mut Transform,
+        &CameraGoal,
         &DebugCamera,
-        &ActionState<CameraMovement>,
-        &ActionState<CameraAction>,
+        Option<&CharacterVisual>,
     )>,
-    state: Res<State<CameraState>>,
+    time: Res<Time>,
 ) {
-    let (mut transform, camera, movement, actions) = q.single_mut();
-    let triggered = actions.pressed(CameraAction::MoveTrigger);
+    let dt = time.delta_seconds();
 
-    if (*state.current() == CameraState::FreeFloat) || triggered {
-        movement.get_pressed().iter().for_each(|movement| {
-            let mut direction = movement.into_vec();
+    for (mut transform, goal, camera, visual) in q.iter_mut() {
+        // Attached cameras are positioned by character interpolation instead
+        if visual.is_none() {
+            let t = 1.0 - (-camera.position_damping * dt).exp();
+            transform.translation = transform.translation.lerp(goal.translation, t);
+        }
 
-            // Apply up and down movements on global axis
-            if *movement != CameraMovement::Up && *movement != CameraMovement::Down {
-                direction = transform.rotation * direction;
-            }
+        let t = 1.0 - (-camera.rotation_damping * dt).exp();
+        transform.rotation = transform.rotation.slerp(goal.rotation, t);
+    }
+}
+
+/// Orbits around whatever is in front of the camera at focus distance
+fn enter_orbit(mut q: Query<(&CameraGoal, &mut DebugCamera), With<ControlledCamera>>) {
+    for (goal, mut camera) in q.iter_mut() {
+        camera.focus = goal.translation + goal.forward() * camera.radius;
+        camera.upside_down = false;
+    }
+}
+
+fn exit_orbit(mut q: Query<(&CameraGoal, &mut DebugCamera), With<ControlledCamera>>) {
+    for (goal, mut camera) in q.iter_mut() {
+        camera.set_rotation(goal.rotation);
+    }
+}
+
+/// Drag to orbit around the focus point, middle drag to move the focus point itself
+///
+/// Unlike the free camera, orbiting may go over the poles; horizontal dragging is flipped
+/// while upside down so the scene keeps following the mouse.
+fn update_camera_orbit(
+    mut q: Query<
+        (
+            &mut CameraGoal,
+            &mut DebugCamera,
+            &LookInput,
+            &ActionState<CameraAction>,
+        ),
+        With<ControlledCamera>,
+    >,
+) {
+    for (mut goal, mut camera, look, actions) in q.iter_mut() {
+        let motion = look.delta();
+
+        // Decide once per drag, flipping mid drag would reverse the motion under the mouse
+        if actions.just_pressed(CameraAction::MoveTrigger) {
+            camera.upside_down = (goal.rotation * Vec3::Y).y <= 0.0;
+        }
+
+        if actions.pressed(CameraAction::MoveTrigger) {
+            let yaw = match camera.upside_down {
+                true => motion.x * camera.look_sens(),
+                false => -motion.x * camera.look_sens(),
+            };
+            let pitch = -motion.y * camera.look_sens();
+
+            goal.rotation = Quat::from_rotation_y(yaw) * goal.rotation;
+            goal.rotation *= Quat::from_rotation_x(pitch);
+        } else if actions.pressed(CameraAction::PanTrigger) {
+            // A pixel of motion covers about `look_sens` radians at the focus distance
+            let scale = camera.look_sens() * camera.radius;
+            let right = goal.rotation * Vec3::X * -motion.x * scale;
+            let up = goal.rotation * Vec3::Y * motion.y * scale;
+            camera.focus += right + up;
+        }
+
+        goal.translation = camera.focus + goal.rotation * Vec3::new(0.0, 0.0, camera.radius);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::input::InputPlugin;
+
+    #[test]
+    fn bindings_without_mouse_wheel_dont_panic() {
+        let bindings: Bindings = ron::from_str(
+            "(camera_actions: [
+                (Pan, MouseMotion),
+                (MoveTrigger, Mouse(Right)),
+                (PanTrigger, Mouse(Middle)),
+            ])",
+        )
+        .unwrap();
+
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .add_plugin(InputPlugin)
+            .add_plugin(InputManagerPlugin::<CameraAction>::default())
+            .add_event::<HudMessage>()
+            .add_system(update_camera_pan)
+            .add_system(update_camera_zoom)
+            .add_system(update_camera_dolly)
+            .add_system(update_fly_speed);
+
+        app.world.spawn((
+            DebugCamera::default(),
+            CameraGoal::default(),
+            Projection::default(),
+            InputManagerBundle::<CameraAction> {
+                input_map: bindings.camera_action_map(),
+                ..Default::default()
+            },
+            ControlledCamera,
+        ));
 
-            transform.translation += direction * camera.move_sens;
-        });
+        // Flying turns scrolling into fly speed, so check both paths
+        app.update();
+        app.world
+            .resource_mut::<Input<MouseButton>>()
+            .press(MouseButton::Right);
+        app.update();
     }
 }
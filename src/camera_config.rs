@@ -0,0 +1,86 @@
+use crate::prelude::*;
+use bevy::{prelude::*, reflect::TypeUuid};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Debug camera settings, also loadable as a `.camera.ron` asset
+#[derive(Resource, TypeUuid, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[uuid = "9a3c8e51-2d47-4f1b-b6a0-5c7e1d2f8b94"]
+#[serde(default)]
+pub struct CameraConfig {
+    pub move_sens: f32,
+    pub look_sens: f32,
+    pub zoom_sens: f32,
+    /// Sensitivities replacing the ones above in some states
+    pub profiles: HashMap<CameraState, Sensitivity>,
+    /// State the camera starts in
+    pub default_state: CameraState,
+}
+
+impl Default for CameraConfig {
+    fn default() -> Self {
+        let camera = DebugCamera::default();
+
+        CameraConfig {
+            move_sens: camera.sensitivity.move_sens,
+            look_sens: camera.sensitivity.look_sens,
+            zoom_sens: camera.sensitivity.zoom_sens,
+            profiles: camera.profiles,
+            default_state: CameraState::FreeFloat,
+        }
+    }
+}
+
+impl CameraConfig {
+    /// Copies the sensitivities onto a camera
+    pub fn apply_to(&self, camera: &mut DebugCamera) {
+        camera.sensitivity = Sensitivity {
+            move_sens: self.move_sens,
+            look_sens: self.look_sens,
+            zoom_sens: self.zoom_sens,
+        };
+        camera.profiles = self.profiles.clone();
+    }
+}
+
+/// Handle to the config asset the camera plugin keeps in sync
+#[derive(Resource, Deref)]
+pub struct CameraConfigHandle(pub Handle<CameraConfig>);
+
+pub fn apply_camera_config(
+    mut events: EventReader<AssetEvent<CameraConfig>>,
+    mut q: Query<&mut DebugCamera>,
+    mut config: ResMut<CameraConfig>,
+    mut state: ResMut<State<CameraState>>,
+    assets: Res<Assets<CameraConfig>>,
+    handle: Option<Res<CameraConfigHandle>>,
+) {
+    let Some(handle) = handle else { return };
+
+    for event in events.iter() {
+        let (loaded, created) = match event {
+            AssetEvent::Created { handle } => (handle, true),
+            AssetEvent::Modified { handle } => (handle, false),
+            AssetEvent::Removed { .. } => continue,
+        };
+
+        if *loaded != handle.0 {
+            continue;
+        }
+
+        let Some(loaded) = assets.get(loaded) else {
+            continue;
+        };
+        info!("Applying camera config");
+        *config = loaded.clone();
+
+        for mut camera in q.iter_mut() {
+            config.apply_to(&mut camera);
+        }
+
+        // Only the first load picks the state, reloading shouldn't yank the camera around
+        if created && *state.current() != config.default_state {
+            state.set(config.default_state).unwrap_or_default();
+        }
+    }
+}
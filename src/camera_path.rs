@@ -0,0 +1,283 @@
+use crate::prelude::*;
+use bevy::prelude::*;
+
+/// Camera transform the path passes at `time` seconds from its start
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CameraKeyframe {
+    pub time: f32,
+    pub transform: Transform,
+}
+
+impl CameraKeyframe {
+    pub fn new(time: f32, transform: Transform) -> Self {
+        CameraKeyframe { time, transform }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum PathInterpolation {
+    Linear,
+    /// Smooth curve through every keyframe
+    #[default]
+    CatmullRom,
+    /// Single curve using the keyframes as control points, only passing the first and last
+    Bezier,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum PathEasing {
+    Linear,
+    EaseIn,
+    EaseOut,
+    #[default]
+    EaseInOut,
+}
+
+impl PathEasing {
+    pub fn apply(&self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            PathEasing::Linear => t,
+            PathEasing::EaseIn => t * t * t,
+            PathEasing::EaseOut => 1.0 - (1.0 - t).powi(3),
+            PathEasing::EaseInOut => t * t * (3.0 - 2.0 * t),
+        }
+    }
+}
+
+/// Authored camera flight for cutscenes and flythroughs
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct CameraPath {
+    /// Sorted by time
+    pub keyframes: Vec<CameraKeyframe>,
+    pub interpolation: PathInterpolation,
+    /// Applied to the progress over the whole path
+    pub easing: PathEasing,
+}
+
+impl CameraPath {
+    pub fn new(keyframes: Vec<CameraKeyframe>) -> Self {
+        CameraPath {
+            keyframes,
+            ..Default::default()
+        }
+    }
+
+    pub fn with_interpolation(mut self, interpolation: PathInterpolation) -> Self {
+        self.interpolation = interpolation;
+        self
+    }
+
+    pub fn with_easing(mut self, easing: PathEasing) -> Self {
+        self.easing = easing;
+        self
+    }
+
+    pub fn duration(&self) -> f32 {
+        self.keyframes.last().map(|key| key.time).unwrap_or(0.0)
+    }
+
+    /// Transform at `elapsed` seconds from the start, easing included
+    pub fn sample(&self, elapsed: f32) -> Transform {
+        let keys = &self.keyframes;
+        match keys.len() {
+            0 => return Transform::IDENTITY,
+            1 => return keys[0].transform,
+            _ => {}
+        }
+
+        let duration = self.duration();
+        let progress = match duration > 0.0 {
+            true => self.easing.apply(elapsed / duration),
+            false => 1.0,
+        };
+        let time = progress * duration;
+
+        // Segment containing `time` and how far along it is
+        let index = keys
+            .windows(2)
+            .position(|pair| time <= pair[1].time)
+            .unwrap_or(keys.len() - 2);
+        let (a, b) = (keys[index], keys[index + 1]);
+        let span = b.time - a.time;
+        let t = match span > 0.0 {
+            true => ((time - a.time) / span).clamp(0.0, 1.0),
+            false => 1.0,
+        };
+
+        let rotation = a.transform.rotation.slerp(b.transform.rotation, t);
+        let translation = match self.interpolation {
+            PathInterpolation::Linear => a.transform.translation.lerp(b.transform.translation, t),
+            PathInterpolation::CatmullRom => {
+                let p0 = keys[index.saturating_sub(1)].transform.translation;
+                let p3 = keys[(index + 2).min(keys.len() - 1)].transform.translation;
+                catmull_rom(p0, a.transform.translation, b.transform.translation, p3, t)
+            }
+            PathInterpolation::Bezier => {
+                let points: Vec<_> = keys.iter().map(|key| key.transform.translation).collect();
+                bezier(points, progress)
+            }
+        };
+
+        Transform {
+            translation,
+            rotation,
+            scale: Vec3::ONE,
+        }
+    }
+}
+
+fn catmull_rom(p0: Vec3, p1: Vec3, p2: Vec3, p3: Vec3, t: f32) -> Vec3 {
+    let (t2, t3) = (t * t, t * t * t);
+    0.5 * ((2.0 * p1)
+        + (p2 - p0) * t
+        + (2.0 * p0 - 5.0 * p1 + 4.0 * p2 - p3) * t2
+        + (3.0 * p1 - p0 - 3.0 * p2 + p3) * t3)
+}
+
+/// De Casteljau evaluation over all control points
+fn bezier(mut points: Vec<Vec3>, t: f32) -> Vec3 {
+    while points.len() > 1 {
+        points = points
+            .windows(2)
+            .map(|pair| pair[0].lerp(pair[1], t))
+            .collect();
+    }
+    points[0]
+}
+
+/// Plays a path on a camera, every debug camera when `camera` is `None`
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlayCameraPath {
+    pub path: CameraPath,
+    pub camera: Option<Entity>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CameraPathStarted {
+    pub camera: Entity,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CameraPathFinished {
+    pub camera: Entity,
+    /// False when the path was cut short by leaving `CameraState::Cinematic`
+    pub completed: bool,
+}
+
+/// Path currently played by a camera
+#[derive(Component, Debug, Clone, PartialEq)]
+pub struct CameraPathPlayer {
+    pub path: CameraPath,
+    pub elapsed: f32,
+}
+
+pub fn start_camera_paths(
+    mut commands: Commands,
+    mut events: EventReader<PlayCameraPath>,
+    mut started: EventWriter<CameraPathStarted>,
+    mut finished: EventWriter<CameraPathFinished>,
+    mut cameras: Query<(Entity, &mut Transform, &mut CameraGoal, &mut DebugCamera)>,
+    mut state: ResMut<State<CameraState>>,
+) {
+    for event in events.iter() {
+        // Nothing to play, the camera just jumps to the only keyframe if there is one
+        let instant = event.path.duration() <= 0.0;
+
+        for (camera, mut transform, mut goal, mut debug_camera) in cameras.iter_mut() {
+            if event.camera.map_or(false, |target| target != camera) {
+                continue;
+            }
+            started.send(CameraPathStarted { camera });
+
+            if instant {
+                if let Some(key) = event.path.keyframes.last() {
+                    *transform = key.transform;
+                    goal.0 = key.transform;
+                    debug_camera.set_rotation(key.transform.rotation);
+                }
+                finished.send(CameraPathFinished {
+                    camera,
+                    completed: true,
+                });
+                continue;
+            }
+
+            commands.entity(camera).insert(CameraPathPlayer {
+                path: event.path.clone(),
+                elapsed: 0.0,
+            });
+        }
+
+        if !instant && *state.current() != CameraState::Cinematic {
+            state.push(CameraState::Cinematic).unwrap_or_default();
+        }
+    }
+}
+
+pub fn play_camera_paths(
+    mut cameras: Query<(
+        Entity,
+        &mut CameraPathPlayer,
+        &mut Transform,
+        &mut CameraGoal,
+        &mut DebugCamera,
+    )>,
+    mut finished: EventWriter<CameraPathFinished>,
+    mut state: ResMut<State<CameraState>>,
+    time: Res<SimulationTime>,
+) {
+    // Players are inserted through commands and may not have arrived yet
+    if cameras.is_empty() {
+        return;
+    }
+
+    let mut playing = false;
+
+    for (entity, mut player, mut transform, mut goal, mut camera) in cameras.iter_mut() {
+        let duration = player.path.duration();
+        if player.elapsed >= duration {
+            continue;
+        }
+        player.elapsed += time.delta_seconds();
+
+        // Follow the path exactly, smoothing would cut its corners
+        let sample = player.path.sample(player.elapsed);
+        *transform = sample;
+        goal.0 = sample;
+        camera.set_rotation(sample.rotation);
+
+        if player.elapsed < duration {
+            playing = true;
+            continue;
+        }
+
+        finished.send(CameraPathFinished {
+            camera: entity,
+            completed: true,
+        });
+    }
+
+    // Hand the camera back to whatever state started the cinematic
+    if !playing && state.pop().is_err() {
+        state.set(CameraState::FreeFloat).unwrap_or_default();
+    }
+}
+
+/// Removes players once the cinematic ends, reporting paths that were cut short
+pub fn stop_camera_paths(
+    mut commands: Commands,
+    cameras: Query<(Entity, &CameraPathPlayer)>,
+    mut finished: EventWriter<CameraPathFinished>,
+) {
+    for (camera, player) in cameras.iter() {
+        commands.entity(camera).remove::<CameraPathPlayer>();
+
+        if player.elapsed < player.path.duration() {
+            finished.send(CameraPathFinished {
+                camera,
+                completed: false,
+            });
+        }
+    }
+}
@@ -0,0 +1,318 @@
+use crate::prelude::*;
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use leafwing_input_manager::prelude::*;
+
+/// Collision group of carried objects, their carrier walks through them
+pub const CARRIED_GROUP: Group = Group::GROUP_32;
+/// Decay rate of the spin of carried objects, per second
+const CARRY_ANGULAR_DAMPING: f32 = 8.0;
+
+/// Lets characters with a `Carrier` pick up `Carryable` bodies by holding `Interact`
+///
+/// Holding `Throw` while carrying charges up a throw, releasing it lets go.
+pub struct CarryPlugin;
+
+impl Plugin for CarryPlugin {
+    fn build(&self, app: &mut App) {
+        init_simulation_time(app);
+        app.add_event::<Thrown>()
+            .add_system(setup_carryables)
+            .add_system(pick_up_carryables.after(interact))
+            .add_system(carry_objects.after(pick_up_carryables));
+    }
+}
+
+/// Dynamic body characters can pick up
+#[derive(Component, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Carryable;
+
+/// Character holding objects in front of its view while `Interact` is held
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct Carrier {
+    /// Heaviest body that can be picked up, in kilograms
+    pub max_mass: f32,
+    /// Distance from the eyes objects are held at, in meters
+    pub hold_distance: f32,
+    /// How quickly the held object closes the gap to the hold point, per second
+    pub stiffness: f32,
+    /// Fastest a held object is moved, in meters per second
+    pub max_speed: f32,
+    /// Gap to the hold point at which a stuck object is dropped, in meters
+    pub break_distance: f32,
+    /// Impulse of a throw without charging and at full charge
+    pub throw_impulse: f32,
+    pub max_throw_impulse: f32,
+    /// Seconds `Throw` is held to reach full charge
+    pub charge_time: f32,
+    charge: f32,
+    carried: Option<Entity>,
+    /// Groups of the character and its controller before carrying
+    groups: Option<CollisionGroups>,
+    controller_groups: Option<CollisionGroups>,
+}
+
+impl Default for Carrier {
+    fn default() -> Self {
+        Carrier {
+            max_mass: 50.0,
+            hold_distance: 2.0,
+            stiffness: 15.0,
+            max_speed: 20.0,
+            break_distance: 1.5,
+            throw_impulse: 50.0,
+            max_throw_impulse: 400.0,
+            charge_time: 1.0,
+            charge: 0.0,
+            carried: None,
+            groups: None,
+            controller_groups: None,
+        }
+    }
+}
+
+impl Carrier {
+    pub fn carried(&self) -> Option<Entity> {
+        self.carried
+    }
+
+    /// How far a throw is charged up, `0..=1`
+    pub fn charge(&self) -> f32 {
+        match self.charge_time > 0.0 {
+            true => (self.charge / self.charge_time).min(1.0),
+            false => 1.0,
+        }
+    }
+
+    fn throw_impulse(&self) -> f32 {
+        self.throw_impulse + (self.max_throw_impulse - self.throw_impulse) * self.charge()
+    }
+}
+
+/// Object held by a `Carrier`, restored to its own groups and gravity once dropped
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct Carried {
+    pub by: Entity,
+    groups: Option<CollisionGroups>,
+    gravity: Option<GravityScale>,
+}
+
+/// A carried object was thrown, e.g. for sounds and effects
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Thrown {
+    pub carrier: Entity,
+    pub object: Entity,
+    /// Impulse applied along the view, in kilogram meters per second
+    pub impulse: Vec3,
+}
+
+fn setup_carryables(
+    mut commands: Commands,
+    q: Query<(Entity, Option<&Interactable>, Option<&Velocity>), Added<Carryable>>,
+) {
+    for (entity, interactable, velocity) in q.iter() {
+        commands
+            .entity(entity)
+            .insert(ReadMassProperties::default());
+        if interactable.is_none() {
+            commands.entity(entity).insert(Interactable::default());
+        }
+        // Carrying drives the body through its velocity
+        if velocity.is_none() {
+            commands.entity(entity).insert(Velocity::default());
+        }
+    }
+}
+
+#[allow(clippy::type_complexity)]
+pub fn pick_up_carryables(
+    mut commands: Commands,
+    mut interactions: EventReader<Interacted>,
+    mut carriers: Query<(
+        &mut Carrier,
+        Option<&mut KinematicCharacterController>,
+        Option<&CollisionGroups>,
+    )>,
+    objects: Query<
+        (
+            &RigidBody,
+            &ReadMassProperties,
+            Option<&CollisionGroups>,
+            Option<&GravityScale>,
+        ),
+        (With<Carryable>, Without<Carried>),
+    >,
+) {
+    for Interacted { interactor, target } in interactions.iter() {
+        let Ok((mut carrier, mut controller, groups)) = carriers.get_mut(*interactor) else {
+            continue;
+        };
+        let Ok((body, mass, object_groups, gravity)) = objects.get(*target) else {
+            continue;
+        };
+        if carrier.carried.is_some() || *body != RigidBody::Dynamic {
+            continue;
+        }
+        if mass.0.mass > carrier.max_mass {
+            debug!("{target:?} is too heavy to carry");
+            continue;
+        }
+
+        commands.entity(*target).insert(Carried {
+            by: *interactor,
+            groups: object_groups.copied(),
+            gravity: gravity.copied(),
+        });
+        commands
+            .entity(*target)
+            .insert(CollisionGroups::new(CARRIED_GROUP, Group::ALL))
+            .insert(GravityScale(0.0));
+
+        // Neither the character nor its controller may bump into what it holds
+        let ignore_carried = |groups: Option<CollisionGroups>| {
+            let groups = groups.unwrap_or_default();
+            CollisionGroups::new(groups.memberships, groups.filters - CARRIED_GROUP)
+        };
+        carrier.groups = groups.copied();
+        if let Some(controller) = controller.as_deref_mut() {
+            carrier.controller_groups = controller.filter_groups;
+            controller.filter_groups = Some(ignore_carried(controller.filter_groups));
+        }
+        commands
+            .entity(*interactor)
+            .insert(ignore_carried(groups.copied()));
+        carrier.carried = Some(*target);
+    }
+}
+
+/// Lets go of the carried object and restores the groups of both
+fn drop_carried(
+    commands: &mut Commands,
+    carrier_entity: Entity,
+    carrier: &mut Carrier,
+    controller: Option<&mut KinematicCharacterController>,
+    carried: Option<(Entity, &Carried)>,
+) {
+    let mut character = commands.entity(carrier_entity);
+    match carrier.groups.take() {
+        Some(groups) => character.insert(groups),
+        None => character.remove::<CollisionGroups>(),
+    };
+    if let Some(controller) = controller {
+        controller.filter_groups = carrier.controller_groups.take();
+    }
+    carrier.carried = None;
+    carrier.charge = 0.0;
+
+    let Some((entity, carried)) = carried else {
+        return;
+    };
+    let mut object = commands.entity(entity);
+    object.remove::<Carried>();
+    match carried.groups {
+        Some(groups) => object.insert(groups),
+        None => object.remove::<CollisionGroups>(),
+    };
+    match carried.gravity {
+        Some(gravity) => object.insert(gravity),
+        None => object.remove::<GravityScale>(),
+    };
+}
+
+#[allow(clippy::type_complexity)]
+pub fn carry_objects(
+    mut commands: Commands,
+    mut carriers: Query<(
+        Entity,
+        &mut Carrier,
+        Option<&mut KinematicCharacterController>,
+        &ActionState<CharacterActions>,
+        &Transform,
+        &CharacterMovementController,
+        &CharacterHeight,
+    )>,
+    mut objects: Query<(&Transform, &mut Velocity, &Carried), Without<Carrier>>,
+    cameras: Query<(&Camera, &GlobalTransform), With<Camera3d>>,
+    mut thrown: EventWriter<Thrown>,
+    rapier: Res<RapierContext>,
+    time: Res<SimulationTime>,
+) {
+    let dt = time.delta_seconds();
+
+    for (entity, mut carrier, mut controller, actions, transform, character, height) in
+        carriers.iter_mut()
+    {
+        let Some(object) = carrier.carried else {
+            continue;
+        };
+        let Ok((object_transform, mut velocity, carried)) = objects.get_mut(object) else {
+            // Despawned while held
+            drop_carried(
+                &mut commands,
+                entity,
+                &mut carrier,
+                controller.as_deref_mut(),
+                None,
+            );
+            continue;
+        };
+
+        let eye_height = character.settings().eye_height(height.current());
+        let eye = transform.translation + Vec3::Y * eye_height;
+        let (_, direction) = aim(&cameras, eye, transform.rotation);
+
+        // Hold the object in front of walls rather than pressing it into them
+        let filter = QueryFilter::default()
+            .exclude_rigid_body(entity)
+            .exclude_collider(object)
+            .exclude_sensors();
+        let distance = rapier
+            .cast_ray(eye, direction, carrier.hold_distance, true, filter)
+            .map_or(carrier.hold_distance, |(_, toi)| toi * 0.8);
+        let target = eye + direction * distance;
+
+        // Charge only builds up from a press while carrying, holding it since before starts nothing
+        let throwing = carrier.charge > 0.0;
+        if actions.just_pressed(CharacterActions::Throw)
+            || (throwing && actions.pressed(CharacterActions::Throw))
+        {
+            carrier.charge += dt;
+        }
+        if throwing && actions.just_released(CharacterActions::Throw) {
+            let impulse = direction * carrier.throw_impulse();
+            drop_carried(
+                &mut commands,
+                entity,
+                &mut carrier,
+                controller.as_deref_mut(),
+                Some((object, carried)),
+            );
+            commands.entity(object).insert(ExternalImpulse {
+                impulse,
+                ..Default::default()
+            });
+            thrown.send(Thrown {
+                carrier: entity,
+                object,
+                impulse,
+            });
+            continue;
+        }
+
+        let gap = target - object_transform.translation;
+        let released = !actions.pressed(CharacterActions::Interact);
+        if released || gap.length() > carrier.break_distance {
+            drop_carried(
+                &mut commands,
+                entity,
+                &mut carrier,
+                controller.as_deref_mut(),
+                Some((object, carried)),
+            );
+            continue;
+        }
+
+        velocity.linvel = (gap * carrier.stiffness).clamp_length_max(carrier.max_speed);
+        velocity.angvel *= (-CARRY_ANGULAR_DAMPING * dt).exp();
+    }
+}
@@ -1,9 +1,22 @@
 use crate::prelude::*;
-use bevy::prelude::*;
+use bevy::{
+    prelude::*,
+    reflect::TypeUuid,
+    time::{FixedTimestep, FixedTimesteps},
+    transform::TransformSystem,
+};
 use bevy_rapier3d::prelude::*;
-use leafwing_input_manager::prelude::*;
+use leafwing_input_manager::{plugin::InputManagerSystem, prelude::*};
+use serde::{Deserialize, Serialize};
 use std::ops::{Add, Mul};
 
+/// Step of the character simulation, in seconds
+pub const CHARACTER_TIMESTEP: f64 = 1.0 / 60.0;
+const CHARACTER_TIMESTEP_LABEL: &str = "character_timestep";
+const GRAVITY: f32 = 9.81;
+/// Seconds a jump pressed just before landing is remembered
+const JUMP_BUFFER: f32 = 0.15;
+
 #[derive(Debug, Hash, PartialEq, Eq, Clone, StageLabel, SystemLabel)]
 pub enum CharacterControllerStages {
     Physics,
@@ -11,60 +24,703 @@ pub enum CharacterControllerStages {
     Position,
 }
 
-pub struct CharacterControllerPlugin;
+/// Places character visuals, including first person cameras, in `CoreStage::PostUpdate`
+///
+/// Camera effects offsetting those visuals run after this so they aren't overwritten.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, SystemLabel)]
+pub struct CharacterInterpolationSystem;
+
+pub struct CharacterControllerPlugin {
+    default_spawn: bool,
+    settings: CharacterSettings,
+    keymap: CharacterKeymap,
+    tuning: Option<String>,
+}
+
+impl Default for CharacterControllerPlugin {
+    fn default() -> Self {
+        CharacterControllerPlugin {
+            default_spawn: true,
+            settings: Default::default(),
+            keymap: Default::default(),
+            tuning: None,
+        }
+    }
+}
+
+impl CharacterControllerPlugin {
+    /// Whether the plugin spawns a player at the selected `PlayerSpawn` once there is one
+    ///
+    /// Only has an effect with the `default_player` feature.
+    pub fn with_default_spawn(mut self, enabled: bool) -> Self {
+        self.default_spawn = enabled;
+        self
+    }
+
+    /// Tuning used by the default player and available as a resource
+    pub fn with_settings(mut self, settings: CharacterSettings) -> Self {
+        self.settings = settings;
+        self
+    }
+
+    /// Bindings used by the default player and available as a resource
+    pub fn with_keymap(mut self, keymap: CharacterKeymap) -> Self {
+        self.keymap = keymap;
+        self
+    }
+
+    /// Load tuning from a `.character.ron` asset, reapplied whenever it changes
+    pub fn with_tuning(mut self, path: impl Into<String>) -> Self {
+        self.tuning = Some(path.into());
+        self
+    }
+}
+
 impl Plugin for CharacterControllerPlugin {
     fn build(&self, app: &mut App) {
-        app.add_state(CharacterSpeed(1))
+        if self.default_spawn && cfg!(feature = "default_player") {
+            app.add_system(spawn_player);
+        }
+
+        if let Some(path) = &self.tuning {
+            app.insert_resource(CharacterTuningPath(path.clone()))
+                .add_startup_system(load_character_tuning);
+        }
+
+        init_simulation_time(app);
+        app.add_asset::<CharacterSettings>()
+            .add_asset_loader(
+                RonAssetLoader::<CharacterSettings>::new(&["character.ron"])
+                    .with_upgrade(upgrade_legacy_heights),
+            )
+            .add_system(add_kinematic_controllers)
+            .add_system(apply_character_tuning)
+            .insert_resource(self.settings.clone())
+            .insert_resource(self.keymap.clone())
+            .init_resource::<Bindings>()
+            .init_resource::<InputSettings>()
+            .init_resource::<SelectedSpawn>()
+            .add_event::<CharacterStateChanged>()
+            .init_resource::<CharacterStateMachine>()
             .add_plugin(InputManagerPlugin::<CharacterMovement>::default())
             .add_plugin(InputManagerPlugin::<CharacterActions>::default())
-            .add_startup_system(spawn_player)
-            .add_stage_after(
-                PhysicsStages::Writeback,
+            .add_plugin(RebindPlugin::<CharacterMovement>::default())
+            .add_plugin(RebindPlugin::<CharacterActions>::default())
+            .add_plugin(ActionBufferPlugin::<CharacterActions>::default())
+            .add_plugin(GesturePlugin::<CharacterMovement>::default())
+            .add_plugin(GesturePlugin::<CharacterActions>::default())
+            .add_system_set_to_stage(
+                CoreStage::PreUpdate,
+                SystemSet::new()
+                    .after(InputManagerSystem::Update)
+                    .with_system(
+                        apply_input_assists
+                            .before(read_device_input)
+                            .before(ActionBufferSystem)
+                            .before(GestureSystem),
+                    )
+                    .with_system(read_device_input)
+                    .with_system(
+                        drive_character_input
+                            .before(ActionBufferSystem)
+                            .before(GestureSystem),
+                    ),
+            )
+            .add_system(detach_input_maps)
+            // Steps feed rapier the same frame, so what they move is there once it wrote back
+            .add_stage_before(
+                PhysicsStages::SyncBackend,
                 CharacterControllerStages::Physics,
-                SystemStage::parallel(),
+                SystemStage::parallel().with_run_criteria(simulation_running),
             )
-            .add_state_to_stage(CharacterControllerStages::Physics, CharacterState::Idle)
             .add_system_set_to_stage(
                 CharacterControllerStages::Physics,
                 SystemSet::new()
+                    .with_run_criteria(
+                        FixedTimestep::step(CHARACTER_TIMESTEP)
+                            .with_label(CHARACTER_TIMESTEP_LABEL),
+                    )
+                    .with_system(advance_interpolation)
                     .with_system(update_player_speed)
                     .with_system(update_gravity_force)
                     .with_system(update_action_force)
-                    .with_system(update_movement_force),
+                    .with_system(update_movement_force)
+                    .with_system(
+                        update_player_pos
+                            .after(update_player_speed)
+                            .after(update_gravity_force)
+                            .after(update_action_force)
+                            .after(update_movement_force),
+                    ),
             )
             .add_system_set(
                 SystemSet::new()
-                    .with_system(update_player_pos)
-                    .with_system(update_player_state),
+                    .with_run_criteria(simulation_running)
+                    .with_system(update_grounded.before(update_dynamic_output))
+                    .with_system(update_dynamic_output.before(update_player_state))
+                    .with_system(update_player_state)
+                    .with_system(update_player_height.after(update_player_state)),
+            )
+            .add_system_set_to_stage(
+                CoreStage::PostUpdate,
+                SystemSet::new()
+                    .before(TransformSystem::TransformPropagate)
+                    .with_system(record_interpolation)
+                    .with_system(
+                        interpolate_visuals
+                            .label(CharacterInterpolationSystem)
+                            .after(record_interpolation),
+                    ),
             );
     }
 }
 
-#[derive(Default, Debug)]
-struct CharacterForces {
+/// Per-step velocity contributions in the character's local space
+#[derive(Default, Debug, Clone, Copy, PartialEq)]
+pub struct CharacterForces {
     gravity: Vec3,
     movement: Vec3,
     actions: Vec3,
 }
 
-struct CharacterSpeedSettings {
-    pub base: CharacterSpeed,
-    pub run: CharacterSpeed,
-    pub crouch: CharacterSpeed,
-    pub slide: CharacterSpeed,
+impl CharacterForces {
+    pub fn gravity(&self) -> Vec3 {
+        self.gravity
+    }
+
+    pub fn movement(&self) -> Vec3 {
+        self.movement
+    }
+
+    pub fn actions(&self) -> Vec3 {
+        self.actions
+    }
+
+    pub fn total(&self) -> Vec3 {
+        self.gravity + self.movement + self.actions
+    }
+}
+
+/// World space velocity contributed by other systems, summed into every character step
+///
+/// Every backend moves the character at this velocity on top of its own, dynamic bodies included.
+#[derive(Component, Debug, Default, Clone, Copy, PartialEq, Deref, DerefMut)]
+pub struct ExternalForces(pub Vec3);
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CharacterSpeedSettings {
+    pub base: f32,
+    pub run: f32,
+    pub crouch: f32,
+    pub slide: f32,
+    /// How fast the current speed approaches the target tier, per second
+    pub acceleration: f32,
+}
+
+impl Default for CharacterSpeedSettings {
+    fn default() -> Self {
+        CharacterSpeedSettings {
+            base: 10.0,
+            run: 20.0,
+            crouch: 5.0,
+            slide: 25.0,
+            acceleration: 8.0,
+        }
+    }
+}
+
+/// How the character body is moved
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CharacterBackend {
+    /// Systems feed a per-step translation to the kinematic character controller
+    #[default]
+    KinematicPositionBased,
+    /// Systems write the body velocity, which rapier integrates with CCD
+    KinematicVelocityBased,
+    /// A rotation-locked dynamic body pushed by impulses, simulated by rapier
+    Dynamic,
+}
+
+impl CharacterBackend {
+    /// Kinematic bodies are moved through rapier's character controller
+    pub fn kinematic(&self) -> bool {
+        *self != CharacterBackend::Dynamic
+    }
+
+    pub fn rigid_body(&self) -> RigidBody {
+        match self {
+            CharacterBackend::KinematicPositionBased => RigidBody::KinematicPositionBased,
+            CharacterBackend::KinematicVelocityBased => RigidBody::KinematicVelocityBased,
+            CharacterBackend::Dynamic => RigidBody::Dynamic,
+        }
+    }
+}
+
+/// Layers the character is on and collides with, as bits of rapier `Group`s
+///
+/// Groups 31 and 32 are taken by pushed and carried objects.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CharacterCollisionSettings {
+    pub memberships: u32,
+    /// Layers the character collides with, also used by its ground probe and sweeps
+    pub filters: u32,
+    /// Layers the character exchanges contact forces with
+    pub solver_memberships: u32,
+    pub solver_filters: u32,
+}
+
+impl Default for CharacterCollisionSettings {
+    fn default() -> Self {
+        CharacterCollisionSettings {
+            memberships: Group::ALL.bits(),
+            filters: Group::ALL.bits(),
+            solver_memberships: Group::ALL.bits(),
+            solver_filters: Group::ALL.bits(),
+        }
+    }
+}
+
+impl CharacterCollisionSettings {
+    /// Stops colliding with `groups`, e.g. debris, triggers or other players
+    pub fn ignoring(mut self, groups: Group) -> Self {
+        self.filters &= !groups.bits();
+        self.solver_filters &= !groups.bits();
+        self
+    }
+
+    pub fn collision_groups(&self) -> CollisionGroups {
+        CollisionGroups::new(
+            Group::from_bits_truncate(self.memberships),
+            Group::from_bits_truncate(self.filters),
+        )
+    }
+
+    pub fn solver_groups(&self) -> SolverGroups {
+        SolverGroups::new(
+            Group::from_bits_truncate(self.solver_memberships),
+            Group::from_bits_truncate(self.solver_filters),
+        )
+    }
+
+    /// Filter for scene queries on behalf of `entity`, hitting what it collides with
+    pub fn query_filter(&self, entity: Entity) -> QueryFilter<'static> {
+        QueryFilter::default()
+            .exclude_rigid_body(entity)
+            .exclude_sensors()
+            .groups(self.collision_groups())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CharacterAutostepSettings {
+    pub max_height: f32,
+    pub min_width: f32,
+}
+
+impl Default for CharacterAutostepSettings {
+    fn default() -> Self {
+        CharacterAutostepSettings {
+            max_height: 0.5,
+            min_width: 0.2,
+        }
+    }
+}
+
+/// Character tuning, also loadable as a `.character.ron` asset
+#[derive(Resource, TypeUuid, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[uuid = "4b0f3c3e-6a3f-4a55-9d8e-2f6f4f0b1c21"]
+#[serde(default)]
+pub struct CharacterSettings {
+    pub backend: CharacterBackend,
+    /// Sweeps fast velocity based and dynamic movement, so thin walls can't be skipped
+    ///
+    /// Position based movement is always swept, rapier's character controller casts the capsule
+    /// along the whole move.
+    pub ccd: bool,
+    pub speed: CharacterSpeedSettings,
+    /// Capsule height from feet to head while crouching or sliding
+    pub crouching_height: f32,
+    /// Seconds it takes to move between standing and crouching height
+    pub height_transition: f32,
+    /// Seconds a slide started by tapping crouch lasts
+    pub slide_duration: f32,
+    /// Apex of a standing jump, in meters
+    pub jump_height: f32,
+    /// Initial jump velocity, overriding the one derived from `jump_height`
+    pub jump_force: Option<f32>,
+    /// Capsule height from feet to head while standing
+    pub standing_height: f32,
+    /// Capsule radius, heights below its diameter leave a ball
+    pub radius: f32,
+    pub mass: f32,
+    pub ground: CharacterGroundSettings,
+    pub collision: CharacterCollisionSettings,
+    pub autostep: Option<CharacterAutostepSettings>,
+    /// Degrees
+    pub max_slope_climb_angle: f32,
+    /// Degrees
+    pub min_slope_slide_angle: f32,
+}
+
+impl Default for CharacterSettings {
+    fn default() -> Self {
+        CharacterSettings {
+            backend: Default::default(),
+            ccd: true,
+            speed: Default::default(),
+            crouching_height: 3.0,
+            height_transition: 0.15,
+            slide_duration: 0.6,
+            jump_height: 1.5,
+            jump_force: None,
+            standing_height: 4.0,
+            radius: 1.0,
+            mass: 30.0,
+            ground: Default::default(),
+            collision: Default::default(),
+            autostep: Some(Default::default()),
+            max_slope_climb_angle: 45.0,
+            min_slope_slide_angle: 30.0,
+        }
+    }
+}
+
+/// Heights older `.character.ron` files measured without the capsule caps
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct LegacyHeights {
+    height: Option<f32>,
+    crouch_height: Option<f32>,
+}
+
+/// Turns `height` and `crouch_height` left in older files into heights from feet to head
+fn upgrade_legacy_heights(bytes: &[u8], settings: &mut CharacterSettings) {
+    let legacy: LegacyHeights = ron::de::from_bytes(bytes).unwrap_or_default();
+    let caps = 2.0 * settings.radius;
+
+    if let Some(height) = legacy.height {
+        warn!("Converting `height` to `standing_height`, which includes the caps");
+        settings.standing_height = height + caps;
+    }
+    if let Some(height) = legacy.crouch_height {
+        warn!("Converting `crouch_height` to `crouching_height`, which includes the caps");
+        settings.crouching_height = height + caps;
+    }
+}
+
+impl CharacterSettings {
+    pub fn collider(&self) -> Collider {
+        self.collider_with_height(self.standing_height)
+    }
+
+    pub fn collider_with_height(&self, height: f32) -> Collider {
+        Collider::capsule_y(self.segment_half_height(height), self.radius)
+    }
+
+    /// Half the length of the straight part of a capsule `height` tall, from the character origin
+    /// to the center of either cap
+    pub fn segment_half_height(&self, height: f32) -> f32 {
+        (height / 2. - self.radius).max(0.)
+    }
+
+    /// Eye position above the character origin for a capsule of `height`
+    pub fn eye_height(&self, height: f32) -> f32 {
+        self.segment_half_height(height) + self.radius * 0.5
+    }
+
+    /// Downward acceleration the character falls with
+    pub fn gravity(&self) -> f32 {
+        match self.backend {
+            // Rapier integrates plain gravity on dynamic bodies
+            CharacterBackend::Dynamic => GRAVITY,
+            _ => GRAVITY * self.mass,
+        }
+    }
+
+    /// Initial upward velocity of a jump
+    pub fn jump_velocity(&self) -> f32 {
+        self.jump_force
+            .unwrap_or_else(|| (2.0 * self.gravity() * self.jump_height).sqrt())
+    }
+
+    pub fn radius(&self) -> f32 {
+        self.radius
+    }
+
+    /// Distance from the character origin to the bottom of its capsule
+    pub fn foot_offset(&self) -> f32 {
+        self.segment_half_height(self.standing_height) + self.radius
+    }
+
+    /// Highest step climbed, never more than the bottom half of the crouched capsule
+    pub fn autostep_height(&self) -> f32 {
+        let limit =
+            self.segment_half_height(self.crouching_height.min(self.standing_height)) + self.radius;
+        self.autostep
+            .map_or(0.0, |autostep| autostep.max_height.min(limit))
+    }
+
+    /// Copies the tuned parameters onto a rapier character controller
+    pub fn apply_to(&self, controller: &mut KinematicCharacterController) {
+        controller.autostep = self.autostep.map(|autostep| CharacterAutostep {
+            max_height: CharacterLength::Absolute(self.autostep_height()),
+            min_width: CharacterLength::Absolute(autostep.min_width),
+            include_dynamic_bodies: false,
+        });
+        controller.max_slope_climb_angle = self.max_slope_climb_angle.to_radians();
+        controller.min_slope_slide_angle = self.min_slope_slide_angle.to_radians();
+        controller.filter_groups = Some(self.collision.collision_groups());
+
+        // Follow the ground as far down as it still counts as grounded, rather than hovering
+        let snap = self.ground.release_distance.max(self.ground.cast_distance);
+        controller.snap_to_ground = Some(CharacterLength::Absolute(snap));
+    }
+
+    pub fn kinematic_controller(&self) -> KinematicCharacterController {
+        let mut controller = KinematicCharacterController {
+            offset: CharacterLength::Absolute(0.05),
+            slide: false,
+            apply_impulse_to_dynamic_bodies: true,
+            ..Default::default()
+        };
+        self.apply_to(&mut controller);
+        controller
+    }
+}
+
+/// Simulated positions of the last two character steps, as rapier resolved them
+#[derive(Component, Debug, Default, Clone, Copy)]
+pub struct CharacterInterpolation {
+    previous: Vec3,
+    current: Vec3,
+    /// Steps ran this frame, recorded once rapier wrote them back
+    stepped: bool,
+}
+
+impl CharacterInterpolation {
+    /// Position blended between the last two steps by `alpha` in `0..=1`
+    pub fn translation(&self, alpha: f32) -> Vec3 {
+        self.previous.lerp(self.current, alpha)
+    }
+
+    /// Average velocity over the last step
+    pub fn velocity(&self) -> Vec3 {
+        (self.current - self.previous) / CHARACTER_TIMESTEP as f32
+    }
+
+    /// Stops blending from the last steps, e.g. after teleporting
+    pub fn reset(&mut self, translation: Vec3) {
+        self.previous = translation;
+        self.current = translation;
+        self.stepped = false;
+    }
+}
+
+/// Capsule height of a character, eased towards the height of its state
+#[derive(Component, Debug, Default, Clone, Copy, PartialEq)]
+pub struct CharacterHeight {
+    current: f32,
+    target: f32,
+}
+
+impl CharacterHeight {
+    pub fn new(height: f32) -> Self {
+        CharacterHeight {
+            current: height,
+            target: height,
+        }
+    }
+
+    pub fn current(&self) -> f32 {
+        self.current
+    }
+
+    pub fn target(&self) -> f32 {
+        self.target
+    }
+}
+
+/// Child of a character rendered at the interpolated position instead of the simulated one
+#[derive(Component, Debug, Default, Clone, Copy)]
+pub struct CharacterVisual {
+    /// Local offset from the character origin
+    pub offset: Vec3,
+}
+
+#[derive(Resource)]
+struct CharacterTuningPath(String);
+
+/// Everything a controllable character needs
+///
+/// Kinematic backends get their `KinematicCharacterController` once spawned.
+#[derive(Bundle)]
+pub struct CharacterBundle {
+    pub body: RigidBody,
+    pub output: KinematicCharacterControllerOutput,
+    pub collider: Collider,
+    pub collision_groups: CollisionGroups,
+    pub solver_groups: SolverGroups,
+    pub mass: ColliderMassProperties,
+    pub locked_axes: LockedAxes,
+    pub restitution: Restitution,
+    pub velocity: Velocity,
+    pub ccd: Ccd,
+    pub impulse: ExternalImpulse,
+    pub external_forces: ExternalForces,
+    pub grounded: Grounded,
+    pub height: CharacterHeight,
+    pub abilities: CharacterAbilities,
+    pub buffer: ActionBuffer<CharacterActions>,
+    pub assists: InputAssists,
+    pub interactor: Interactor,
+    pub carrier: Carrier,
+    pub pusher: Pusher,
+    pub weapon: HitscanWeapon,
+    pub rider: PlatformRider,
+    pub last_valid: LastValidPosition,
+    pub knockback: Knockback,
+    pub one_way: OneWayPassage,
+    pub movement_gestures: ActionGestures<CharacterMovement>,
+    pub action_gestures: ActionGestures<CharacterActions>,
+    pub state: CharacterState,
+    pub input: CharacterInput,
+    pub speed: CharacterSpeed,
+    pub interpolation: CharacterInterpolation,
+    pub character: CharacterMovementController,
+    pub movement_input: InputManagerBundle<CharacterMovement>,
+    pub actions_input: InputManagerBundle<CharacterActions>,
+    pub transform: TransformBundle,
+}
+
+impl CharacterBundle {
+    pub fn new(
+        settings: &CharacterSettings,
+        keymap: &CharacterKeymap,
+        transform: Transform,
+    ) -> Self {
+        CharacterBundle {
+            body: settings.backend.rigid_body(),
+            output: Default::default(),
+            collider: settings.collider(),
+            collision_groups: settings.collision.collision_groups(),
+            solver_groups: settings.collision.solver_groups(),
+            mass: ColliderMassProperties::Mass(settings.mass),
+            locked_axes: LockedAxes::ROTATION_LOCKED_X | LockedAxes::ROTATION_LOCKED_Z,
+            restitution: Restitution::coefficient(1.0),
+            velocity: Default::default(),
+            ccd: Ccd {
+                enabled: settings.ccd,
+            },
+            impulse: Default::default(),
+            external_forces: Default::default(),
+            grounded: Default::default(),
+            height: CharacterHeight::new(settings.standing_height),
+            abilities: CharacterAbilities::standard(),
+            buffer: ActionBuffer::default().with_window(CharacterActions::Jump, JUMP_BUFFER),
+            assists: Default::default(),
+            interactor: Default::default(),
+            carrier: Default::default(),
+            pusher: Default::default(),
+            weapon: Default::default(),
+            rider: Default::default(),
+            last_valid: Default::default(),
+            knockback: Default::default(),
+            one_way: Default::default(),
+            movement_gestures: Default::default(),
+            action_gestures: Default::default(),
+            state: CharacterState::Idle,
+            input: Default::default(),
+            speed: CharacterSpeed(settings.speed.base),
+            interpolation: Default::default(),
+            character: CharacterMovementController::new(settings.clone()),
+            movement_input: InputManagerBundle {
+                input_map: keymap.movement.clone(),
+                action_state: ActionState::default(),
+            },
+            actions_input: InputManagerBundle {
+                input_map: keymap.actions.clone(),
+                action_state: ActionState::default(),
+            },
+            transform: TransformBundle::from(transform),
+        }
+    }
+}
+
+/// Handle to the tuning asset the plugin keeps in sync
+#[derive(Resource, Deref)]
+pub struct CharacterTuning(pub Handle<CharacterSettings>);
+
+/// Character bindings, replaced by `BindingsPlugin` when its asset loads
+#[derive(Resource, Debug, Clone)]
+pub struct CharacterKeymap {
+    pub movement: InputMap<CharacterMovement>,
+    pub actions: InputMap<CharacterActions>,
+}
+
+impl Default for CharacterKeymap {
+    fn default() -> Self {
+        Bindings::default().character_keymap()
+    }
 }
 
 #[derive(Component)]
 pub struct CharacterMovementController {
-    speed: CharacterSpeedSettings,
+    settings: CharacterSettings,
     forces: CharacterForces,
-    jump_force: f32,
     grounded: bool,
-    height: f32,
-    mass: f32,
+    state_entered_at: f32,
+    jump_consumed: bool,
+    /// Vertical external velocity a dynamic body was last given, taken back off as it changes
+    external_lift: f32,
 }
 
 impl CharacterMovementController {
+    pub fn new(settings: CharacterSettings) -> Self {
+        CharacterMovementController {
+            settings,
+            forces: Default::default(),
+            grounded: false,
+            state_entered_at: 0.0,
+            jump_consumed: false,
+            external_lift: 0.0,
+        }
+    }
+
+    pub fn settings(&self) -> &CharacterSettings {
+        &self.settings
+    }
+
+    pub fn forces(&self) -> &CharacterForces {
+        &self.forces
+    }
+
+    pub fn ability_context<'a>(
+        &'a self,
+        entity: Entity,
+        state: CharacterState,
+        actions: &'a ActionState<CharacterActions>,
+        grounded: &Grounded,
+        time: &SimulationTime,
+    ) -> AbilityContext<'a> {
+        AbilityContext {
+            entity,
+            state,
+            settings: &self.settings,
+            actions,
+            movement: self.forces.movement,
+            grounded: grounded.get(),
+            time_in_state: time.elapsed_seconds() - self.state_entered_at,
+        }
+    }
+
+    pub fn settings_mut(&mut self) -> &mut CharacterSettings {
+        &mut self.settings
+    }
+
+    /// Ground contact after probing, coyote time and hysteresis, as movement sees it
     pub fn grounded(&self) -> bool {
         self.grounded
     }
@@ -72,181 +728,550 @@ impl CharacterMovementController {
     pub fn set_grounded(&mut self, grounded: bool) {
         self.grounded = grounded;
     }
+
+    /// Drops all accumulated forces, e.g. after teleporting the character
+    pub fn reset(&mut self) {
+        self.forces = Default::default();
+        self.jump_consumed = false;
+        self.external_lift = 0.0;
+    }
+
+    /// `SimulationTime` at which the current state was entered
+    pub fn state_entered_at(&self) -> f32 {
+        self.state_entered_at
+    }
+
+    pub fn set_state_entered_at(&mut self, timestamp: f32) {
+        self.state_entered_at = timestamp;
+    }
 }
 
-#[derive(Actionlike, Clone, Debug, Copy, PartialEq, Eq)]
+#[derive(Actionlike, Clone, Debug, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum CharacterMovement {
     Left,
     Right,
     Back,
     Forward,
+    /// Direction and tilt of a stick, x to the right and y forward
+    Move,
 }
 
 impl CharacterMovement {
+    /// Direction of a digital input, `Move` is read through its axis pair instead
     pub fn into_vec(self) -> Vec3 {
         match self {
             CharacterMovement::Right => Vec3::X,
             CharacterMovement::Left => Vec3::NEG_X,
             CharacterMovement::Back => Vec3::Z,
             CharacterMovement::Forward => Vec3::NEG_Z,
+            CharacterMovement::Move => Vec3::ZERO,
         }
     }
 }
 
-#[derive(Actionlike, Clone, Debug, Copy, PartialEq, Eq)]
+#[derive(Actionlike, Clone, Debug, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum CharacterActions {
     Jump,
     Sprint,
     Crouch,
+    Interact,
+    Throw,
+    Fire,
 }
 
-fn spawn_player(mut commands: Commands) {
-    let settings = CharacterMovementController {
-        speed: CharacterSpeedSettings {
-            base: CharacterSpeed(10),
-            run: CharacterSpeed(20),
-            crouch: CharacterSpeed(5),
-            slide: CharacterSpeed(25),
-        },
-        forces: Default::default(),
-        jump_force: 30.0,
-        height: 2.0,
-        mass: 30.0,
-        grounded: false,
+/// Spawns the player once a level provides the selected `PlayerSpawn`
+fn spawn_player(
+    mut commands: Commands,
+    spawns: PlayerSpawns,
+    settings: Res<CharacterSettings>,
+    keymap: Res<CharacterKeymap>,
+    mut spawned: Local<bool>,
+) {
+    if *spawned {
+        return;
+    }
+    let Some(transform) = spawns.selected().and_then(|spawn| spawns.transform(spawn)) else {
+        return;
     };
+
     commands
-        .spawn(RigidBody::KinematicPositionBased)
-        .insert(KinematicCharacterController {
-            offset: CharacterLength::Absolute(0.05),
-            slide: false,
-            autostep: Some(CharacterAutostep {
-                max_height: CharacterLength::Absolute(0.5),
-                min_width: CharacterLength::Absolute(0.2),
-                include_dynamic_bodies: false,
-            }),
-            max_slope_climb_angle: 45.0_f32.to_radians(),
-            min_slope_slide_angle: 30.0_f32.to_radians(),
-            apply_impulse_to_dynamic_bodies: true,
-            snap_to_ground: Some(CharacterLength::Absolute(0.1)),
-            ..Default::default()
-        })
-        .insert(Collider::capsule_y(settings.height / 2., 1.0))
-        .insert(Restitution::coefficient(1.0))
-        .insert(TransformBundle::from(Transform::from_xyz(0.0, 4.0, 0.0)))
-        .insert(settings)
-        .insert(InputManagerBundle::<CharacterMovement> {
-            input_map: InputMap::default()
-                .insert(KeyCode::W, CharacterMovement::Forward)
-                .insert(KeyCode::A, CharacterMovement::Left)
-                .insert(KeyCode::S, CharacterMovement::Back)
-                .insert(KeyCode::D, CharacterMovement::Right)
-                .build(),
-            action_state: ActionState::default(),
-        })
-        .insert(InputManagerBundle::<CharacterActions> {
-            input_map: InputMap::default()
-                .insert(KeyCode::Space, CharacterActions::Jump)
-                .insert(KeyCode::LControl, CharacterActions::Crouch)
-                .insert(KeyCode::LShift, CharacterActions::Sprint)
-                .build(),
-            action_state: ActionState::default(),
-        })
-        .insert(KinematicCharacterControllerOutput::default());
-}
-
-fn update_gravity_force(
+        .spawn(CharacterBundle::new(&settings, &keymap, transform))
+        .with_children(|character| {
+            // Meshes and attachments added under it move smoothly between steps
+            character.spawn((CharacterVisual::default(), SpatialBundle::default()));
+        });
+    *spawned = true;
+}
+
+fn load_character_tuning(
+    mut commands: Commands,
+    path: Res<CharacterTuningPath>,
+    asset_server: Res<AssetServer>,
+) {
+    commands.insert_resource(CharacterTuning(asset_server.load(path.0.as_str())));
+}
+
+/// Gives characters with a kinematic backend the controller that moves them
+fn add_kinematic_controllers(
+    mut commands: Commands,
+    q: Query<
+        (Entity, &CharacterMovementController),
+        (
+            Added<CharacterMovementController>,
+            Without<KinematicCharacterController>,
+        ),
+    >,
+) {
+    for (entity, character) in q.iter() {
+        if character.settings.backend.kinematic() {
+            commands
+                .entity(entity)
+                .insert(character.settings.kinematic_controller());
+        }
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn apply_character_tuning(
+    mut commands: Commands,
+    mut events: EventReader<AssetEvent<CharacterSettings>>,
+    mut q: Query<
+        (
+            Entity,
+            &mut CharacterMovementController,
+            Option<&mut KinematicCharacterController>,
+            &mut Collider,
+            &mut RigidBody,
+            &mut Ccd,
+            (&mut CollisionGroups, &mut SolverGroups),
+            &mut ColliderMassProperties,
+            &CharacterHeight,
+        ),
+        Without<Ragdolled>,
+    >,
+    mut settings: ResMut<CharacterSettings>,
+    assets: Res<Assets<CharacterSettings>>,
+    tuning: Option<Res<CharacterTuning>>,
+) {
+    let Some(tuning) = tuning else { return };
+
+    for event in events.iter() {
+        let handle = match event {
+            AssetEvent::Created { handle } | AssetEvent::Modified { handle } => handle,
+            AssetEvent::Removed { .. } => continue,
+        };
+
+        if *handle != tuning.0 {
+            continue;
+        }
+
+        let Some(tuned) = assets.get(handle) else {
+            continue;
+        };
+        info!("Applying character tuning");
+        *settings = tuned.clone();
+
+        for components in q.iter_mut() {
+            let (
+                entity,
+                mut character,
+                controller,
+                mut collider,
+                mut body,
+                mut ccd,
+                (mut collision_groups, mut solver_groups),
+                mut mass,
+                height,
+            ) = components;
+
+            // Switching backends adds or drops the kinematic controller
+            match (controller, tuned.backend.kinematic()) {
+                (Some(mut controller), true) => tuned.apply_to(&mut controller),
+                (None, true) => {
+                    commands.entity(entity).insert(tuned.kinematic_controller());
+                }
+                (Some(_), false) => {
+                    commands
+                        .entity(entity)
+                        .remove::<KinematicCharacterController>();
+                }
+                (None, false) => {}
+            }
+            *mass = ColliderMassProperties::Mass(tuned.mass);
+            *collision_groups = tuned.collision.collision_groups();
+            *solver_groups = tuned.collision.solver_groups();
+            *collider = tuned.collider_with_height(height.current());
+            *body = tuned.backend.rigid_body();
+            ccd.enabled = tuned.ccd;
+            *character.settings_mut() = tuned.clone();
+        }
+    }
+}
+
+fn advance_interpolation(mut q: Query<&mut CharacterInterpolation>) {
+    for mut interpolation in q.iter_mut() {
+        interpolation.previous = interpolation.current;
+        interpolation.stepped = true;
+    }
+}
+
+/// Takes the position rapier resolved the steps of this frame to
+fn record_interpolation(mut q: Query<(&mut CharacterInterpolation, &Transform)>) {
+    for (mut interpolation, transform) in q.iter_mut() {
+        if interpolation.stepped {
+            interpolation.current = transform.translation;
+            interpolation.stepped = false;
+        }
+    }
+}
+
+fn interpolate_visuals(
+    characters: Query<(&CharacterInterpolation, &Transform, &Children)>,
+    mut visuals: Query<(&mut Transform, &CharacterVisual), Without<CharacterInterpolation>>,
+    timesteps: Res<FixedTimesteps>,
+) {
+    let alpha = timesteps
+        .get(CHARACTER_TIMESTEP_LABEL)
+        .map(|step| step.overstep_percentage() as f32)
+        .unwrap_or(1.0);
+
+    for (interpolation, transform, children) in characters.iter() {
+        let lag = interpolation.translation(alpha) - transform.translation;
+        let lag = transform.rotation.inverse() * lag;
+
+        for child in children.iter() {
+            if let Ok((mut visual_transform, visual)) = visuals.get_mut(*child) {
+                visual_transform.translation = visual.offset + lag;
+            }
+        }
+    }
+}
+
+/// Fills the controller output rapier only produces for kinematic bodies
+fn update_dynamic_output(
     mut q: Query<(
-        &mut CharacterMovementController,
-        &KinematicCharacterControllerOutput,
+        &CharacterMovementController,
+        &Grounded,
+        &Velocity,
+        &mut KinematicCharacterControllerOutput,
     )>,
-    time: Res<Time>,
 ) {
-    let (mut character, physics) = q.single_mut();
+    for (character, grounded, velocity, mut output) in q.iter_mut() {
+        if character.settings.backend != CharacterBackend::Dynamic {
+            continue;
+        }
 
-    let mass = character.mass;
-    let gravity = Vec3::new(0.0, -9.81, 0.0);
+        output.grounded = grounded.get();
+        output.desired_translation = velocity.linvel * CHARACTER_TIMESTEP as f32;
+        output.effective_translation = output.desired_translation;
+    }
+}
 
-    if physics.grounded {
-        character.forces.gravity = Vec3::ZERO;
-    } else {
-        character.forces.gravity += gravity * mass * time.delta_seconds();
-    };
+fn update_player_height(
+    mut q: Query<(
+        &CharacterMovementController,
+        &CharacterState,
+        &mut CharacterHeight,
+        &mut Collider,
+        &mut Transform,
+    )>,
+    time: Res<SimulationTime>,
+) {
+    for (character, state, mut height, mut collider, mut transform) in q.iter_mut() {
+        let settings = &character.settings;
+        height.target = match state {
+            CharacterState::Crouch | CharacterState::Slide => settings.crouching_height,
+            _ => settings.standing_height,
+        };
+
+        if height.current == height.target {
+            continue;
+        }
+
+        let range = (settings.standing_height - settings.crouching_height).abs();
+        let step = match settings.height_transition > 0.0 {
+            true => range / settings.height_transition * time.delta_seconds(),
+            false => f32::INFINITY,
+        };
+        let delta = (height.target - height.current).clamp(-step, step);
+
+        height.current += delta;
+        *collider = settings.collider_with_height(height.current);
+
+        // Resize around the feet rather than the capsule center
+        transform.translation.y += delta / 2.;
+    }
+}
+
+fn update_gravity_force(mut q: Query<(&mut CharacterMovementController, &Grounded)>) {
+    for (mut character, grounded) in q.iter_mut() {
+        let mass = character.settings.mass;
+        let gravity = Vec3::new(0.0, -GRAVITY, 0.0);
+
+        // Rapier applies gravity to dynamic bodies itself
+        if grounded.get() || character.settings.backend == CharacterBackend::Dynamic {
+            character.forces.gravity = Vec3::ZERO;
+        } else {
+            character.forces.gravity += gravity * mass * CHARACTER_TIMESTEP as f32;
+        };
+    }
 }
 
 fn update_movement_force(
     mut q: Query<(
         &mut CharacterMovementController,
-        &ActionState<CharacterMovement>,
+        &CharacterSpeed,
+        &CharacterInput,
     )>,
-    speed: Res<State<CharacterSpeed>>,
 ) {
-    let (mut character, movement) = q.single_mut();
-    let speed = speed.current().get();
-
-    character.forces.movement = movement
-        .get_pressed()
-        .iter()
-        .map(|movement| movement.into_vec())
-        .sum::<Vec3>()
-        .mul(speed)
-        .clamp_length(0., speed);
+    for (mut character, speed, input) in q.iter_mut() {
+        // A half tilted stick walks at half speed
+        character.forces.movement = input.movement.clamp_length_max(1.0).mul(speed.get());
+    }
 }
 
 fn update_player_speed(
-    q: Query<&CharacterMovementController>,
-    state: Res<State<CharacterState>>,
-    mut speed: ResMut<State<CharacterSpeed>>,
+    mut q: Query<(
+        Entity,
+        &CharacterMovementController,
+        &CharacterAbilities,
+        &CharacterState,
+        &ActionState<CharacterActions>,
+        &Grounded,
+        &mut CharacterSpeed,
+    )>,
+    time: Res<SimulationTime>,
 ) {
-    let character = q.single();
-
-    let new_speed: Option<CharacterSpeed> = match state.current() {
-        CharacterState::Run => Some(character.speed.run),
-        CharacterState::Walk => Some(character.speed.base),
-        CharacterState::Slide => Some(character.speed.slide),
-        CharacterState::Crouch => Some(character.speed.crouch),
-        _ => None,
-    };
+    for (entity, character, abilities, state, actions, grounded, mut speed) in q.iter_mut() {
+        let ctx = character.ability_context(entity, *state, actions, grounded, &time);
+        let target = match abilities.iter().find_map(|ability| ability.speed(&ctx)) {
+            Some(target) => target,
+            None if ctx.state == CharacterState::Walk => character.settings.speed.base,
+            // Keep momentum while idle or airborne
+            None => continue,
+        };
 
-    if let Some(new_speed) = new_speed {
-        speed.set(new_speed).unwrap_or_default();
+        // Frame-rate independent exponential approach to the target tier
+        let dt = CHARACTER_TIMESTEP as f32;
+        let t = 1.0 - (-character.settings.speed.acceleration * dt).exp();
+        speed.0 += (target - speed.0) * t;
     }
 }
 
 fn update_action_force(
-    mut q: Query<&mut CharacterMovementController>,
-    state: Res<State<CharacterState>>,
+    mut q: Query<(
+        Entity,
+        &mut CharacterMovementController,
+        &mut CharacterAbilities,
+        &CharacterState,
+        &ActionState<CharacterActions>,
+        &Grounded,
+    )>,
+    time: Res<SimulationTime>,
 ) {
-    let mut character = q.single_mut();
-    let move_direction = character.forces.movement;
+    for (entity, mut character, mut abilities, state, actions, grounded) in q.iter_mut() {
+        let ctx = character.ability_context(entity, *state, actions, grounded, &time);
+        let action_force = abilities
+            .iter_mut()
+            .map(|ability| ability.force(&ctx))
+            .sum::<Vec3>();
 
-    let action_force = match state.current() {
-        CharacterState::Slide => move_direction,
-        CharacterState::Jump => Vec3::new(0., character.jump_force, 0.),
-        _ => Vec3::ZERO,
-    };
-
-    character.forces.actions = action_force;
+        character.forces.actions = action_force;
+    }
 }
 
-#[rustfmt::skip]
+#[allow(clippy::type_complexity)]
 fn update_player_pos(
-    mut q: Query<(
-        &mut KinematicCharacterController,
-        &CharacterMovementController,
-        &Transform
-    )>,
-    time: Res<Time>,
+    mut q: Query<
+        (
+            Entity,
+            Option<&mut KinematicCharacterController>,
+            &mut Velocity,
+            &mut ExternalImpulse,
+            &mut CharacterMovementController,
+            &mut Transform,
+            &Collider,
+            Option<&ExternalForces>,
+            Option<&PlatformRider>,
+        ),
+        Without<Ragdolled>,
+    >,
+    rapier: Res<RapierContext>,
 ) {
-    let (mut controller,  character, transform) = q.single_mut();
+    for components in q.iter_mut() {
+        let (
+            entity,
+            controller,
+            mut velocity,
+            mut impulse,
+            mut character,
+            mut transform,
+            collider,
+            external,
+            rider,
+        ) = components;
+        let gravity = character.forces.gravity;
+        let movement = character.forces.movement;
+        let actions = character.forces.actions;
+        let external = external.map(|external| external.0).unwrap_or(Vec3::ZERO);
+
+        // Rotating platforms carry the character around and turn it with them
+        let (orbit, turn) = rider
+            .map(|rider| rider.orbit(transform.translation, CHARACTER_TIMESTEP as f32))
+            .unwrap_or((Vec3::ZERO, Quat::IDENTITY));
+        let external = external + orbit;
+        if turn != Quat::IDENTITY {
+            let (yaw, _, _) = turn.to_euler(EulerRot::YXZ);
+            transform.rotation = Quat::from_rotation_y(yaw) * transform.rotation;
+        }
+
+        let velocity_local = movement.add(actions).add(gravity);
+
+        match character.settings.backend {
+            CharacterBackend::KinematicPositionBased => {
+                let Some(mut controller) = controller else {
+                    continue;
+                };
+                let direction = (transform.rotation * velocity_local)
+                    .add(external)
+                    .mul(CHARACTER_TIMESTEP as f32);
 
-    let gravity = character.forces.gravity;
-    let movement = character.forces.movement;
-    let actions = character.forces.actions;
+                // Rapier resolves all steps of a frame at once, so their moves add up. The
+                // controller casts the capsule along the sum, which can't skip thin walls either.
+                let pending = controller.translation.unwrap_or(Vec3::ZERO);
+                controller.translation = Some(pending + direction);
+            }
+            CharacterBackend::KinematicVelocityBased => {
+                let mut linvel = transform.rotation * velocity_local + external;
+
+                // Rapier only sweeps dynamic bodies, stop short of what a fast step would skip
+                let step = linvel.length() * CHARACTER_TIMESTEP as f32;
+                if character.settings.ccd && step > character.settings.radius() {
+                    let direction = linvel / linvel.length();
+                    let filter = character.settings.collision.query_filter(entity);
+                    let position = transform.translation;
+                    let rotation = transform.rotation;
+                    let hit =
+                        rapier.cast_shape(position, rotation, direction, collider, step, filter);
+                    if let Some((_, toi)) = hit {
+                        linvel = direction * toi.toi / CHARACTER_TIMESTEP as f32;
+                    }
+                }
+                velocity.linvel = linvel;
+
+                // An empty move keeps the controller output (grounded) up to date
+                if let Some(mut controller) = controller {
+                    controller.translation = Some(Vec3::ZERO);
+                }
+            }
+            CharacterBackend::Dynamic => {
+                let mass = character.settings.mass;
+                let target = transform.rotation * movement.add(actions) + external;
+
+                // Match horizontal velocity, leaving the vertical axis to rapier
+                let delta = (target - velocity.linvel) * Vec3::new(1., 0., 1.);
+                impulse.impulse += delta * mass;
+
+                // Rapier integrates vertical velocity, so only changes in the external part go in
+                impulse.impulse.y += (external.y - character.external_lift) * mass;
+                character.external_lift = external.y;
+
+                // Jump once per jump action rather than every step of the state
+                if actions.y > 0.0 {
+                    if !character.jump_consumed {
+                        impulse.impulse.y += (actions.y - velocity.linvel.y).max(0.0) * mass;
+                        character.jump_consumed = true;
+                    }
+                } else {
+                    character.jump_consumed = false;
+                }
+            }
+        }
+    }
+}
 
-    let direction = movement
-        .add(actions) 
-        .add(gravity)
-        .mul(time.delta_seconds());
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::{scene::ScenePlugin, time::TimeUpdateStrategy};
+    use std::time::Duration;
 
-    controller.translation = Some(transform.rotation * direction);
+    /// Where a character running at `speed` towards a 5 cm wall 3 m ahead ends up
+    fn run_into_thin_wall(backend: CharacterBackend, speed: f32) -> Vec3 {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .add_plugin(TransformPlugin)
+            .add_plugin(HierarchyPlugin)
+            .add_plugin(AssetPlugin::default())
+            .add_plugin(ScenePlugin)
+            .add_asset::<Mesh>()
+            .add_plugin(RapierPhysicsPlugin::<NoUserData>::default())
+            .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f64(
+                CHARACTER_TIMESTEP,
+            )))
+            .add_stage_before(
+                PhysicsStages::SyncBackend,
+                CharacterControllerStages::Physics,
+                SystemStage::single(update_player_pos),
+            );
+        app.world.resource_mut::<RapierConfiguration>().gravity = Vec3::ZERO;
+
+        app.world.spawn((
+            RigidBody::Fixed,
+            Collider::cuboid(0.025, 10.0, 10.0),
+            TransformBundle::from(Transform::from_xyz(3.0, 0.0, 0.0)),
+        ));
+
+        let settings = CharacterSettings {
+            backend,
+            ..Default::default()
+        };
+        let character = app
+            .world
+            .spawn((
+                backend.rigid_body(),
+                settings.collider(),
+                Velocity::default(),
+                ExternalImpulse::default(),
+                settings.kinematic_controller(),
+                CharacterMovementController::new(settings),
+                TransformBundle::default(),
+            ))
+            .id();
+
+        // The wall only shows up in queries once rapier stepped with it
+        app.update();
+        app.world
+            .get_mut::<CharacterMovementController>(character)
+            .unwrap()
+            .forces
+            .movement = Vec3::X * speed;
+
+        for _ in 0..30 {
+            app.update();
+        }
+        app.world.get::<Transform>(character).unwrap().translation
+    }
+
+    #[test]
+    fn position_based_stops_at_thin_walls() {
+        let position = run_into_thin_wall(CharacterBackend::KinematicPositionBased, 300.0);
+        assert!(position.x < 3.0, "went through the wall to {position}");
+    }
+
+    #[test]
+    fn velocity_based_stops_at_thin_walls() {
+        let position = run_into_thin_wall(CharacterBackend::KinematicVelocityBased, 300.0);
+        assert!(position.x < 3.0, "went through the wall to {position}");
+    }
+
+    #[test]
+    fn legacy_heights_get_the_caps() {
+        let file = b"(height: 2.0, crouch_height: 1.0, radius: 1.0)";
+        let mut settings: CharacterSettings = ron::de::from_bytes(file).unwrap();
+        upgrade_legacy_heights(file, &mut settings);
+
+        assert_eq!(settings.standing_height, 4.0);
+        assert_eq!(settings.crouching_height, 3.0);
+    }
 }
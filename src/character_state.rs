@@ -4,95 +4,363 @@ use bevy_rapier3d::prelude::*;
 use leafwing_input_manager::prelude::*;
 use CharacterState::*;
 
-#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+/// Movement state of a character
+#[derive(Component, Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum CharacterState {
     Run,
+    #[default]
     Idle,
     Walk,
     Slide,
     Crouch,
     Jump,
     Fall,
+    /// Game specific state like a dash or grapple, named by the ability entering it
+    Special(&'static str),
 }
 
-#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq, Hash, Deref, DerefMut)]
-pub struct CharacterSpeed(pub i32);
+/// Parent states of the hierarchical character state machine
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CharacterStateGroup {
+    Grounded,
+    Airborne,
+    /// Special states have no built-in transitions, whatever enters them also leaves them
+    Special,
+}
+
+impl CharacterState {
+    pub fn group(&self) -> CharacterStateGroup {
+        match self {
+            Idle | Walk | Run | Crouch | Slide => CharacterStateGroup::Grounded,
+            Jump | Fall => CharacterStateGroup::Airborne,
+            Special(_) => CharacterStateGroup::Special,
+        }
+    }
+}
+
+/// Sent whenever a character transitions between movement states
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CharacterStateChanged {
+    pub entity: Entity,
+    pub from: CharacterState,
+    pub to: CharacterState,
+    /// `SimulationTime` at which the transition happened
+    pub timestamp: f32,
+    /// How long the character stayed in `from`, in seconds
+    pub duration: f32,
+}
+
+/// Current movement speed of a character, eased towards the tier of its state
+#[derive(Component, Debug, Default, Clone, Copy, PartialEq, Deref, DerefMut)]
+pub struct CharacterSpeed(pub f32);
 impl CharacterSpeed {
     pub fn get(&self) -> f32 {
-        self.0 as f32
+        self.0
     }
 }
 
-#[rustfmt::skip]
-pub fn update_player_state(
-    mut q: Query<(
-        &mut CharacterMovementController,
-        &KinematicCharacterControllerOutput,
-        &ActionState<CharacterActions>,
-    )>,
-    mut state: ResMut<State<CharacterState>>,
-) {
-    let (mut character, physics, actions) = q.single_mut();
-    let mut new_state = None;
-    let grounded = character.grounded();
-
-    match state.current() {
-        Run => {
-            if actions.just_released(CharacterActions::Sprint) { new_state = Some(Walk) }
-            if actions.just_pressed(CharacterActions::Crouch) { new_state = Some(Slide) }
-            if actions.just_pressed(CharacterActions::Jump) { new_state = Some(Jump) }
-            if physics.effective_translation == Vec3::ZERO { new_state = Some(Idle) }
-            if !grounded { new_state = Some(Fall) }
+/// Inputs a transition guard decides on
+pub struct TransitionContext<'a> {
+    pub entity: Entity,
+    pub state: CharacterState,
+    pub actions: &'a ActionState<CharacterActions>,
+    pub buffer: Option<&'a ActionBuffer<CharacterActions>>,
+    pub gestures: Option<&'a ActionGestures<CharacterActions>>,
+    pub settings: &'a CharacterSettings,
+    pub physics: &'a KinematicCharacterControllerOutput,
+    pub grounded: bool,
+    /// Seconds spent in the current state
+    pub time_in_state: f32,
+}
+
+impl TransitionContext<'_> {
+    /// Pressed this frame, or recently enough for the action buffer to remember it
+    pub fn buffered(&self, action: CharacterActions) -> bool {
+        self.actions.just_pressed(action)
+            || self.buffer.map_or(false, |buffer| buffer.buffered(&action))
+    }
+
+    /// Released before becoming a hold, or just pressed without gesture detection
+    pub fn tapped(&self, action: CharacterActions) -> bool {
+        match self.gestures {
+            Some(gestures) => gestures.just_tapped(action),
+            None => self.buffered(action),
+        }
+    }
+
+    /// Held past the hold threshold, never without gesture detection
+    pub fn held(&self, action: CharacterActions) -> bool {
+        self.gestures
+            .map_or(false, |gestures| gestures.held(action))
+    }
+
+    pub fn moving(&self) -> bool {
+        self.physics.effective_translation != Vec3::ZERO
+    }
+
+    /// Crouch while crouch is pressed, otherwise locomotion
+    pub fn crouched(&self) -> CharacterState {
+        match self.actions.pressed(CharacterActions::Crouch) {
+            true => Crouch,
+            false => self.locomotion(),
         }
-        Walk => {
-            if actions.pressed(CharacterActions::Sprint) { new_state = Some(Run) }
-            if actions.just_pressed(CharacterActions::Crouch) { new_state = Some(Crouch) }
-            if actions.just_pressed(CharacterActions::Jump) { new_state = Some(Jump) }
-            if physics.effective_translation == Vec3::ZERO { new_state = Some(Idle) }
-            if !grounded { new_state = Some(Fall) }
+    }
+
+    /// Idle, walk or run depending on movement and sprint
+    pub fn locomotion(&self) -> CharacterState {
+        match (
+            self.moving(),
+            self.actions.pressed(CharacterActions::Sprint),
+        ) {
+            (false, _) => Idle,
+            (true, false) => Walk,
+            (true, true) => Run,
         }
-        Slide => {
-            if actions.just_pressed(CharacterActions::Jump) { new_state = Some(Jump) }
-            if actions.just_released(CharacterActions::Crouch) { new_state = Some(Run) }
-            if !grounded { new_state = Some(Fall) }
+    }
+}
+
+/// Passed to enter and exit hooks
+pub struct StateHookContext<'a> {
+    pub entity: Entity,
+    pub from: CharacterState,
+    pub to: CharacterState,
+    pub character: &'a mut CharacterMovementController,
+}
+
+/// Which states a transition or hook applies to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StateScope {
+    State(CharacterState),
+    Group(CharacterStateGroup),
+    Any,
+}
+
+impl StateScope {
+    pub fn contains(&self, state: CharacterState) -> bool {
+        match self {
+            StateScope::State(scope) => *scope == state,
+            StateScope::Group(group) => state.group() == *group,
+            StateScope::Any => true,
+        }
+    }
+
+    fn depth(&self) -> u8 {
+        match self {
+            StateScope::State(_) => 2,
+            StateScope::Group(_) => 1,
+            StateScope::Any => 0,
+        }
+    }
+}
+
+impl From<CharacterState> for StateScope {
+    fn from(state: CharacterState) -> Self {
+        StateScope::State(state)
+    }
+}
+
+impl From<CharacterStateGroup> for StateScope {
+    fn from(group: CharacterStateGroup) -> Self {
+        StateScope::Group(group)
+    }
+}
+
+type Guard = Box<dyn Fn(&TransitionContext) -> Option<CharacterState> + Send + Sync>;
+type Hook = Box<dyn Fn(&mut StateHookContext) + Send + Sync>;
+
+struct Transition {
+    scope: StateScope,
+    guard: Guard,
+}
+
+/// Hierarchical character state machine
+///
+/// Transitions of the current state are checked first, along with those of the character's
+/// abilities, then those of its group and finally global ones; the last guard that fires wins,
+/// so broader rules like falling off a ledge take precedence over the state specific ones.
+#[derive(Resource)]
+pub struct CharacterStateMachine {
+    transitions: Vec<Transition>,
+    on_enter: Vec<(StateScope, Hook)>,
+    on_exit: Vec<(StateScope, Hook)>,
+}
+
+impl CharacterStateMachine {
+    pub fn empty() -> Self {
+        CharacterStateMachine {
+            transitions: Vec::new(),
+            on_enter: Vec::new(),
+            on_exit: Vec::new(),
         }
-        Jump => {
-            if physics.effective_translation.y < 0.0 { new_state = Some(Fall) }
+    }
+
+    pub fn add_transition(
+        &mut self,
+        scope: impl Into<StateScope>,
+        guard: impl Fn(&TransitionContext) -> Option<CharacterState> + Send + Sync + 'static,
+    ) -> &mut Self {
+        self.transitions.push(Transition {
+            scope: scope.into(),
+            guard: Box::new(guard),
+        });
+        self
+    }
+
+    /// Runs when a state inside `scope` is entered from outside of it
+    pub fn on_enter(
+        &mut self,
+        scope: impl Into<StateScope>,
+        hook: impl Fn(&mut StateHookContext) + Send + Sync + 'static,
+    ) -> &mut Self {
+        self.on_enter.push((scope.into(), Box::new(hook)));
+        self
+    }
+
+    /// Runs when a state inside `scope` is left for one outside of it
+    pub fn on_exit(
+        &mut self,
+        scope: impl Into<StateScope>,
+        hook: impl Fn(&mut StateHookContext) + Send + Sync + 'static,
+    ) -> &mut Self {
+        self.on_exit.push((scope.into(), Box::new(hook)));
+        self
+    }
+
+    pub fn next(
+        &self,
+        ctx: &TransitionContext,
+        abilities: Option<&mut CharacterAbilities>,
+    ) -> Option<CharacterState> {
+        let mut next = self.next_at(ctx, 2);
+        for ability in abilities
+            .into_iter()
+            .flat_map(|abilities| abilities.iter_mut())
+        {
+            next = ability.transition(ctx).or(next);
         }
-        Idle => {
-            if actions.just_pressed(CharacterActions::Jump) { new_state = Some(Jump) }
-            if actions.just_pressed(CharacterActions::Crouch) { new_state = Some(Crouch) }
-            if physics.effective_translation != Vec3::ZERO { new_state = Some(Walk) }
-            if !grounded { new_state = Some(Fall) }
+        for depth in [1, 0] {
+            next = self.next_at(ctx, depth).or(next);
         }
-        Crouch => {
-            if actions.just_released(CharacterActions::Crouch) { 
-                new_state = Some(Idle);
-                if physics.effective_translation != Vec3::ZERO { 
-                    new_state = Some(Walk);
-                    if actions.pressed(CharacterActions::Sprint) { 
-                        new_state = Some(Run) 
-                    } 
-                } 
+        next
+    }
+
+    /// Last firing transition of the scopes at `depth`
+    fn next_at(&self, ctx: &TransitionContext, depth: u8) -> Option<CharacterState> {
+        self.transitions
+            .iter()
+            .filter(|t| t.scope.depth() == depth && t.scope.contains(ctx.state))
+            .rev()
+            .find_map(|t| (t.guard)(ctx))
+    }
+
+    pub fn run_hooks(&self, ctx: &mut StateHookContext) {
+        let (from, to) = (ctx.from, ctx.to);
+        for (scope, hook) in self.on_exit.iter() {
+            if scope.contains(from) && !scope.contains(to) {
+                hook(ctx);
             }
-            if !grounded { new_state = Some(Fall) }
         }
-        Fall => {
-            if grounded { 
-                new_state = Some(Idle);
-                if physics.effective_translation != Vec3::ZERO { 
-                    new_state = Some(Walk);
-                    if actions.pressed(CharacterActions::Sprint) { 
-                        new_state = Some(Run) 
-                    } 
-                } 
+        for (scope, hook) in self.on_enter.iter() {
+            if scope.contains(to) && !scope.contains(from) {
+                hook(ctx);
             }
         }
     }
+}
+
+impl Default for CharacterStateMachine {
+    fn default() -> Self {
+        // Locomotion and falling, jumping, sprinting, crouching and sliding come from abilities
+        let mut machine = CharacterStateMachine::empty();
+        machine
+            .add_transition(Run, |c| (!c.moving()).then_some(Idle))
+            .add_transition(Walk, |c| (!c.moving()).then_some(Idle))
+            .add_transition(Idle, |c| c.moving().then_some(Walk))
+            .add_transition(Fall, |c| c.grounded.then(|| c.locomotion()))
+            .add_transition(CharacterStateGroup::Grounded, |c| {
+                (!c.grounded).then_some(Fall)
+            });
+        machine
+    }
+}
 
-    character.set_grounded(physics.grounded);
+#[allow(clippy::type_complexity)]
+pub fn update_player_state(
+    mut q: Query<(
+        Entity,
+        &mut CharacterState,
+        &mut CharacterMovementController,
+        &KinematicCharacterControllerOutput,
+        &Grounded,
+        &ActionState<CharacterActions>,
+        Option<&mut ActionBuffer<CharacterActions>>,
+        Option<&ActionGestures<CharacterActions>>,
+        Option<&mut CharacterAbilities>,
+    )>,
+    mut events: EventWriter<CharacterStateChanged>,
+    machine: Res<CharacterStateMachine>,
+    time: Res<SimulationTime>,
+) {
+    for components in q.iter_mut() {
+        let (
+            entity,
+            mut state,
+            mut character,
+            physics,
+            grounded,
+            actions,
+            mut buffer,
+            gestures,
+            mut abilities,
+        ) = components;
+        let ctx = TransitionContext {
+            entity,
+            state: *state,
+            actions,
+            buffer: buffer.as_deref(),
+            gestures,
+            settings: character.settings(),
+            physics,
+            grounded: grounded.get(),
+            time_in_state: time.elapsed_seconds() - character.state_entered_at(),
+        };
+        let new_state = machine.next(&ctx, abilities.as_deref_mut());
+
+        let Some(new_state) = new_state else { continue };
+        let from = *state;
+        if from == new_state {
+            continue;
+        }
+
+        *state = new_state;
+        if let (Some(buffer), Some(action)) = (buffer.as_mut(), buffered_action(new_state)) {
+            buffer.consume(&action);
+        }
+
+        let timestamp = time.elapsed_seconds();
+        events.send(CharacterStateChanged {
+            entity,
+            from,
+            to: new_state,
+            timestamp,
+            duration: timestamp - character.state_entered_at(),
+        });
+        character.set_state_entered_at(timestamp);
+
+        machine.run_hooks(&mut StateHookContext {
+            entity,
+            from,
+            to: new_state,
+            character: &mut *character,
+        });
+    }
+}
 
-    if let Some(new_state) = new_state {
-        state.set(new_state).unwrap_or_default();
+/// Action whose buffered press a transition into `state` uses up
+fn buffered_action(state: CharacterState) -> Option<CharacterActions> {
+    match state {
+        Jump => Some(CharacterActions::Jump),
+        Crouch | Slide => Some(CharacterActions::Crouch),
+        _ => None,
     }
 }
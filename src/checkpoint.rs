@@ -0,0 +1,139 @@
+use crate::prelude::*;
+use bevy::prelude::*;
+
+/// Makes `Checkpoint` trigger volumes the respawn target of characters crossing them
+///
+/// Characters come back where and how they were when they crossed it. Needs the `TriggerPlugin`
+/// and the `RespawnPlugin`.
+pub struct CheckpointPlugin;
+
+impl Plugin for CheckpointPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<CheckpointReached>()
+            .add_system(setup_checkpoints)
+            .add_system(reach_checkpoints.after(update_trigger_volumes))
+            .add_system(highlight_checkpoints.after(reach_checkpoints));
+    }
+}
+
+/// Trigger volume characters respawn at once crossed
+#[derive(Component, Debug, Default, Clone, Copy, PartialEq)]
+pub struct Checkpoint {
+    /// Position along the level, characters never go back to a lower one
+    pub order: u32,
+    /// Material color swapped in once a character reached it
+    pub active_color: Option<Color>,
+    activated: bool,
+}
+
+impl Checkpoint {
+    pub fn new(order: u32) -> Self {
+        Checkpoint {
+            order,
+            ..Default::default()
+        }
+    }
+
+    pub fn with_active_color(mut self, color: Color) -> Self {
+        self.active_color = Some(color);
+        self
+    }
+
+    /// Whether any character reached it
+    pub fn activated(&self) -> bool {
+        self.activated
+    }
+}
+
+/// Last checkpoint of a character and how it crossed it
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct CheckpointProgress {
+    pub checkpoint: Entity,
+    pub order: u32,
+    pub transform: Transform,
+    pub state: CharacterState,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CheckpointReached {
+    pub character: Entity,
+    pub checkpoint: Entity,
+    /// Whether no character reached it before
+    pub first: bool,
+}
+
+fn setup_checkpoints(
+    mut commands: Commands,
+    q: Query<(Entity, Option<&TriggerVolume>), Added<Checkpoint>>,
+) {
+    for (entity, trigger) in q.iter() {
+        // Respawning looks for its target among respawn points
+        commands.entity(entity).insert(RespawnPoint);
+        if trigger.is_none() {
+            commands.entity(entity).insert(TriggerVolume::default());
+        }
+    }
+}
+
+pub fn reach_checkpoints(
+    mut commands: Commands,
+    mut entered: EventReader<TriggerEntered>,
+    mut checkpoints: Query<&mut Checkpoint>,
+    characters: Query<(&Transform, &CharacterState, Option<&CheckpointProgress>)>,
+    mut reached: EventWriter<CheckpointReached>,
+) {
+    for TriggerEntered { trigger, entity } in entered.iter() {
+        let Ok(mut checkpoint) = checkpoints.get_mut(*trigger) else {
+            continue;
+        };
+        let Ok((transform, state, progress)) = characters.get(*entity) else {
+            continue;
+        };
+
+        let behind = progress.map_or(false, |progress| {
+            progress.checkpoint == *trigger || progress.order > checkpoint.order
+        });
+        if behind {
+            continue;
+        }
+
+        commands
+            .entity(*entity)
+            .insert(RespawnTarget(*trigger))
+            .insert(CheckpointProgress {
+                checkpoint: *trigger,
+                order: checkpoint.order,
+                transform: *transform,
+                state: *state,
+            });
+
+        let first = !checkpoint.activated;
+        checkpoint.activated = true;
+        info!("{entity:?} reached checkpoint {trigger:?}");
+        reached.send(CheckpointReached {
+            character: *entity,
+            checkpoint: *trigger,
+            first,
+        });
+    }
+}
+
+fn highlight_checkpoints(
+    mut commands: Commands,
+    mut reached: EventReader<CheckpointReached>,
+    checkpoints: Query<&Checkpoint, With<Handle<StandardMaterial>>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    for event in reached.iter().filter(|event| event.first) {
+        let Ok(checkpoint) = checkpoints.get(event.checkpoint) else {
+            continue;
+        };
+        let Some(color) = checkpoint.active_color else {
+            continue;
+        };
+
+        // A material of its own, others may share the old one
+        let material = materials.add(StandardMaterial::from(color));
+        commands.entity(event.checkpoint).insert(material);
+    }
+}
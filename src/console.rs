@@ -1 +1,153 @@
-// TODO
\ No newline at end of file
+use crate::prelude::*;
+use bevy::prelude::*;
+
+/// Line of text toggled with a key, sending what is typed as `ConsoleCommand`s
+///
+/// While open the console holds the `InputContext::Console`, so needs the `InputContextPlugin`.
+/// Other plugins read the commands and pick out the names they know.
+pub struct ConsolePlugin {
+    toggle: KeyCode,
+}
+
+impl Default for ConsolePlugin {
+    fn default() -> Self {
+        ConsolePlugin {
+            toggle: KeyCode::Grave,
+        }
+    }
+}
+
+impl ConsolePlugin {
+    pub fn with_toggle(mut self, key: KeyCode) -> Self {
+        self.toggle = key;
+        self
+    }
+}
+
+impl Plugin for ConsolePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Console {
+            toggle: self.toggle,
+            ..Default::default()
+        })
+        .add_event::<ConsoleCommand>()
+        .add_startup_system(spawn_console)
+        .add_system(toggle_console)
+        .add_system(read_console_input.after(toggle_console))
+        .add_system(update_console_text.after(read_console_input));
+    }
+}
+
+#[derive(Resource, Debug, Default, Clone, PartialEq, Eq)]
+pub struct Console {
+    pub open: bool,
+    /// Text typed since the last command
+    pub input: String,
+    toggle: KeyCode,
+}
+
+/// Line entered in the console, split on whitespace
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConsoleCommand {
+    pub name: String,
+    pub args: Vec<String>,
+}
+
+impl ConsoleCommand {
+    pub fn parse(line: &str) -> Option<Self> {
+        let mut words = line.split_whitespace().map(str::to_owned);
+        Some(ConsoleCommand {
+            name: words.next()?,
+            args: words.collect(),
+        })
+    }
+}
+
+/// Text node showing the line being typed
+#[derive(Component, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ConsoleText;
+
+fn spawn_console(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let style = TextStyle {
+        font: asset_server.load(HUD_FONT),
+        font_size: 18.0,
+        color: Color::WHITE,
+    };
+
+    commands
+        .spawn(TextBundle::from_section("", style).with_style(Style {
+            position_type: PositionType::Absolute,
+            position: UiRect {
+                top: Val::Px(16.0),
+                left: Val::Px(16.0),
+                ..Default::default()
+            },
+            ..Default::default()
+        }))
+        .insert(ConsoleText);
+}
+
+fn toggle_console(
+    keys: Res<Input<KeyCode>>,
+    mut console: ResMut<Console>,
+    mut context: ResMut<State<InputContext>>,
+) {
+    if !keys.just_pressed(console.toggle) {
+        return;
+    }
+
+    let result = match console.open {
+        true => context.pop(),
+        false => context.push(InputContext::Console),
+    };
+    if let Err(error) = result {
+        warn!("Failed to toggle the console: {error:?}");
+        return;
+    }
+
+    console.open = !console.open;
+    console.input.clear();
+}
+
+fn read_console_input(
+    mut characters: EventReader<ReceivedCharacter>,
+    mut console: ResMut<Console>,
+    mut commands: EventWriter<ConsoleCommand>,
+) {
+    if !console.open {
+        characters.clear();
+        return;
+    }
+
+    for event in characters.iter() {
+        match event.char {
+            '\r' | '\n' => {
+                let line = std::mem::take(&mut console.input);
+                info!("> {line}");
+                if let Some(command) = ConsoleCommand::parse(&line) {
+                    commands.send(command);
+                }
+            }
+            '\u{8}' => {
+                console.input.pop();
+            }
+            // The toggle key types a character too
+            '`' => {}
+            c if !c.is_control() => console.input.push(c),
+            _ => {}
+        }
+    }
+}
+
+fn update_console_text(console: Res<Console>, mut q: Query<&mut Text, With<ConsoleText>>) {
+    if !console.is_changed() {
+        return;
+    }
+
+    for mut text in q.iter_mut() {
+        text.sections[0].value = match console.open {
+            true => format!("> {}", console.input),
+            false => String::new(),
+        };
+    }
+}
@@ -0,0 +1,67 @@
+use crate::prelude::*;
+use bevy::{prelude::*, window::CursorGrabMode};
+use leafwing_input_manager::prelude::*;
+
+/// Cursor ownership of the primary window
+///
+/// Looking around grabs and hides the cursor, `CameraAction::CursorToggle` hands it back
+/// until toggled again or the camera state changes. Menus can set `released` directly.
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct CursorGrab {
+    pub released: bool,
+    locked: bool,
+}
+
+impl CursorGrab {
+    /// Whether the cursor is currently grabbed by the camera
+    pub fn locked(&self) -> bool {
+        self.locked
+    }
+}
+
+impl CameraState {
+    /// States where the mouse steers the camera without holding a button
+    pub fn grabs_cursor(&self) -> bool {
+        matches!(
+            self,
+            CameraState::FreeFloat
+                | CameraState::Fps
+                | CameraState::ThirdPerson
+                | CameraState::Spectator
+        )
+    }
+}
+
+pub fn update_cursor_grab(
+    q: Query<&ActionState<CameraAction>, With<ControlledCamera>>,
+    state: Res<State<CameraState>>,
+    mut grab: ResMut<CursorGrab>,
+    mut windows: ResMut<Windows>,
+) {
+    if state.is_changed() {
+        grab.released = false;
+    }
+
+    if q.iter()
+        .any(|actions| actions.just_pressed(CameraAction::CursorToggle))
+    {
+        grab.released = !grab.released;
+    }
+
+    let Some(window) = windows.get_primary_mut() else {
+        return;
+    };
+
+    // Alt-tabbing away must not leave the cursor trapped
+    let locked = state.current().grabs_cursor() && !grab.released && window.is_focused();
+    if locked == grab.locked {
+        return;
+    }
+
+    window.set_cursor_grab_mode(match locked {
+        true => CursorGrabMode::Locked,
+        false => CursorGrabMode::None,
+    });
+    window.set_cursor_visibility(!locked);
+    grab.locked = locked;
+}
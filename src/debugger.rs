@@ -1 +1 @@
-// TODO
\ No newline at end of file
+// TODO
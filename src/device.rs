@@ -0,0 +1,133 @@
+use crate::prelude::*;
+use bevy::prelude::*;
+use leafwing_input_manager::{axislike::AxisType, prelude::*, user_input::InputKind};
+
+/// Restricts characters with an `AssignedDevice` to the bindings of that device
+pub struct InputDevicePlugin;
+
+impl Plugin for InputDevicePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(assign_next_gamepad)
+            .add_system(apply_assigned_devices.after(assign_next_gamepad));
+    }
+}
+
+/// Device a character listens to, e.g. the keyboard for player one and a gamepad for player two
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum InputDevice {
+    /// Every binding, from any device
+    #[default]
+    Any,
+    KeyboardMouse,
+    Gamepad(Gamepad),
+    /// The first connected gamepad no other character has, once there is one
+    NextGamepad,
+}
+
+impl InputDevice {
+    /// Whether every part of `input` comes from this device
+    pub fn provides(&self, input: &UserInput) -> bool {
+        match input {
+            UserInput::Single(kind) => self.provides_kind(kind),
+            UserInput::Chord(kinds) => kinds.iter().all(|kind| self.provides_kind(kind)),
+            UserInput::VirtualDPad(dpad) => [dpad.up, dpad.down, dpad.left, dpad.right]
+                .iter()
+                .all(|kind| self.provides_kind(kind)),
+        }
+    }
+
+    fn provides_kind(&self, kind: &InputKind) -> bool {
+        let gamepad = match kind {
+            InputKind::GamepadButton(_) => true,
+            InputKind::SingleAxis(axis) => matches!(axis.axis_type, AxisType::Gamepad(_)),
+            InputKind::DualAxis(axis) => matches!(axis.x.axis_type, AxisType::Gamepad(_)),
+            _ => false,
+        };
+
+        match self {
+            InputDevice::Any => true,
+            InputDevice::KeyboardMouse => !gamepad,
+            InputDevice::Gamepad(_) | InputDevice::NextGamepad => gamepad,
+        }
+    }
+
+    /// The bindings of `map` this device provides, limited to its gamepad
+    pub fn filter<A: Actionlike>(&self, map: &InputMap<A>) -> InputMap<A> {
+        if *self == InputDevice::Any {
+            return map.clone();
+        }
+
+        let mut filtered = InputMap::default();
+        for action in A::variants() {
+            for input in map.get(action.clone()).iter() {
+                if self.provides(input) {
+                    filtered.insert(input.clone(), action.clone());
+                }
+            }
+        }
+        if let InputDevice::Gamepad(gamepad) = self {
+            filtered.set_gamepad(*gamepad);
+        }
+        filtered
+    }
+}
+
+/// Ties a character to one input device, so several can share a machine
+#[derive(Component, Debug, Clone, PartialEq)]
+pub struct AssignedDevice {
+    pub device: InputDevice,
+    /// Maps before filtering, so the device can be changed again
+    keymap: Option<CharacterKeymap>,
+}
+
+impl AssignedDevice {
+    pub fn new(device: InputDevice) -> Self {
+        AssignedDevice {
+            device,
+            keymap: None,
+        }
+    }
+}
+
+fn assign_next_gamepad(mut q: Query<&mut AssignedDevice>, gamepads: Res<Gamepads>) {
+    let taken: Vec<Gamepad> = q
+        .iter()
+        .filter_map(|assigned| match assigned.device {
+            InputDevice::Gamepad(gamepad) => Some(gamepad),
+            _ => None,
+        })
+        .collect();
+    let mut free = gamepads.iter().filter(|gamepad| !taken.contains(gamepad));
+
+    for mut assigned in q.iter_mut() {
+        if assigned.device != InputDevice::NextGamepad {
+            continue;
+        }
+        let Some(gamepad) = free.next() else { return };
+        info!("Assigned {gamepad:?} to a character");
+        assigned.device = InputDevice::Gamepad(gamepad);
+    }
+}
+
+fn apply_assigned_devices(
+    mut q: Query<
+        (
+            &mut AssignedDevice,
+            &mut InputMap<CharacterMovement>,
+            &mut InputMap<CharacterActions>,
+        ),
+        Changed<AssignedDevice>,
+    >,
+) {
+    for (mut assigned, mut movement, mut actions) in q.iter_mut() {
+        // Remembering the full maps isn't a change of device
+        let assigned = assigned.bypass_change_detection();
+        let keymap = assigned.keymap.get_or_insert_with(|| CharacterKeymap {
+            movement: movement.clone(),
+            actions: actions.clone(),
+        });
+
+        *movement = assigned.device.filter(&keymap.movement);
+        *actions = assigned.device.filter(&keymap.actions);
+    }
+}
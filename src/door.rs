@@ -0,0 +1,214 @@
+use crate::prelude::*;
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+/// Opens and closes `Door`s through interaction, switches and nearby characters
+pub struct DoorPlugin;
+
+impl Plugin for DoorPlugin {
+    fn build(&self, app: &mut App) {
+        init_simulation_time(app);
+        app.add_system(setup_doors)
+            .add_system(use_doors.after(interact))
+            .add_system(
+                switch_doors
+                    .after(press_buttons)
+                    .after(update_pressure_plates),
+            )
+            .add_system(detect_door_proximity)
+            .add_system(
+                animate_doors
+                    .with_run_criteria(simulation_running)
+                    .after(use_doors)
+                    .after(switch_doors)
+                    .after(detect_door_proximity),
+            );
+    }
+}
+
+/// How a door moves between its closed and open pose, both in its local space
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DoorKind {
+    /// Swings around the vertical axis through `pivot`, by `angle` degrees
+    Hinged { pivot: Vec3, angle: f32 },
+    /// Slides by `offset`
+    Sliding { offset: Vec3 },
+}
+
+/// Kinematic door, closed where it was spawned
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct Door {
+    pub kind: DoorKind,
+    /// Seconds to fully open or close
+    pub duration: f32,
+    /// Opened by interaction or a linked switch
+    pub open: bool,
+    /// Whether the `Interact` action opens and closes it
+    pub interactive: bool,
+    /// Opens while a character is within this many meters
+    pub proximity: Option<f32>,
+    near: bool,
+    /// From closed at `0` to open at `1`
+    progress: f32,
+    closed: Transform,
+}
+
+impl Door {
+    pub fn new(kind: DoorKind) -> Self {
+        Door {
+            kind,
+            duration: 0.8,
+            open: false,
+            interactive: true,
+            proximity: None,
+            near: false,
+            progress: 0.0,
+            closed: Transform::IDENTITY,
+        }
+    }
+
+    pub fn hinged(pivot: Vec3, angle: f32) -> Self {
+        Door::new(DoorKind::Hinged { pivot, angle })
+    }
+
+    pub fn sliding(offset: Vec3) -> Self {
+        Door::new(DoorKind::Sliding { offset })
+    }
+
+    pub fn with_proximity(mut self, distance: f32) -> Self {
+        self.proximity = Some(distance);
+        self
+    }
+
+    /// Opens only through switches or proximity
+    pub fn without_interaction(mut self) -> Self {
+        self.interactive = false;
+        self
+    }
+
+    /// Whether the door is on its way open or fully open
+    pub fn opening(&self) -> bool {
+        self.open || self.near
+    }
+
+    /// Pose at `progress` between closed and open
+    fn pose(&self, progress: f32) -> Transform {
+        // Ease in and out
+        let t = progress * progress * (3.0 - 2.0 * progress);
+        let closed = self.closed;
+
+        match self.kind {
+            DoorKind::Hinged { pivot, angle } => {
+                let hinge = closed.transform_point(pivot);
+                let rotation = Quat::from_rotation_y(angle.to_radians() * t) * closed.rotation;
+                let translation = hinge - rotation * (pivot * closed.scale);
+                closed.with_translation(translation).with_rotation(rotation)
+            }
+            DoorKind::Sliding { offset } => {
+                let offset = closed.rotation * (offset * closed.scale * t);
+                closed.with_translation(closed.translation + offset)
+            }
+        }
+    }
+}
+
+fn setup_doors(
+    mut commands: Commands,
+    mut q: Query<(Entity, &mut Door, &Transform, Option<&RigidBody>), Added<Door>>,
+) {
+    for (entity, mut door, transform, body) in q.iter_mut() {
+        door.closed = *transform;
+
+        // Moved by its transform, the character controller collides with it like a wall
+        if body.is_none() {
+            commands
+                .entity(entity)
+                .insert(RigidBody::KinematicPositionBased);
+        }
+        if door.interactive {
+            commands.entity(entity).insert(Interactable::default());
+        }
+    }
+}
+
+fn use_doors(mut interactions: EventReader<Interacted>, mut doors: Query<&mut Door>) {
+    for Interacted { target, .. } in interactions.iter() {
+        if let Ok(mut door) = doors.get_mut(*target) {
+            if door.interactive {
+                door.open = !door.open;
+            }
+        }
+    }
+}
+
+fn switch_doors(mut activations: EventReader<Activated>, mut doors: Query<&mut Door>) {
+    for Activated { target, active, .. } in activations.iter() {
+        if let Ok(mut door) = doors.get_mut(*target) {
+            door.open = *active;
+        }
+    }
+}
+
+fn detect_door_proximity(
+    mut doors: Query<&mut Door>,
+    characters: Query<&Transform, With<CharacterMovementController>>,
+) {
+    for mut door in doors.iter_mut() {
+        let Some(distance) = door.proximity else {
+            continue;
+        };
+        let center = door.closed.translation;
+        let near = characters
+            .iter()
+            .any(|character| character.translation.distance(center) <= distance);
+
+        if door.near != near {
+            door.near = near;
+        }
+    }
+}
+
+pub fn animate_doors(
+    mut doors: Query<(Entity, &mut Door, &mut Transform, Option<&Collider>)>,
+    characters: Query<(), With<CharacterMovementController>>,
+    rapier: Res<RapierContext>,
+    time: Res<SimulationTime>,
+) {
+    for (entity, mut door, mut transform, collider) in doors.iter_mut() {
+        let target = if door.opening() { 1.0 } else { 0.0 };
+        if door.progress == target {
+            continue;
+        }
+
+        let step = match door.duration > 0.0 {
+            true => time.delta_seconds() / door.duration,
+            false => 1.0,
+        };
+        let progress = door.progress + (target - door.progress).clamp(-step, step);
+        let pose = door.pose(progress);
+
+        // Kinematic bodies don't push characters, so wait for them to get out of the way
+        if let Some(collider) = collider {
+            let mut blocked = false;
+            let filter = QueryFilter::default()
+                .exclude_collider(entity)
+                .exclude_sensors();
+            rapier.intersections_with_shape(
+                pose.translation,
+                pose.rotation,
+                collider,
+                filter,
+                |hit| {
+                    blocked = characters.contains(hit);
+                    !blocked
+                },
+            );
+            if blocked {
+                continue;
+            }
+        }
+
+        door.progress = progress;
+        *transform = pose;
+    }
+}
@@ -0,0 +1,218 @@
+use crate::prelude::*;
+use bevy::{
+    ecs::system::SystemParam,
+    prelude::*,
+    render::{camera::Projection, primitives::Aabb, view::NoFrustumCulling},
+};
+use bevy_rapier3d::prelude::*;
+use leafwing_input_manager::prelude::*;
+
+/// Entity picked in the editor, target of focusing
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct EditorSelection(pub Option<Entity>);
+
+/// How far picking rays reach
+const PICK_DISTANCE: f32 = 1000.0;
+
+/// World space ray through the cursor, as origin and direction
+pub fn cursor_ray(
+    camera: &Camera,
+    transform: &GlobalTransform,
+    window: &Window,
+) -> Option<(Vec3, Vec3)> {
+    let cursor = window.cursor_position()?;
+    let size = Vec2::new(window.width(), window.height());
+    let ndc = cursor / size * 2.0 - Vec2::ONE;
+
+    let ndc_to_world = transform.compute_matrix() * camera.projection_matrix().inverse();
+    let near = ndc_to_world.project_point3(ndc.extend(1.0));
+    let far = ndc_to_world.project_point3(ndc.extend(f32::EPSILON));
+
+    Some((near, (far - near).try_normalize()?))
+}
+
+type PickableMesh = (
+    Entity,
+    &'static GlobalTransform,
+    &'static Aabb,
+    &'static ComputedVisibility,
+);
+type PickableMeshFilter = (
+    Without<Collider>,
+    Without<GizmoHandle>,
+    Without<NoFrustumCulling>,
+);
+
+/// Picks entities along rays by their colliders, and meshes without colliders by their bounds
+///
+/// Sensors, meshes around the ray origin and meshes drawn wherever the view is, like skies, are
+/// skipped.
+#[derive(SystemParam)]
+pub struct Picking<'w, 's> {
+    rapier: Res<'w, RapierContext>,
+    meshes: Query<'w, 's, PickableMesh, PickableMeshFilter>,
+}
+
+impl<'w, 's> Picking<'w, 's> {
+    /// Closest entity along the ray and its distance
+    pub fn cast(&self, origin: Vec3, direction: Vec3) -> Option<(Entity, f32)> {
+        let filter = QueryFilter::default().exclude_sensors();
+        let collider = self
+            .rapier
+            .cast_ray(origin, direction, PICK_DISTANCE, true, filter);
+        let mesh = self
+            .meshes
+            .iter()
+            .filter(|(.., visibility)| visibility.is_visible())
+            .filter_map(|(entity, transform, aabb, _)| {
+                let distance = ray_aabb(origin, direction, transform, aabb)?;
+                Some((entity, distance))
+            })
+            .filter(|(_, distance)| *distance <= PICK_DISTANCE)
+            .min_by(|(_, a), (_, b)| a.total_cmp(b));
+
+        match (collider, mesh) {
+            (Some(collider), Some(mesh)) if mesh.1 < collider.1 => Some(mesh),
+            (Some(collider), _) => Some(collider),
+            (None, mesh) => mesh,
+        }
+    }
+
+    /// Entity under the cursor
+    pub fn under_cursor(
+        &self,
+        camera: &Camera,
+        transform: &GlobalTransform,
+        window: &Window,
+    ) -> Option<Entity> {
+        let (origin, direction) = cursor_ray(camera, transform, window)?;
+        self.cast(origin, direction).map(|(entity, _)| entity)
+    }
+}
+
+/// Distance along the ray to where it enters the transformed box, none from inside of it
+pub fn ray_aabb(
+    origin: Vec3,
+    direction: Vec3,
+    transform: &GlobalTransform,
+    aabb: &Aabb,
+) -> Option<f32> {
+    // Distances along the ray stay the same in local space, the direction just isn't unit length
+    let to_local = transform.affine().inverse();
+    let origin = to_local.transform_point3(origin);
+    let direction = to_local.transform_vector3(direction);
+    let min = Vec3::from(aabb.center - aabb.half_extents);
+    let max = Vec3::from(aabb.center + aabb.half_extents);
+
+    let near = (min - origin) / direction;
+    let far = (max - origin) / direction;
+    let enter = near.min(far).max_element();
+    let exit = near.max(far).min_element();
+
+    (enter >= 0.0 && enter <= exit).then_some(enter)
+}
+
+/// Where the ray crosses the plane through `point`, if ahead of it
+pub fn ray_plane(origin: Vec3, direction: Vec3, point: Vec3, normal: Vec3) -> Option<Vec3> {
+    let facing = direction.dot(normal);
+    if facing.abs() < 1e-4 {
+        return None;
+    }
+    let distance = (point - origin).dot(normal) / facing;
+    (distance >= 0.0).then(|| origin + direction * distance)
+}
+
+/// World space bounding sphere of an entity, from its collider or mesh bounds
+pub fn bounding_sphere(
+    transform: &GlobalTransform,
+    collider: Option<&Collider>,
+    aabb: Option<&Aabb>,
+) -> (Vec3, f32) {
+    let (center, radius) = match (collider, aabb) {
+        (Some(collider), _) => {
+            let sphere = collider.raw.compute_local_bounding_sphere();
+            let center = sphere.center();
+            (Vec3::new(center.x, center.y, center.z), sphere.radius())
+        }
+        (None, Some(aabb)) => (aabb.center.into(), aabb.half_extents.length()),
+        (None, None) => (Vec3::ZERO, 1.0),
+    };
+
+    let scale = transform.compute_transform().scale.max_element();
+    (transform.transform_point(center), radius * scale)
+}
+
+pub fn select_under_cursor(
+    cameras: Query<(&Camera, &GlobalTransform, &ActionState<CameraAction>), With<ControlledCamera>>,
+    mut selection: ResMut<EditorSelection>,
+    grab: Option<Res<GizmoGrab>>,
+    windows: Res<Windows>,
+    picking: Picking,
+) {
+    let Some(window) = windows.get_primary() else {
+        return;
+    };
+    // Clicks on a gizmo handle drag the selection instead
+    if grab.map_or(false, |grab| grab.grabbing()) {
+        return;
+    }
+
+    for (camera, transform, actions) in cameras.iter() {
+        if actions.just_pressed(CameraAction::Select) {
+            selection.0 = picking.under_cursor(camera, transform, window);
+        }
+    }
+}
+
+/// Frames the selection, or whatever is under the cursor, and orbits around it afterwards
+pub fn focus_selection(
+    mut cameras: Query<
+        (
+            &Camera,
+            &GlobalTransform,
+            &mut Projection,
+            &mut CameraGoal,
+            &mut DebugCamera,
+            &ActionState<CameraAction>,
+        ),
+        With<ControlledCamera>,
+    >,
+    targets: Query<(&GlobalTransform, Option<&Collider>, Option<&Aabb>)>,
+    mut selection: ResMut<EditorSelection>,
+    windows: Res<Windows>,
+    picking: Picking,
+) {
+    let Some(window) = windows.get_primary() else {
+        return;
+    };
+
+    for (view, transform, mut projection, mut goal, mut camera, actions) in cameras.iter_mut() {
+        if !actions.just_pressed(CameraAction::Focus) {
+            continue;
+        }
+
+        let target = selection
+            .0
+            .filter(|target| targets.contains(*target))
+            .or_else(|| picking.under_cursor(view, transform, window));
+        let Some(target) = target else { continue };
+        let Ok((target_transform, collider, aabb)) = targets.get(target) else {
+            continue;
+        };
+        let (center, radius) = bounding_sphere(target_transform, collider, aabb);
+
+        // Distance at which the sphere touches the top and bottom of the view
+        let distance = match projection.as_mut() {
+            Projection::Perspective(perspective) => radius / (perspective.fov / 2.).sin(),
+            Projection::Orthographic(orthographic) => {
+                orthographic.scale = radius;
+                camera.radius
+            }
+        };
+
+        selection.0 = Some(target);
+        camera.focus = center;
+        camera.radius = distance.clamp(camera.min_radius, camera.max_radius);
+        goal.translation = center - goal.forward() * camera.radius;
+    }
+}
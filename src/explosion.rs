@@ -0,0 +1,210 @@
+use crate::prelude::*;
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+/// Applies `Explode` events to everything in range and sends `Exploded` for effects
+///
+/// Dynamic bodies get an impulse, characters are knocked back through their `Knockback` and
+/// breakables take an `Impact`.
+pub struct ExplosionPlugin;
+
+impl Plugin for ExplosionPlugin {
+    fn build(&self, app: &mut App) {
+        init_simulation_time(app);
+        app.add_event::<Explode>()
+            .add_event::<Exploded>()
+            .add_event::<Impact>()
+            .add_system(apply_explosions)
+            .add_system(
+                apply_knockback
+                    .with_run_criteria(simulation_running)
+                    .after(apply_explosions),
+            );
+
+        #[cfg(feature = "debug_camera")]
+        app.add_event::<CameraShake>()
+            .add_system(shake_on_explosions.after(apply_explosions));
+    }
+}
+
+/// Blast pushing things within `radius` of `center` away from it
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Explode {
+    pub center: Vec3,
+    /// Meters
+    pub radius: f32,
+    /// Impulse at the center, falling off to none at `radius`, in kilogram meters per second
+    pub force: f32,
+}
+
+/// Sent after an `Explode` was applied, for camera shake, sounds and particles
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Exploded {
+    pub center: Vec3,
+    pub radius: f32,
+    pub force: f32,
+    /// Bodies and characters that were pushed
+    pub affected: usize,
+}
+
+/// Velocity given to a character by impulses, fading out over time
+///
+/// Kinematic characters aren't moved by rapier impulses, so this is added to their
+/// `ExternalForces` instead.
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct Knockback {
+    /// World space, in meters per second
+    pub velocity: Vec3,
+    /// Rate at which the velocity fades, per second
+    pub decay: f32,
+    /// Velocity currently added to the `ExternalForces`
+    applied: Vec3,
+}
+
+impl Default for Knockback {
+    fn default() -> Self {
+        Knockback {
+            velocity: Vec3::ZERO,
+            decay: 4.0,
+            applied: Vec3::ZERO,
+        }
+    }
+}
+
+impl Knockback {
+    /// Adds `impulse` in kilogram meters per second to a character weighing `mass` kilograms
+    pub fn add_impulse(&mut self, impulse: Vec3, mass: f32) {
+        if mass > 0.0 {
+            self.velocity += impulse / mass;
+        }
+    }
+}
+
+/// Share of the force left at `distance` from the center
+fn falloff(explosion: &Explode, distance: f32) -> f32 {
+    match explosion.radius > 0.0 {
+        true => (1.0 - distance / explosion.radius).clamp(0.0, 1.0),
+        false => 0.0,
+    }
+}
+
+#[allow(clippy::type_complexity)]
+pub fn apply_explosions(
+    mut commands: Commands,
+    mut explosions: EventReader<Explode>,
+    mut bodies: Query<(
+        &RigidBody,
+        &GlobalTransform,
+        Option<&mut ExternalImpulse>,
+        Option<&mut Knockback>,
+        Option<&CharacterMovementController>,
+        Option<&Breakable>,
+    )>,
+    mut impacts: EventWriter<Impact>,
+    mut exploded: EventWriter<Exploded>,
+    rapier: Res<RapierContext>,
+) {
+    for explosion in explosions.iter() {
+        let mut targets = Vec::new();
+        let shape = Collider::ball(explosion.radius.max(0.0));
+        let filter = QueryFilter::default().exclude_sensors();
+        rapier.intersections_with_shape(explosion.center, Quat::IDENTITY, &shape, filter, |hit| {
+            let target = rapier.collider_parent(hit).unwrap_or(hit);
+            if !targets.contains(&target) {
+                targets.push(target);
+            }
+            true
+        });
+
+        let mut affected = 0;
+        for target in targets {
+            let Ok(components) = bodies.get_mut(target) else {
+                continue;
+            };
+            let (body, transform, impulse, knockback, character, breakable) = components;
+
+            let offset = transform.translation() - explosion.center;
+            let strength = explosion.force * falloff(explosion, offset.length());
+            if strength <= 0.0 {
+                continue;
+            }
+            // Straight up when right at the center
+            let push = offset.try_normalize().unwrap_or(Vec3::Y) * strength;
+
+            if breakable.is_some() {
+                impacts.send(Impact {
+                    target,
+                    impulse: strength,
+                });
+            }
+
+            // Ragdolled characters are dynamic bodies like any other
+            match (body, knockback, character) {
+                (RigidBody::Dynamic, ..) => match impulse {
+                    Some(mut impulse) => impulse.impulse += push,
+                    None => {
+                        commands.entity(target).insert(ExternalImpulse {
+                            impulse: push,
+                            ..Default::default()
+                        });
+                    }
+                },
+                (_, Some(mut knockback), Some(character)) => {
+                    knockback.add_impulse(push, character.settings().mass);
+                }
+                _ => continue,
+            }
+            affected += 1;
+        }
+
+        exploded.send(Exploded {
+            center: explosion.center,
+            radius: explosion.radius,
+            force: explosion.force,
+            affected,
+        });
+    }
+}
+
+/// Fades knockback and moves the change into the character's `ExternalForces`
+pub fn apply_knockback(
+    mut q: Query<(&mut Knockback, &mut ExternalForces)>,
+    time: Res<SimulationTime>,
+) {
+    for (mut knockback, mut external) in q.iter_mut() {
+        let fade = (-knockback.decay * time.delta_seconds()).exp();
+        knockback.velocity *= fade;
+        if knockback.velocity.length() < 0.01 {
+            knockback.velocity = Vec3::ZERO;
+        }
+
+        if knockback.velocity != knockback.applied {
+            external.0 += knockback.velocity - knockback.applied;
+            knockback.applied = knockback.velocity;
+        }
+    }
+}
+
+/// Shakes cameras near explosions, more the closer and stronger they are
+#[cfg(feature = "debug_camera")]
+fn shake_on_explosions(
+    mut explosions: EventReader<Exploded>,
+    cameras: Query<(Entity, &GlobalTransform), With<CameraShaker>>,
+    mut shakes: EventWriter<CameraShake>,
+) {
+    for explosion in explosions.iter() {
+        for (camera, transform) in cameras.iter() {
+            // Felt well beyond the blast itself
+            let reach = explosion.radius * 4.0;
+            let distance = transform.translation().distance(explosion.center);
+            if reach <= 0.0 || distance >= reach {
+                continue;
+            }
+
+            shakes.send(CameraShake {
+                trauma: (1.0 - distance / reach) * (explosion.force / 1000.0).min(1.0),
+                camera: Some(camera),
+            });
+        }
+    }
+}
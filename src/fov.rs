@@ -0,0 +1,210 @@
+use crate::prelude::*;
+use bevy::{prelude::*, render::camera::Projection};
+
+/// Widens the field of view while the followed character runs or slides
+///
+/// The kick is added on top of whatever the zoom left the projection at, and only the
+/// difference to the last frame is applied, so zooming during a kick keeps working.
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct FovKick {
+    /// Degrees added while running
+    pub run: f32,
+    /// Degrees added while sliding
+    pub slide: f32,
+    /// How fast the kick approaches its target, per second
+    pub speed: f32,
+    target: f32,
+    applied: f32,
+}
+
+impl Default for FovKick {
+    fn default() -> Self {
+        FovKick {
+            run: 8.0,
+            slide: 14.0,
+            speed: 8.0,
+            target: 0.0,
+            applied: 0.0,
+        }
+    }
+}
+
+impl FovKick {
+    /// Degrees currently added to the field of view
+    pub fn current(&self) -> f32 {
+        self.applied.to_degrees()
+    }
+
+    fn for_state(&self, state: CharacterState) -> f32 {
+        match state {
+            CharacterState::Run => self.run,
+            CharacterState::Slide => self.slide,
+            _ => 0.0,
+        }
+    }
+}
+
+pub fn update_fov_kick(
+    mut cameras: Query<(
+        &mut FovKick,
+        &mut Projection,
+        Option<&FpsCameraRig>,
+        Option<&ThirdPersonCameraRig>,
+    )>,
+    characters: Query<&CharacterState>,
+    time: Res<SimulationTime>,
+) {
+    for (mut kick, mut projection, fps, third_person) in cameras.iter_mut() {
+        let target = fps
+            .and_then(|rig| rig.attached())
+            .or_else(|| third_person.and_then(|rig| rig.attached()));
+
+        // States also change without events, e.g. on respawn, so they're read every frame
+        let state = target.and_then(|target| characters.get(target).ok());
+        kick.target = state.map_or(0.0, |state| kick.for_state(*state).to_radians());
+
+        let Projection::Perspective(projection) = projection.as_mut() else {
+            continue;
+        };
+
+        // Frame-rate independent exponential approach to the target kick
+        let t = 1.0 - (-kick.speed * time.delta_seconds()).exp();
+        let next = kick.applied + (kick.target - kick.applied) * t;
+        projection.fov += next - kick.applied;
+        kick.applied = next;
+    }
+}
+
+/// Vertigo effect: changes the field of view to `fov` degrees over `duration` seconds while
+/// moving the camera so `target` keeps its size on screen
+///
+/// Without a target the camera focus point is kept instead. Applies to every debug camera
+/// when `camera` is `None`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DollyZoom {
+    pub fov: f32,
+    pub duration: f32,
+    pub target: Option<Entity>,
+    pub camera: Option<Entity>,
+}
+
+impl DollyZoom {
+    pub fn new(fov: f32, duration: f32) -> Self {
+        DollyZoom {
+            fov,
+            duration,
+            target: None,
+            camera: None,
+        }
+    }
+
+    pub fn with_target(mut self, target: Entity) -> Self {
+        self.target = Some(target);
+        self
+    }
+}
+
+/// Dolly zoom in progress on a camera
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct DollyZoomEffect {
+    zoom: DollyZoom,
+    elapsed: f32,
+    /// Radians the effect started from and last set
+    from_fov: f32,
+    applied_fov: f32,
+    /// Width of the view at the focus depth, kept constant
+    width: Option<f32>,
+}
+
+pub fn start_dolly_zoom(
+    mut commands: Commands,
+    mut events: EventReader<DollyZoom>,
+    cameras: Query<(Entity, &Projection), With<DebugCamera>>,
+) {
+    for event in events.iter() {
+        for (entity, projection) in cameras.iter() {
+            if event.camera.map_or(false, |camera| camera != entity) {
+                continue;
+            }
+            let Projection::Perspective(perspective) = projection else {
+                warn!("Dolly zoom needs a perspective camera");
+                continue;
+            };
+
+            commands.entity(entity).insert(DollyZoomEffect {
+                zoom: *event,
+                elapsed: 0.0,
+                from_fov: perspective.fov,
+                applied_fov: perspective.fov,
+                width: None,
+            });
+        }
+    }
+}
+
+pub fn update_dolly_zoom(
+    mut commands: Commands,
+    mut cameras: Query<
+        (
+            Entity,
+            &mut DollyZoomEffect,
+            &mut DebugCamera,
+            &mut CameraGoal,
+            &mut Transform,
+            &mut Projection,
+        ),
+        Without<CharacterVisual>,
+    >,
+    targets: Query<&GlobalTransform>,
+    time: Res<Time>,
+) {
+    for (entity, mut effect, mut camera, mut goal, mut transform, mut projection) in
+        cameras.iter_mut()
+    {
+        let Projection::Perspective(perspective) = projection.as_mut() else {
+            commands.entity(entity).remove::<DollyZoomEffect>();
+            continue;
+        };
+
+        // Moving targets are followed, lost ones leave the focus where it was
+        if let Some(target) = effect
+            .zoom
+            .target
+            .and_then(|target| targets.get(target).ok())
+        {
+            camera.focus = target.translation();
+        }
+        let forward = goal.forward();
+        let depth = (camera.focus - goal.translation)
+            .dot(forward)
+            .max(camera.min_radius);
+        let width = *effect
+            .width
+            .get_or_insert_with(|| 2.0 * depth * (effect.from_fov / 2.).tan());
+
+        effect.elapsed += time.delta_seconds();
+        let t = match effect.zoom.duration > 0.0 {
+            true => (effect.elapsed / effect.zoom.duration).min(1.0),
+            false => 1.0,
+        };
+        let t = t * t * (3.0 - 2.0 * t);
+        let to_fov = effect.zoom.fov.clamp(1.0, 170.0).to_radians();
+        let fov = effect.from_fov + (to_fov - effect.from_fov) * t;
+
+        // Only the change is applied, leaving other field of view offsets alone
+        perspective.fov += fov - effect.applied_fov;
+        effect.applied_fov = fov;
+
+        // Move along the view axis, the focus doesn't have to be centered
+        let next = width / (2.0 * (fov / 2.).tan());
+        camera.radius = next.clamp(camera.min_radius, camera.max_radius);
+        goal.translation += forward * (depth - camera.radius);
+
+        // Damping would let the size drift while the field of view changes
+        transform.translation = goal.translation;
+
+        if t >= 1.0 {
+            commands.entity(entity).remove::<DollyZoomEffect>();
+        }
+    }
+}
@@ -0,0 +1,150 @@
+use crate::prelude::*;
+use bevy::{
+    prelude::*,
+    utils::{HashMap, HashSet},
+};
+use leafwing_input_manager::{plugin::InputManagerSystem, prelude::*};
+use std::marker::PhantomData;
+
+/// Detects gestures on `A` for every `ActionGestures<A>`
+pub struct GesturePlugin<A: Actionlike> {
+    _marker: PhantomData<A>,
+}
+
+impl<A: Actionlike> Default for GesturePlugin<A> {
+    fn default() -> Self {
+        GesturePlugin {
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<A: Actionlike> Plugin for GesturePlugin<A> {
+    fn build(&self, app: &mut App) {
+        init_simulation_time(app);
+        app.add_system_to_stage(
+            CoreStage::PreUpdate,
+            detect_gestures::<A>
+                .label(GestureSystem)
+                .after(InputManagerSystem::Update),
+        );
+    }
+}
+
+/// Systems writing action states must run before this to have their gestures detected
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, SystemLabel)]
+pub struct GestureSystem;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GestureSettings {
+    /// Longest gap between the presses of a double tap, in seconds
+    pub double_tap_window: f32,
+    /// Seconds a press lasts before it counts as a hold rather than a tap
+    pub hold_threshold: f32,
+}
+
+impl Default for GestureSettings {
+    fn default() -> Self {
+        GestureSettings {
+            double_tap_window: 0.25,
+            hold_threshold: 0.2,
+        }
+    }
+}
+
+/// Gestures performed on the actions of an `ActionState<A>`, read like the action state itself
+#[derive(Component, Debug, Clone, PartialEq)]
+pub struct ActionGestures<A: Actionlike> {
+    pub settings: GestureSettings,
+    /// `SimulationTime` of the last press that may start a double tap
+    last_tap: HashMap<A, f32>,
+    double_tapped: HashSet<A>,
+    /// `SimulationTime` of the press of every held action
+    pressed_at: HashMap<A, f32>,
+    tapped: HashSet<A>,
+    held: HashSet<A>,
+    just_held: HashSet<A>,
+}
+
+impl<A: Actionlike> Default for ActionGestures<A> {
+    fn default() -> Self {
+        ActionGestures::new(Default::default())
+    }
+}
+
+impl<A: Actionlike> ActionGestures<A> {
+    pub fn new(settings: GestureSettings) -> Self {
+        ActionGestures {
+            settings,
+            last_tap: HashMap::default(),
+            double_tapped: HashSet::default(),
+            pressed_at: HashMap::default(),
+            tapped: HashSet::default(),
+            held: HashSet::default(),
+            just_held: HashSet::default(),
+        }
+    }
+
+    /// Pressed a second time within the double tap window this frame
+    pub fn just_double_tapped(&self, action: A) -> bool {
+        self.double_tapped.contains(&action)
+    }
+
+    /// Released this frame before becoming a hold
+    pub fn just_tapped(&self, action: A) -> bool {
+        self.tapped.contains(&action)
+    }
+
+    /// Pressed for at least the hold threshold and not released yet
+    pub fn held(&self, action: A) -> bool {
+        self.held.contains(&action)
+    }
+
+    /// Became a hold this frame
+    pub fn just_held(&self, action: A) -> bool {
+        self.just_held.contains(&action)
+    }
+}
+
+pub fn detect_gestures<A: Actionlike>(
+    mut q: Query<(&mut ActionGestures<A>, &ActionState<A>)>,
+    time: Res<SimulationTime>,
+) {
+    let now = time.elapsed_seconds();
+
+    for (mut gestures, actions) in q.iter_mut() {
+        let gestures = gestures.as_mut();
+        gestures.double_tapped.clear();
+        gestures.tapped.clear();
+        gestures.just_held.clear();
+
+        for action in actions.get_just_released() {
+            let pressed_at = gestures.pressed_at.remove(&action);
+            if !gestures.held.remove(&action) && pressed_at.is_some() {
+                gestures.tapped.insert(action);
+            }
+        }
+
+        for (action, pressed_at) in gestures.pressed_at.iter() {
+            let long = now - pressed_at >= gestures.settings.hold_threshold;
+            if long && gestures.held.insert(action.clone()) {
+                gestures.just_held.insert(action.clone());
+            }
+        }
+
+        for action in actions.get_just_pressed() {
+            gestures.pressed_at.insert(action.clone(), now);
+
+            let last = gestures.last_tap.remove(&action);
+            match last {
+                // A third tap starts over rather than counting as another double tap
+                Some(last) if now - last <= gestures.settings.double_tap_window => {
+                    gestures.double_tapped.insert(action);
+                }
+                _ => {
+                    gestures.last_tap.insert(action, now);
+                }
+            }
+        }
+    }
+}
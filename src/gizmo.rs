@@ -0,0 +1,391 @@
+use crate::prelude::*;
+use bevy::{
+    pbr::{NotShadowCaster, NotShadowReceiver},
+    prelude::*,
+};
+use bevy_rapier3d::prelude::*;
+use leafwing_input_manager::prelude::*;
+use std::f32::consts::FRAC_PI_2;
+
+/// Size of gizmos relative to their distance from the camera, so they look the same size anywhere
+const GIZMO_SCALE: f32 = 0.15;
+/// Thickness of handles relative to the gizmo size
+const HANDLE_THICKNESS: f32 = 0.03;
+/// How far off a handle clicks still grab it, relative to the gizmo size
+const GRAB_TOLERANCE: f32 = 0.08;
+
+/// Shows translate, rotate or scale handles on the `EditorSelection` in the `Editor` camera state
+///
+/// Dragging a handle moves the selection along its axis, turns it around it or scales it along
+/// it, in the local space of the selection. Bevy 0.9 can't draw lines, so handles are unlit meshes
+/// that clicks are tested against by their shape rather than through rapier.
+pub struct GizmoPlugin;
+
+impl Plugin for GizmoPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GizmoMode>()
+            .init_resource::<GizmoGrab>()
+            .add_system_set(
+                SystemSet::on_update(CameraState::Editor)
+                    .with_system(grab_gizmo_handles.before(select_under_cursor))
+                    .with_system(drag_gizmo_handles.after(grab_gizmo_handles))
+                    .with_system(update_gizmos.after(select_under_cursor)),
+            )
+            .add_system_set(SystemSet::on_exit(CameraState::Editor).with_system(remove_gizmos));
+    }
+}
+
+/// Handles shown on the selection
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GizmoMode {
+    #[default]
+    Translate,
+    Rotate,
+    Scale,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GizmoAxis {
+    X,
+    Y,
+    Z,
+}
+
+impl GizmoAxis {
+    pub const ALL: [GizmoAxis; 3] = [GizmoAxis::X, GizmoAxis::Y, GizmoAxis::Z];
+
+    pub fn vector(&self) -> Vec3 {
+        match self {
+            GizmoAxis::X => Vec3::X,
+            GizmoAxis::Y => Vec3::Y,
+            GizmoAxis::Z => Vec3::Z,
+        }
+    }
+
+    fn color(&self) -> Color {
+        match self {
+            GizmoAxis::X => Color::rgb(0.9, 0.2, 0.2),
+            GizmoAxis::Y => Color::rgb(0.3, 0.85, 0.3),
+            GizmoAxis::Z => Color::rgb(0.25, 0.4, 0.95),
+        }
+    }
+}
+
+/// Root of the handles drawn on `target`
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TransformGizmo {
+    pub target: Entity,
+    pub mode: GizmoMode,
+}
+
+/// Mesh of a gizmo handle, left out of picking
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GizmoHandle(pub GizmoAxis);
+
+/// Handle held down since the select button went down
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Grab {
+    target: Entity,
+    mode: GizmoMode,
+    axis: GizmoAxis,
+    /// World space axis and center of the gizmo when grabbed
+    direction: Vec3,
+    center: Vec3,
+    /// Where the handle was grabbed
+    anchor: Vec3,
+    /// World transform of the target when grabbed
+    start: Transform,
+}
+
+/// Gizmo handle being dragged, if any
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq)]
+pub struct GizmoGrab(Option<Grab>);
+
+impl GizmoGrab {
+    pub fn grabbing(&self) -> bool {
+        self.0.is_some()
+    }
+
+    /// Entity the dragged handle belongs to
+    pub fn target(&self) -> Option<Entity> {
+        self.0.map(|grab| grab.target)
+    }
+}
+
+/// Spawns, replaces and places the gizmo of the selection
+#[allow(clippy::too_many_arguments)]
+fn update_gizmos(
+    mut commands: Commands,
+    mut gizmos: Query<(Entity, &TransformGizmo, &mut Transform)>,
+    targets: Query<&GlobalTransform>,
+    cameras: Query<&GlobalTransform, With<ControlledCamera>>,
+    selection: Res<EditorSelection>,
+    mode: Res<GizmoMode>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let target = selection
+        .0
+        .and_then(|target| Some((target, targets.get(target).ok()?)));
+    let placement = target.map(|(_, target)| {
+        let (_, rotation, translation) = target.to_scale_rotation_translation();
+        let distance = cameras
+            .get_single()
+            .map_or(10.0, |camera| camera.translation().distance(translation));
+        Transform::from_translation(translation)
+            .with_rotation(rotation)
+            .with_scale(Vec3::splat(distance * GIZMO_SCALE))
+    });
+
+    let mut current = false;
+    for (entity, gizmo, mut transform) in gizmos.iter_mut() {
+        let selected = target.map(|(target, _)| target);
+        let up_to_date = gizmo.mode == *mode && selected == Some(gizmo.target);
+        match (up_to_date, placement) {
+            (true, Some(placement)) => {
+                *transform = placement;
+                current = true;
+            }
+            _ => commands.entity(entity).despawn_recursive(),
+        }
+    }
+
+    let (Some((target, _)), Some(placement)) = (target, placement) else {
+        return;
+    };
+    if current {
+        return;
+    }
+
+    let gizmo = TransformGizmo {
+        target,
+        mode: *mode,
+    };
+    commands
+        .spawn((gizmo, SpatialBundle::from_transform(placement)))
+        .with_children(|root| {
+            for axis in GizmoAxis::ALL {
+                let material = materials.add(StandardMaterial {
+                    base_color: axis.color(),
+                    unlit: true,
+                    ..Default::default()
+                });
+                for (mesh, transform) in handle_meshes(*mode, axis) {
+                    root.spawn((
+                        PbrBundle {
+                            mesh: meshes.add(mesh),
+                            material: material.clone(),
+                            transform,
+                            ..Default::default()
+                        },
+                        GizmoHandle(axis),
+                        NotShadowCaster,
+                        NotShadowReceiver,
+                    ));
+                }
+            }
+        });
+}
+
+/// Meshes of the handle of an axis, for a gizmo of unit size
+fn handle_meshes(mode: GizmoMode, axis: GizmoAxis) -> Vec<(Mesh, Transform)> {
+    let direction = axis.vector();
+    let shaft_size = direction + (Vec3::ONE - direction) * HANDLE_THICKNESS;
+    let shaft = shape::Box::new(shaft_size.x, shaft_size.y, shaft_size.z);
+    let shaft = (shaft.into(), Transform::from_translation(direction * 0.5));
+
+    match mode {
+        GizmoMode::Translate => {
+            let tip = shape::Box::new(0.08, 0.08, 0.08);
+            vec![shaft, (tip.into(), Transform::from_translation(direction))]
+        }
+        GizmoMode::Scale => {
+            let tip = shape::Cube { size: 0.15 };
+            vec![shaft, (tip.into(), Transform::from_translation(direction))]
+        }
+        GizmoMode::Rotate => {
+            let ring = shape::Torus {
+                radius: 1.0,
+                ring_radius: HANDLE_THICKNESS / 2.0,
+                subdivisions_segments: 48,
+                subdivisions_sides: 8,
+            };
+            // Tori lie flat around `Y`
+            let rotation = match axis {
+                GizmoAxis::X => Quat::from_rotation_z(-FRAC_PI_2),
+                GizmoAxis::Y => Quat::IDENTITY,
+                GizmoAxis::Z => Quat::from_rotation_x(FRAC_PI_2),
+            };
+            vec![(ring.into(), Transform::from_rotation(rotation))]
+        }
+    }
+}
+
+/// Closest points of a ray and an axis, as distances along both and the gap between them
+fn closest_on_axis(
+    origin: Vec3,
+    direction: Vec3,
+    center: Vec3,
+    axis: Vec3,
+) -> Option<(f32, f32, f32)> {
+    let offset = origin - center;
+    let facing = direction.dot(axis);
+    let parallel = 1.0 - facing * facing;
+    if parallel < 1e-6 {
+        return None;
+    }
+
+    let along_ray = (facing * axis.dot(offset) - direction.dot(offset)) / parallel;
+    let along_axis = (axis.dot(offset) - facing * direction.dot(offset)) / parallel;
+    let gap = (origin + direction * along_ray).distance(center + axis * along_axis);
+    Some((along_ray, along_axis, gap))
+}
+
+fn grab_gizmo_handles(
+    gizmos: Query<(&TransformGizmo, &Transform)>,
+    targets: Query<&GlobalTransform>,
+    cameras: Query<(&Camera, &GlobalTransform, &ActionState<CameraAction>), With<ControlledCamera>>,
+    windows: Res<Windows>,
+    mut grab: ResMut<GizmoGrab>,
+) {
+    let Ok((camera, camera_transform, actions)) = cameras.get_single() else {
+        return;
+    };
+    if !actions.pressed(CameraAction::Select) {
+        grab.0 = None;
+        return;
+    }
+    if !actions.just_pressed(CameraAction::Select) {
+        return;
+    }
+
+    let Some(window) = windows.get_primary() else {
+        return;
+    };
+    let Some((origin, direction)) = cursor_ray(camera, camera_transform, window) else {
+        return;
+    };
+
+    // Closest handle along the ray
+    let mut closest: Option<(f32, Grab)> = None;
+    for (gizmo, transform) in gizmos.iter() {
+        let Ok(target) = targets.get(gizmo.target) else {
+            continue;
+        };
+        let size = transform.scale.x;
+        let center = transform.translation;
+
+        for axis in GizmoAxis::ALL {
+            let axis_direction = transform.rotation * axis.vector();
+            let hit = match gizmo.mode {
+                GizmoMode::Translate | GizmoMode::Scale => {
+                    closest_on_axis(origin, direction, center, axis_direction)
+                        .filter(|&(along_ray, along_axis, gap)| {
+                            along_ray > 0.0
+                                && (0.0..=size * 1.1).contains(&along_axis)
+                                && gap < size * GRAB_TOLERANCE
+                        })
+                        .map(|(along_ray, along_axis, _)| {
+                            (along_ray, center + axis_direction * along_axis)
+                        })
+                }
+                GizmoMode::Rotate => ray_plane(origin, direction, center, axis_direction)
+                    .filter(|point| (point.distance(center) - size).abs() < size * GRAB_TOLERANCE)
+                    .map(|point| (point.distance(origin), point)),
+            };
+            let Some((distance, anchor)) = hit else {
+                continue;
+            };
+
+            if closest.map_or(true, |(closest, _)| distance < closest) {
+                let grabbed = Grab {
+                    target: gizmo.target,
+                    mode: gizmo.mode,
+                    axis,
+                    direction: axis_direction,
+                    center,
+                    anchor,
+                    start: target.compute_transform(),
+                };
+                closest = Some((distance, grabbed));
+            }
+        }
+    }
+
+    grab.0 = closest.map(|(_, grabbed)| grabbed);
+}
+
+fn drag_gizmo_handles(
+    mut objects: Query<(&mut Transform, Option<&mut Velocity>, Option<&Parent>)>,
+    parents: Query<&GlobalTransform>,
+    cameras: Query<(&Camera, &GlobalTransform), With<ControlledCamera>>,
+    windows: Res<Windows>,
+    grab: Res<GizmoGrab>,
+) {
+    let Some(grab) = grab.0 else { return };
+    let Some(window) = windows.get_primary() else {
+        return;
+    };
+    let Ok((camera, camera_transform)) = cameras.get_single() else {
+        return;
+    };
+    let Some((origin, direction)) = cursor_ray(camera, camera_transform, window) else {
+        return;
+    };
+
+    let axis = grab.direction;
+    let grabbed_at = (grab.anchor - grab.center).dot(axis);
+    let along_axis =
+        closest_on_axis(origin, direction, grab.center, axis).map(|(_, along_axis, _)| along_axis);
+    let mut world = grab.start;
+
+    match grab.mode {
+        GizmoMode::Translate => {
+            let Some(along_axis) = along_axis else { return };
+            world.translation += axis * (along_axis - grabbed_at);
+        }
+        GizmoMode::Rotate => {
+            let Some(point) = ray_plane(origin, direction, grab.center, axis) else {
+                return;
+            };
+            let from = grab.anchor - grab.center;
+            let to = point - grab.center;
+            let angle = axis.dot(from.cross(to)).atan2(from.dot(to));
+            world.rotation = Quat::from_axis_angle(axis, angle) * grab.start.rotation;
+        }
+        GizmoMode::Scale => {
+            let Some(along_axis) = along_axis else { return };
+            if grabbed_at.abs() < f32::EPSILON {
+                return;
+            }
+            let factor = along_axis / grabbed_at;
+            let scaled = (world.scale * factor).max(Vec3::splat(0.05));
+            world.scale = Vec3::select(grab.axis.vector().cmpeq(Vec3::ONE), scaled, world.scale);
+        }
+    }
+
+    let Ok((mut transform, velocity, parent)) = objects.get_mut(grab.target) else {
+        return;
+    };
+    *transform = match parent.and_then(|parent| parents.get(parent.get()).ok()) {
+        Some(parent) => {
+            Transform::from_matrix(parent.compute_matrix().inverse() * world.compute_matrix())
+        }
+        None => world,
+    };
+    // Dynamic bodies would keep the speed they had before being grabbed
+    if let Some(mut velocity) = velocity {
+        *velocity = Velocity::zero();
+    }
+}
+
+fn remove_gizmos(
+    mut commands: Commands,
+    gizmos: Query<Entity, With<TransformGizmo>>,
+    mut grab: ResMut<GizmoGrab>,
+) {
+    for gizmo in gizmos.iter() {
+        commands.entity(gizmo).despawn_recursive();
+    }
+    grab.0 = None;
+}
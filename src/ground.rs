@@ -0,0 +1,119 @@
+use crate::prelude::*;
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CharacterGroundSettings {
+    /// How far below the feet the ground probe reaches
+    pub cast_distance: f32,
+    /// How far it reaches while grounded, so steps and slope seams don't let go of the ground
+    pub release_distance: f32,
+    /// Seconds the character still counts as grounded after losing contact
+    pub coyote_time: f32,
+}
+
+impl Default for CharacterGroundSettings {
+    fn default() -> Self {
+        CharacterGroundSettings {
+            cast_distance: 0.15,
+            release_distance: 0.3,
+            coyote_time: 0.1,
+        }
+    }
+}
+
+/// Smoothed ground contact of a character
+#[derive(Component, Debug, Default, Clone, Copy, PartialEq)]
+pub struct Grounded {
+    grounded: bool,
+    airtime: f32,
+    ground: Option<Entity>,
+    distance: f32,
+}
+
+impl Grounded {
+    pub fn get(&self) -> bool {
+        self.grounded
+    }
+
+    /// Seconds since the probe last touched the ground
+    pub fn airtime(&self) -> f32 {
+        self.airtime
+    }
+
+    /// Entity under the feet at the last contact
+    pub fn ground(&self) -> Option<Entity> {
+        self.ground
+    }
+
+    /// Gap between the feet and the ground at the last contact
+    pub fn distance(&self) -> f32 {
+        self.distance
+    }
+}
+
+pub fn update_grounded(
+    mut q: Query<(
+        Entity,
+        &mut Grounded,
+        &mut CharacterMovementController,
+        &KinematicCharacterControllerOutput,
+        &CharacterHeight,
+        &Transform,
+        Option<&KinematicCharacterController>,
+    )>,
+    rapier: Res<RapierContext>,
+    time: Res<SimulationTime>,
+) {
+    for (entity, mut grounded, mut character, physics, height, transform, controller) in
+        q.iter_mut()
+    {
+        let settings = character.settings();
+        let ground = settings.ground;
+
+        // No coyote time or hysteresis while moving up, otherwise jumps get grounded mid-air
+        let ascending = physics.effective_translation.y > 0.0;
+        let reach = match grounded.grounded && !ascending {
+            true => ground.release_distance.max(ground.cast_distance),
+            false => ground.cast_distance,
+        };
+
+        // Cast a slightly thinner sphere from the center of the bottom cap
+        let radius = settings.radius() * 0.9;
+        let cap = settings.segment_half_height(height.current());
+        let origin = transform.translation - Vec3::Y * cap;
+        let shape = Collider::ball(radius);
+        let max_toi = settings.radius() - radius + reach;
+        // Skip what the controller passes through, like carried boxes and one-way platforms
+        let filter = match controller.and_then(|controller| controller.filter_groups) {
+            Some(groups) => settings.collision.query_filter(entity).groups(groups),
+            None => settings.collision.query_filter(entity),
+        };
+
+        let hit = rapier.cast_shape(origin, Quat::IDENTITY, Vec3::NEG_Y, &shape, max_toi, filter);
+
+        match hit {
+            Some((ground, toi)) => {
+                grounded.grounded = true;
+                grounded.airtime = 0.0;
+                grounded.ground = Some(ground);
+                grounded.distance = (toi.toi - (settings.radius() - radius)).max(0.0);
+            }
+            // The controller touches ground the thinner probe misses, like the edge of a ledge
+            None if physics.grounded && !ascending => {
+                grounded.grounded = true;
+                grounded.airtime = 0.0;
+                grounded.distance = 0.0;
+            }
+            None => {
+                grounded.airtime += time.delta_seconds();
+                grounded.grounded = !ascending && grounded.airtime <= ground.coyote_time;
+            }
+        }
+
+        let value = grounded.grounded;
+        character.set_grounded(value);
+    }
+}
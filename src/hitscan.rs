@@ -0,0 +1,207 @@
+use crate::prelude::*;
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use leafwing_input_manager::prelude::*;
+
+/// Fires the `HitscanWeapon` of characters with the `Fire` action, sending `Hit`s
+///
+/// Characters don't shoot while carrying, their hands are full.
+pub struct HitscanPlugin;
+
+impl Plugin for HitscanPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<Hit>()
+            .init_resource::<ImpactMarkerAssets>()
+            .add_system(fire_hitscan)
+            .add_system(despawn_impact_markers);
+    }
+}
+
+/// Weapon hitting the first collider along the view the moment it's fired
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct HitscanWeapon {
+    /// Farthest hit from the camera or eyes, in meters
+    pub range: f32,
+    /// Impulse given to dynamic bodies that are hit, in kilogram meters per second
+    pub impulse: f32,
+    /// Seconds between shots
+    pub cooldown: f32,
+    /// Keeps firing while `Fire` is held
+    pub automatic: bool,
+    /// Colliders the ray may hit
+    pub groups: CollisionGroups,
+    /// Seconds impact markers stay around, none are spawned at zero
+    pub marker_lifetime: f32,
+    remaining: f32,
+}
+
+impl Default for HitscanWeapon {
+    fn default() -> Self {
+        HitscanWeapon {
+            range: 100.0,
+            impulse: 10.0,
+            cooldown: 0.2,
+            automatic: false,
+            groups: CollisionGroups::default(),
+            marker_lifetime: 3.0,
+            remaining: 0.0,
+        }
+    }
+}
+
+impl HitscanWeapon {
+    pub fn automatic(mut self) -> Self {
+        self.automatic = true;
+        self
+    }
+
+    pub fn with_groups(mut self, groups: CollisionGroups) -> Self {
+        self.groups = groups;
+        self
+    }
+}
+
+/// A shot of `shooter` hit `entity`, the body of the collider if it has one
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Hit {
+    pub shooter: Entity,
+    pub entity: Entity,
+    pub point: Vec3,
+    pub normal: Vec3,
+}
+
+/// Small sphere left where a shot hit
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct ImpactMarker {
+    /// Seconds left until it's despawned
+    pub remaining: f32,
+}
+
+/// Mesh and material shared by all impact markers
+#[derive(Resource)]
+pub struct ImpactMarkerAssets {
+    pub mesh: Handle<Mesh>,
+    pub material: Handle<StandardMaterial>,
+}
+
+impl FromWorld for ImpactMarkerAssets {
+    fn from_world(world: &mut World) -> Self {
+        let sphere = shape::UVSphere {
+            radius: 0.05,
+            ..Default::default()
+        };
+        let mesh = world.resource_mut::<Assets<Mesh>>().add(Mesh::from(sphere));
+        let material = world
+            .resource_mut::<Assets<StandardMaterial>>()
+            .add(StandardMaterial {
+                base_color: Color::rgb(1.0, 0.3, 0.1),
+                unlit: true,
+                ..Default::default()
+            });
+
+        ImpactMarkerAssets { mesh, material }
+    }
+}
+
+#[allow(clippy::type_complexity)]
+pub fn fire_hitscan(
+    mut commands: Commands,
+    mut shooters: Query<(
+        Entity,
+        &mut HitscanWeapon,
+        &ActionState<CharacterActions>,
+        &Transform,
+        &CharacterMovementController,
+        &CharacterHeight,
+        Option<&Carrier>,
+    )>,
+    mut bodies: Query<(&RigidBody, &GlobalTransform, Option<&mut ExternalImpulse>)>,
+    cameras: Query<(&Camera, &GlobalTransform), With<Camera3d>>,
+    mut hits: EventWriter<Hit>,
+    markers: Res<ImpactMarkerAssets>,
+    rapier: Res<RapierContext>,
+    time: Res<Time>,
+) {
+    for (entity, mut weapon, actions, transform, character, height, carrier) in shooters.iter_mut()
+    {
+        weapon.remaining = (weapon.remaining - time.delta_seconds()).max(0.0);
+
+        let firing = match weapon.automatic {
+            true => actions.pressed(CharacterActions::Fire),
+            false => actions.just_pressed(CharacterActions::Fire),
+        };
+        let carrying = carrier.and_then(|carrier| carrier.carried()).is_some();
+        if !firing || carrying || weapon.remaining > 0.0 {
+            continue;
+        }
+        weapon.remaining = weapon.cooldown;
+
+        let eye_height = character.settings().eye_height(height.current());
+        let eye = transform.translation + Vec3::Y * eye_height;
+        let (origin, direction) = aim(&cameras, eye, transform.rotation);
+
+        // A third person camera sits behind the character, range counts from its eyes
+        let max_toi = origin.distance(eye) + weapon.range;
+        let filter = QueryFilter::default()
+            .exclude_rigid_body(entity)
+            .exclude_collider(entity)
+            .exclude_sensors()
+            .groups(weapon.groups);
+        let hit = rapier.cast_ray_and_get_normal(origin, direction, max_toi, true, filter);
+        let Some((collider, hit)) = hit else { continue };
+
+        let target = rapier.collider_parent(collider).unwrap_or(collider);
+        if let Ok((body, body_transform, impulse)) = bodies.get_mut(target) {
+            if *body == RigidBody::Dynamic {
+                let push = direction * weapon.impulse;
+                let torque = (hit.point - body_transform.translation()).cross(push);
+                match impulse {
+                    Some(mut impulse) => {
+                        impulse.impulse += push;
+                        impulse.torque_impulse += torque;
+                    }
+                    None => {
+                        commands.entity(target).insert(ExternalImpulse {
+                            impulse: push,
+                            torque_impulse: torque,
+                        });
+                    }
+                }
+            }
+        }
+
+        if weapon.marker_lifetime > 0.0 {
+            commands.spawn((
+                PbrBundle {
+                    mesh: markers.mesh.clone(),
+                    material: markers.material.clone(),
+                    transform: Transform::from_translation(hit.point),
+                    ..Default::default()
+                },
+                ImpactMarker {
+                    remaining: weapon.marker_lifetime,
+                },
+            ));
+        }
+
+        hits.send(Hit {
+            shooter: entity,
+            entity: target,
+            point: hit.point,
+            normal: hit.normal,
+        });
+    }
+}
+
+fn despawn_impact_markers(
+    mut commands: Commands,
+    mut markers: Query<(Entity, &mut ImpactMarker)>,
+    time: Res<Time>,
+) {
+    for (entity, mut marker) in markers.iter_mut() {
+        marker.remaining -= time.delta_seconds();
+        if marker.remaining <= 0.0 {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
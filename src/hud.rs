@@ -0,0 +1,73 @@
+use bevy::prelude::*;
+
+/// Font used by debug overlays, relative to the asset folder
+pub const HUD_FONT: &str = "fonts/DejaVuSansMono.ttf";
+
+/// Short notice shown at the bottom of the screen, replacing the previous one
+#[derive(Debug, Clone, PartialEq)]
+pub struct HudMessage {
+    pub text: String,
+    /// Seconds until it has faded out
+    pub duration: f32,
+}
+
+impl HudMessage {
+    pub fn new(text: impl Into<String>) -> Self {
+        HudMessage {
+            text: text.into(),
+            duration: 1.5,
+        }
+    }
+}
+
+/// Text node showing the latest `HudMessage`
+#[derive(Component, Debug, Default, Clone, Copy, PartialEq)]
+pub struct HudMessageText {
+    remaining: f32,
+    duration: f32,
+}
+
+pub fn spawn_hud(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let style = TextStyle {
+        font: asset_server.load(HUD_FONT),
+        font_size: 18.0,
+        color: Color::NONE,
+    };
+
+    commands
+        .spawn(TextBundle::from_section("", style).with_style(Style {
+            position_type: PositionType::Absolute,
+            position: UiRect {
+                bottom: Val::Px(16.0),
+                left: Val::Px(16.0),
+                ..Default::default()
+            },
+            ..Default::default()
+        }))
+        .insert(HudMessageText::default());
+}
+
+pub fn update_hud(
+    mut messages: EventReader<HudMessage>,
+    mut q: Query<(&mut Text, &mut HudMessageText)>,
+    time: Res<Time>,
+) {
+    let latest = messages.iter().last();
+
+    for (mut text, mut hud) in q.iter_mut() {
+        if let Some(message) = latest {
+            text.sections[0].value = message.text.clone();
+            hud.remaining = message.duration;
+            hud.duration = message.duration;
+        }
+
+        hud.remaining = (hud.remaining - time.delta_seconds()).max(0.0);
+
+        // Hold for most of the duration, then fade out over the last third
+        let alpha = match hud.duration > 0.0 {
+            true => (hud.remaining / hud.duration * 3.0).min(1.0),
+            false => 0.0,
+        };
+        text.sections[0].style.color = Color::rgba(1.0, 1.0, 1.0, alpha);
+    }
+}
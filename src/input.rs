@@ -0,0 +1,188 @@
+use crate::prelude::*;
+use bevy::prelude::*;
+use leafwing_input_manager::prelude::*;
+
+/// What a character wants to do, whether it comes from a device or a provider
+#[derive(Component, Debug, Default, Clone, Copy, PartialEq)]
+pub struct CharacterInput {
+    /// Local space direction with `-Z` forward, clamped to unit length
+    pub movement: Vec3,
+    pub jump: bool,
+    pub sprint: bool,
+    pub crouch: bool,
+    pub interact: bool,
+    pub throw: bool,
+    pub fire: bool,
+}
+
+impl CharacterInput {
+    pub fn pressed(&self, action: CharacterActions) -> bool {
+        match action {
+            CharacterActions::Jump => self.jump,
+            CharacterActions::Sprint => self.sprint,
+            CharacterActions::Crouch => self.crouch,
+            CharacterActions::Interact => self.interact,
+            CharacterActions::Throw => self.throw,
+            CharacterActions::Fire => self.fire,
+        }
+    }
+}
+
+/// Everything a provider may look at while deciding on an intent
+pub struct InputProviderContext<'a> {
+    pub entity: Entity,
+    pub state: CharacterState,
+    pub transform: &'a Transform,
+    pub grounded: bool,
+    /// Seconds since the last update
+    pub delta: f32,
+}
+
+/// Non-device source of character input, e.g. an AI agent or a cutscene script
+pub trait CharacterInputProvider: Send + Sync + 'static {
+    fn intent(&mut self, ctx: &InputProviderContext) -> CharacterInput;
+}
+
+/// Drives a character from a provider instead of its input maps
+///
+/// The input maps of the character are removed, so devices no longer affect it.
+#[derive(Component)]
+pub struct CharacterInputDriver(Box<dyn CharacterInputProvider>);
+
+impl CharacterInputDriver {
+    pub fn new(provider: impl CharacterInputProvider) -> Self {
+        CharacterInputDriver(Box::new(provider))
+    }
+}
+
+/// Plays back a list of intents, each held for a number of seconds
+#[derive(Debug, Default, Clone)]
+pub struct ScriptedInput {
+    steps: Vec<(f32, CharacterInput)>,
+    elapsed: f32,
+    looping: bool,
+}
+
+impl ScriptedInput {
+    pub fn new() -> Self {
+        ScriptedInput::default()
+    }
+
+    pub fn then(mut self, seconds: f32, input: CharacterInput) -> Self {
+        self.steps.push((seconds, input));
+        self
+    }
+
+    /// Start over once the last step finished instead of standing still
+    pub fn looping(mut self) -> Self {
+        self.looping = true;
+        self
+    }
+
+    pub fn finished(&self) -> bool {
+        !self.looping && self.elapsed >= self.duration()
+    }
+
+    fn duration(&self) -> f32 {
+        self.steps.iter().map(|(seconds, _)| seconds).sum()
+    }
+}
+
+impl CharacterInputProvider for ScriptedInput {
+    fn intent(&mut self, ctx: &InputProviderContext) -> CharacterInput {
+        self.elapsed += ctx.delta;
+
+        let duration = self.duration();
+        if self.looping && duration > 0.0 {
+            self.elapsed %= duration;
+        }
+
+        let mut start = 0.0;
+        for (seconds, input) in self.steps.iter() {
+            start += seconds;
+            if self.elapsed < start {
+                return *input;
+            }
+        }
+
+        CharacterInput::default()
+    }
+}
+
+pub fn read_device_input(
+    mut q: Query<
+        (
+            &mut CharacterInput,
+            &ActionState<CharacterMovement>,
+            &ActionState<CharacterActions>,
+        ),
+        Without<CharacterInputDriver>,
+    >,
+    settings: Res<InputSettings>,
+) {
+    for (mut input, movement, actions) in q.iter_mut() {
+        // Keys are fully pressed, sticks only as far as they are tilted
+        let digital = movement
+            .get_pressed()
+            .iter()
+            .map(|direction| direction.into_vec() * movement.value(*direction).abs().min(1.0))
+            .sum::<Vec3>();
+        let analog = movement
+            .pressed(CharacterMovement::Move)
+            .then(|| movement.axis_pair(CharacterMovement::Move))
+            .flatten()
+            .map(|stick| settings.movement_stick.apply(stick.xy()))
+            .map_or(Vec3::ZERO, |stick| Vec3::new(stick.x, 0.0, -stick.y));
+
+        input.movement = (digital + analog).clamp_length_max(1.0);
+        input.jump = actions.pressed(CharacterActions::Jump);
+        input.sprint = actions.pressed(CharacterActions::Sprint);
+        input.crouch = actions.pressed(CharacterActions::Crouch);
+        input.interact = actions.pressed(CharacterActions::Interact);
+        input.throw = actions.pressed(CharacterActions::Throw);
+        input.fire = actions.pressed(CharacterActions::Fire);
+    }
+}
+
+pub fn detach_input_maps(mut commands: Commands, q: Query<Entity, Added<CharacterInputDriver>>) {
+    for entity in q.iter() {
+        commands
+            .entity(entity)
+            .remove::<InputMap<CharacterMovement>>()
+            .remove::<InputMap<CharacterActions>>();
+    }
+}
+
+/// Feeds provider intents through the action states, so abilities and transitions see them
+pub fn drive_character_input(
+    mut q: Query<(
+        Entity,
+        &mut CharacterInputDriver,
+        &mut CharacterInput,
+        &mut ActionState<CharacterActions>,
+        &CharacterState,
+        &Transform,
+        &Grounded,
+    )>,
+    time: Res<SimulationTime>,
+) {
+    for (entity, mut driver, mut input, mut actions, state, transform, grounded) in q.iter_mut() {
+        let ctx = InputProviderContext {
+            entity,
+            state: *state,
+            transform,
+            grounded: grounded.get(),
+            delta: time.delta_seconds(),
+        };
+
+        *input = driver.0.intent(&ctx);
+        input.movement = input.movement.clamp_length_max(1.0);
+
+        for action in CharacterActions::variants() {
+            match input.pressed(action) {
+                true => actions.press(action),
+                false => actions.release(action),
+            }
+        }
+    }
+}
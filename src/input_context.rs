@@ -0,0 +1,100 @@
+use crate::prelude::*;
+use bevy::prelude::*;
+use leafwing_input_manager::{plugin::ToggleActions, prelude::*};
+
+/// Tracks what has focus and turns character and camera input on or off to match
+///
+/// Menus and the console should `push` their context and `pop` it when closed.
+pub struct InputContextPlugin;
+
+impl Plugin for InputContextPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_state(InputContext::Gameplay);
+
+        for context in InputContext::ALL {
+            app.add_system_set(SystemSet::on_enter(context).with_system(apply_input_context))
+                .add_system_set(SystemSet::on_resume(context).with_system(apply_input_context));
+        }
+    }
+}
+
+/// What player input is meant for
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum InputContext {
+    #[default]
+    Gameplay,
+    /// Pause or settings menus, nothing in the world listens
+    Menu,
+    /// Typing into the `Console`, which holds this context while open
+    Console,
+    /// Level editing, the camera moves but characters stay put
+    Editor,
+}
+
+impl InputContext {
+    pub const ALL: [InputContext; 4] = [
+        InputContext::Gameplay,
+        InputContext::Menu,
+        InputContext::Console,
+        InputContext::Editor,
+    ];
+
+    pub fn character_input(&self) -> bool {
+        matches!(self, InputContext::Gameplay)
+    }
+
+    pub fn camera_input(&self) -> bool {
+        matches!(self, InputContext::Gameplay | InputContext::Editor)
+    }
+}
+
+fn toggle<A: Actionlike>(toggle: Option<ResMut<ToggleActions<A>>>, enabled: bool) {
+    if let Some(mut toggle) = toggle {
+        toggle.enabled = enabled;
+    }
+}
+
+/// Keys held while switching would otherwise stay pressed
+fn release<A: Actionlike>(states: &mut Query<&mut ActionState<A>>, enabled: bool) {
+    if !enabled {
+        states.for_each_mut(|mut state| state.release_all());
+    }
+}
+
+pub fn apply_input_context(
+    context: Res<State<InputContext>>,
+    movement: Option<ResMut<ToggleActions<CharacterMovement>>>,
+    actions: Option<ResMut<ToggleActions<CharacterActions>>>,
+    mut movement_states: Query<&mut ActionState<CharacterMovement>>,
+    mut action_states: Query<&mut ActionState<CharacterActions>>,
+    #[cfg(feature = "debug_camera")] camera: (
+        Option<ResMut<ToggleActions<CameraAction>>>,
+        Option<ResMut<ToggleActions<CameraMovement>>>,
+        Query<&mut ActionState<CameraAction>>,
+        Query<&mut ActionState<CameraMovement>>,
+        Option<Res<Spectating>>,
+    ),
+) {
+    let context = *context.current();
+    #[allow(unused_mut)]
+    let mut character = context.character_input();
+
+    #[cfg(feature = "debug_camera")]
+    {
+        let (camera_actions, camera_movement, mut action_states, mut movement_states, spectating) =
+            camera;
+        let enabled = context.camera_input();
+        toggle(camera_actions, enabled);
+        toggle(camera_movement, enabled);
+        release(&mut action_states, enabled);
+        release(&mut movement_states, enabled);
+
+        // Spectating keeps the player frozen until it's toggled off
+        character &= !spectating.map_or(false, |spectating| spectating.active());
+    }
+
+    toggle(movement, character);
+    toggle(actions, character);
+    release(&mut movement_states, character);
+    release(&mut action_states, character);
+}
@@ -0,0 +1,106 @@
+use crate::prelude::*;
+use bevy::prelude::*;
+use leafwing_input_manager::prelude::*;
+
+/// Overlay of the movement and actions a character is pressing, like a speedrunner's input viewer
+pub struct InputDisplayPlugin;
+
+impl Plugin for InputDisplayPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_startup_system(spawn_input_display)
+            .add_system(update_input_display);
+    }
+}
+
+/// Character shown by the input display, the first one with action states if none has this
+#[derive(Component, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct InputDisplayTarget;
+
+/// Text node listing every input, pressed ones highlighted
+#[derive(Component, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct InputDisplayText;
+
+const PRESSED_COLOR: Color = Color::WHITE;
+const RELEASED_COLOR: Color = Color::rgba(1.0, 1.0, 1.0, 0.2);
+
+/// Digital directions in the order shown, the stick and actions follow them
+const DIRECTIONS: [(CharacterMovement, &str); 4] = [
+    (CharacterMovement::Left, "←"),
+    (CharacterMovement::Back, "↓"),
+    (CharacterMovement::Forward, "↑"),
+    (CharacterMovement::Right, "→"),
+];
+
+fn stick_label(stick: Vec2) -> String {
+    format!(" {:+.2} {:+.2} ", stick.x, stick.y)
+}
+
+pub fn spawn_input_display(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let style = TextStyle {
+        font: asset_server.load(HUD_FONT),
+        font_size: 18.0,
+        color: RELEASED_COLOR,
+    };
+
+    let directions = DIRECTIONS.iter().map(|(_, arrow)| format!("{arrow} "));
+    let stick = std::iter::once(stick_label(Vec2::ZERO));
+    let actions = CharacterActions::variants().map(|action| format!(" {action:?}"));
+    let sections = directions
+        .chain(stick)
+        .chain(actions)
+        .map(|label| TextSection::new(label, style.clone()));
+
+    commands
+        .spawn(TextBundle::from_sections(sections).with_style(Style {
+            position_type: PositionType::Absolute,
+            position: UiRect {
+                top: Val::Px(16.0),
+                right: Val::Px(16.0),
+                ..Default::default()
+            },
+            ..Default::default()
+        }))
+        .insert(InputDisplayText);
+}
+
+pub fn update_input_display(
+    mut displays: Query<&mut Text, With<InputDisplayText>>,
+    targets: Query<(
+        &ActionState<CharacterMovement>,
+        &ActionState<CharacterActions>,
+        Option<&InputDisplayTarget>,
+    )>,
+) {
+    let target = targets.iter().find(|(_, _, target)| target.is_some());
+    let Some((movement, actions, _)) = target.or_else(|| targets.iter().next()) else {
+        return;
+    };
+
+    let color = |pressed: bool| match pressed {
+        true => PRESSED_COLOR,
+        false => RELEASED_COLOR,
+    };
+
+    let stick = movement
+        .pressed(CharacterMovement::Move)
+        .then(|| movement.axis_pair(CharacterMovement::Move))
+        .flatten()
+        .map(|stick| stick.xy());
+
+    for mut text in displays.iter_mut() {
+        let mut sections = text.sections.iter_mut();
+
+        for ((direction, _), section) in DIRECTIONS.iter().zip(sections.by_ref()) {
+            section.style.color = color(movement.pressed(*direction));
+        }
+
+        if let Some(section) = sections.next() {
+            section.value = stick_label(stick.unwrap_or_default());
+            section.style.color = color(stick.is_some());
+        }
+
+        for (action, section) in CharacterActions::variants().zip(sections) {
+            section.style.color = color(actions.pressed(action));
+        }
+    }
+}
@@ -0,0 +1,128 @@
+use crate::prelude::*;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Player input preferences read by both the character and the camera systems
+#[derive(Resource, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct InputSettings {
+    /// Multiplier on mouse look, on top of the camera's look sensitivity
+    pub mouse_sensitivity: f32,
+    /// Multiplier on stick look
+    pub gamepad_sensitivity: f32,
+    /// Mouse pixels per second a fully tilted stick turns like
+    pub stick_speed: f32,
+    /// Per-axis multipliers applied to mouse and stick look alike
+    pub horizontal_sensitivity: f32,
+    pub vertical_sensitivity: f32,
+    pub invert_x: bool,
+    pub invert_y: bool,
+    /// Stick the character moves with
+    pub movement_stick: StickSettings,
+    /// Stick the camera looks around with
+    pub look_stick: StickSettings,
+    pub accessibility: AccessibilitySettings,
+}
+
+impl Default for InputSettings {
+    fn default() -> Self {
+        InputSettings {
+            mouse_sensitivity: 1.0,
+            gamepad_sensitivity: 1.0,
+            stick_speed: 600.0,
+            horizontal_sensitivity: 1.0,
+            vertical_sensitivity: 1.0,
+            invert_x: false,
+            invert_y: false,
+            movement_stick: Default::default(),
+            look_stick: Default::default(),
+            accessibility: Default::default(),
+        }
+    }
+}
+
+impl InputSettings {
+    /// Combines mouse motion with stick motion of the same unit, scaled and flipped
+    pub fn look(&self, mouse: Vec2, stick: Vec2) -> Vec2 {
+        let motion = mouse * self.mouse_sensitivity + stick * self.gamepad_sensitivity;
+        let x = if self.invert_x { -motion.x } else { motion.x };
+        let y = if self.invert_y { -motion.y } else { motion.y };
+        Vec2::new(
+            x * self.horizontal_sensitivity,
+            y * self.vertical_sensitivity,
+        )
+    }
+}
+
+/// How stick tilt past the dead zone maps to output
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ResponseCurve {
+    #[default]
+    Linear,
+    /// Finer control near the center, full speed at the edge
+    Squared,
+    /// Output at points of tilt between `(0, 0)` and `(1, 1)`, linear in between
+    Custom(Vec<(f32, f32)>),
+}
+
+impl ResponseCurve {
+    /// Maps tilt in `0..=1` to output in `0..=1`
+    pub fn apply(&self, tilt: f32) -> f32 {
+        let tilt = tilt.clamp(0.0, 1.0);
+        match self {
+            ResponseCurve::Linear => tilt,
+            ResponseCurve::Squared => tilt * tilt,
+            ResponseCurve::Custom(points) => {
+                let mut previous = (0.0, 0.0);
+                for &(x, y) in points.iter().chain([&(1.0, 1.0)]) {
+                    if tilt <= x {
+                        let t = (tilt - previous.0) / (x - previous.0).max(f32::EPSILON);
+                        return previous.1 + (y - previous.1) * t;
+                    }
+                    previous = (x, y);
+                }
+                1.0
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StickSettings {
+    /// Tilt of each axis ignored before it registers, `0..1`
+    pub deadzone_x: f32,
+    pub deadzone_y: f32,
+    pub curve: ResponseCurve,
+}
+
+impl Default for StickSettings {
+    fn default() -> Self {
+        StickSettings {
+            deadzone_x: 0.1,
+            deadzone_y: 0.1,
+            curve: ResponseCurve::Linear,
+        }
+    }
+}
+
+impl StickSettings {
+    /// Removes the dead zones, rescaled so their edges start from nothing, then applies the curve
+    pub fn apply(&self, stick: Vec2) -> Vec2 {
+        let axis = |value: f32, deadzone: f32| {
+            let deadzone = deadzone.clamp(0.0, 0.99);
+            let tilt = ((value.abs() - deadzone) / (1.0 - deadzone)).clamp(0.0, 1.0);
+            tilt.copysign(value)
+        };
+        let stick = Vec2::new(
+            axis(stick.x, self.deadzone_x),
+            axis(stick.y, self.deadzone_y),
+        );
+
+        let tilt = stick.length();
+        if tilt == 0.0 {
+            return Vec2::ZERO;
+        }
+        stick / tilt * self.curve.apply(tilt.min(1.0))
+    }
+}
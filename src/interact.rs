@@ -0,0 +1,204 @@
+use crate::prelude::*;
+use bevy::{prelude::*, render::camera::RenderTarget, window::WindowId};
+use bevy_rapier3d::prelude::*;
+use leafwing_input_manager::prelude::*;
+
+/// Lets characters with an `Interactor` aim at and use `Interactable` entities
+pub struct InteractionPlugin;
+
+impl Plugin for InteractionPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<Interacted>()
+            .add_system(update_interaction_focus)
+            .add_system(interact.after(update_interaction_focus));
+    }
+}
+
+/// Character using interactables it aims at with the `Interact` action
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct Interactor {
+    /// Reach from the eyes of the character, in meters
+    pub range: f32,
+    focus: Option<Entity>,
+}
+
+impl Default for Interactor {
+    fn default() -> Self {
+        Interactor {
+            range: 2.5,
+            focus: None,
+        }
+    }
+}
+
+impl Interactor {
+    /// Interactable currently aimed at and highlighted
+    pub fn focus(&self) -> Option<Entity> {
+        self.focus
+    }
+}
+
+/// Entity a character can use, its colliders may sit on it or its children
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct Interactable {
+    /// Emissive color of the material while aimed at
+    pub highlight: Color,
+}
+
+impl Default for Interactable {
+    fn default() -> Self {
+        Interactable {
+            highlight: Color::rgb(0.25, 0.25, 0.1),
+        }
+    }
+}
+
+/// Emissive color the material of a highlighted interactable had before
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct Highlighted {
+    previous: Color,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Interacted {
+    pub interactor: Entity,
+    pub target: Entity,
+}
+
+/// Closest ancestor of `entity`, itself included, that is interactable
+fn interactable_of(
+    entity: Entity,
+    interactables: &Query<&Interactable>,
+    parents: &Query<&Parent>,
+) -> Option<Entity> {
+    let mut current = Some(entity);
+    while let Some(entity) = current {
+        if interactables.contains(entity) {
+            return Some(entity);
+        }
+        current = parents.get(entity).ok().map(|parent| parent.get());
+    }
+    None
+}
+
+/// Whether `camera` is the one filling the primary window, rather than a minimap or inset view
+pub fn is_main_camera(camera: &Camera) -> bool {
+    camera.is_active
+        && camera.viewport.is_none()
+        && camera.target == RenderTarget::Window(WindowId::primary())
+}
+
+/// Ray a character aims along, through the active camera if there is one
+pub fn aim(
+    cameras: &Query<(&Camera, &GlobalTransform), With<Camera3d>>,
+    eye: Vec3,
+    rotation: Quat,
+) -> (Vec3, Vec3) {
+    let camera = cameras.iter().find(|(camera, _)| is_main_camera(camera));
+
+    match camera {
+        Some((_, transform)) => (transform.translation(), transform.forward()),
+        None => (eye, rotation * Vec3::NEG_Z),
+    }
+}
+
+/// Sets the emissive color of the materials on `target` and its children, returning the old one
+///
+/// Materials shared with other entities light up on those as well.
+fn set_emissive(
+    target: Entity,
+    color: Color,
+    handles: &Query<&Handle<StandardMaterial>>,
+    children: &Query<&Children>,
+    materials: &mut Assets<StandardMaterial>,
+) -> Color {
+    let below = children.get(target).map(|children| children.to_vec());
+    let mut previous = None;
+
+    for entity in std::iter::once(target).chain(below.unwrap_or_default()) {
+        let Ok(handle) = handles.get(entity) else {
+            continue;
+        };
+        let Some(material) = materials.get_mut(handle) else {
+            continue;
+        };
+        previous.get_or_insert(material.emissive);
+        material.emissive = color;
+    }
+    previous.unwrap_or(Color::BLACK)
+}
+
+#[allow(clippy::type_complexity)]
+pub fn update_interaction_focus(
+    mut commands: Commands,
+    mut interactors: Query<(
+        Entity,
+        &mut Interactor,
+        &Transform,
+        &CharacterMovementController,
+        &CharacterHeight,
+    )>,
+    cameras: Query<(&Camera, &GlobalTransform), With<Camera3d>>,
+    interactables: Query<&Interactable>,
+    parents: Query<&Parent>,
+    highlighted: Query<&Highlighted>,
+    handles: Query<&Handle<StandardMaterial>>,
+    children: Query<&Children>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    rapier: Res<RapierContext>,
+) {
+    for (entity, mut interactor, transform, character, height) in interactors.iter_mut() {
+        let eye_height = character.settings().eye_height(height.current());
+        let eye = transform.translation + Vec3::Y * eye_height;
+        let (origin, direction) = aim(&cameras, eye, transform.rotation);
+
+        // A third person camera sits behind the character, reach counts from its eyes
+        let max_toi = origin.distance(eye) + interactor.range;
+        let filter = QueryFilter::default()
+            .exclude_rigid_body(entity)
+            .exclude_collider(entity)
+            .exclude_sensors();
+        let focus = rapier
+            .cast_ray(origin, direction, max_toi, true, filter)
+            .filter(|(_, toi)| (origin + direction * *toi).distance(eye) <= interactor.range)
+            .and_then(|(hit, _)| interactable_of(hit, &interactables, &parents));
+
+        if focus == interactor.focus {
+            continue;
+        }
+
+        if let Some(previous) = interactor.focus {
+            if let Ok(highlight) = highlighted.get(previous) {
+                set_emissive(
+                    previous,
+                    highlight.previous,
+                    &handles,
+                    &children,
+                    &mut materials,
+                );
+                commands.entity(previous).remove::<Highlighted>();
+            }
+        }
+        if let Some(target) = focus {
+            let color = interactables.get(target).unwrap().highlight;
+            let previous = set_emissive(target, color, &handles, &children, &mut materials);
+            commands.entity(target).insert(Highlighted { previous });
+        }
+
+        interactor.focus = focus;
+    }
+}
+
+pub fn interact(
+    interactors: Query<(Entity, &Interactor, &ActionState<CharacterActions>)>,
+    mut events: EventWriter<Interacted>,
+) {
+    for (interactor, focus, actions) in interactors.iter() {
+        if !actions.just_pressed(CharacterActions::Interact) {
+            continue;
+        }
+        if let Some(target) = focus.focus() {
+            events.send(Interacted { interactor, target });
+        }
+    }
+}
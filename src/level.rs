@@ -0,0 +1,209 @@
+use crate::prelude::*;
+use bevy::{gltf::GltfExtras, prelude::*};
+use bevy_rapier3d::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Instantiates `.level.ron` levels and generates fixed colliders for the meshes of glTF scenes
+/// spawned with `LevelColliders`
+///
+/// Meshes are tagged by a node name suffix, `-col` for a trimesh, `-convcol` for a convex hull
+/// and `-colonly` for an invisible trimesh, or by `{ "collider": "trimesh" | "convex" | "none" }`
+/// in the node extras, which take precedence. Nodes with `{ "player_spawn": "name" }` extras become
+/// a `PlayerSpawn` of that name, unnamed if empty.
+pub struct LevelPlugin;
+
+impl Plugin for LevelPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_asset::<LevelDescription>()
+            .add_asset_loader(RonAssetLoader::<LevelDescription>::new(&["level.ron"]))
+            .add_event::<LevelSpawned>()
+            .add_event::<LevelReloading>()
+            .add_system(instantiate_levels)
+            .add_system(tag_level_meshes)
+            .add_system(tag_player_spawns)
+            .add_system(generate_level_colliders.after(tag_level_meshes));
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LevelCollider {
+    #[serde(alias = "trimesh")]
+    TriMesh,
+    /// Convex hull, cheaper and also usable on dynamic bodies
+    Convex,
+}
+
+impl LevelCollider {
+    fn shape(&self) -> ComputedColliderShape {
+        match self {
+            LevelCollider::TriMesh => ComputedColliderShape::TriMesh,
+            LevelCollider::Convex => ComputedColliderShape::ConvexHull,
+        }
+    }
+}
+
+/// Root of a level scene, its tagged meshes get colliders
+#[derive(Component, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct LevelColliders {
+    /// Collider of meshes without a tag, none by default
+    pub untagged: Option<LevelCollider>,
+}
+
+impl LevelColliders {
+    /// Gives every untagged mesh a collider too, for levels made without the convention
+    pub fn everything(collider: LevelCollider) -> Self {
+        LevelColliders {
+            untagged: Some(collider),
+        }
+    }
+}
+
+#[derive(Bundle, Default)]
+pub struct GltfLevelBundle {
+    pub scene: SceneBundle,
+    pub colliders: LevelColliders,
+}
+
+impl GltfLevelBundle {
+    /// Level from a scene handle, e.g. `asset_server.load("levels/test.glb#Scene0")`
+    pub fn new(scene: Handle<Scene>) -> Self {
+        GltfLevelBundle {
+            scene: SceneBundle {
+                scene,
+                ..Default::default()
+            },
+            colliders: Default::default(),
+        }
+    }
+
+    pub fn with_colliders(mut self, colliders: LevelColliders) -> Self {
+        self.colliders = colliders;
+        self
+    }
+
+    pub fn with_transform(mut self, transform: Transform) -> Self {
+        self.scene.transform = transform;
+        self
+    }
+}
+
+/// Mesh waiting for its asset to load before getting a collider
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PendingLevelCollider(pub LevelCollider);
+
+#[derive(Deserialize)]
+struct ColliderExtras {
+    collider: Option<String>,
+}
+
+/// Collider requested by a node and whether its mesh is only there to shape it
+fn parse_tag(
+    name: Option<&Name>,
+    extras: Option<&GltfExtras>,
+) -> Option<(Option<LevelCollider>, bool)> {
+    let extras = extras
+        .and_then(|extras| serde_json::from_str::<ColliderExtras>(&extras.value).ok())
+        .and_then(|extras| extras.collider);
+    if let Some(collider) = extras {
+        return match collider.to_lowercase().as_str() {
+            "trimesh" | "tri_mesh" => Some((Some(LevelCollider::TriMesh), false)),
+            "convex" => Some((Some(LevelCollider::Convex), false)),
+            "none" => Some((None, false)),
+            other => {
+                warn!("Unknown level collider \"{other}\" in glTF extras");
+                None
+            }
+        };
+    }
+
+    let name = name?.as_str();
+    match () {
+        _ if name.ends_with("-colonly") => Some((Some(LevelCollider::TriMesh), true)),
+        _ if name.ends_with("-convcol") => Some((Some(LevelCollider::Convex), false)),
+        _ if name.ends_with("-col") => Some((Some(LevelCollider::TriMesh), false)),
+        _ => None,
+    }
+}
+
+fn tag_level_meshes(
+    mut commands: Commands,
+    meshes: Query<Entity, Added<Handle<Mesh>>>,
+    nodes: Query<(Option<&Name>, Option<&GltfExtras>, Option<&Parent>)>,
+    levels: Query<&LevelColliders>,
+) {
+    for entity in meshes.iter() {
+        // glTF primitives are children of the node holding the name and extras
+        let mut tag = None;
+        let mut level = None;
+        let mut current = Some(entity);
+        while let Some(node) = current {
+            if let Ok(colliders) = levels.get(node) {
+                level = Some(colliders);
+                break;
+            }
+            let Ok((name, extras, parent)) = nodes.get(node) else {
+                break;
+            };
+            tag = tag.or_else(|| parse_tag(name, extras));
+            current = parent.map(|parent| parent.get());
+        }
+
+        let Some(level) = level else { continue };
+        let (collider, hidden) = tag.unwrap_or((level.untagged, false));
+
+        let mut mesh = commands.entity(entity);
+        if let Some(collider) = collider {
+            mesh.insert(PendingLevelCollider(collider));
+        }
+        if hidden {
+            mesh.insert(Visibility { is_visible: false });
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct SpawnExtras {
+    player_spawn: Option<String>,
+}
+
+fn tag_player_spawns(
+    mut commands: Commands,
+    nodes: Query<(Entity, &GltfExtras), Added<GltfExtras>>,
+) {
+    for (entity, extras) in nodes.iter() {
+        let extras = serde_json::from_str::<SpawnExtras>(&extras.value).ok();
+        let Some(name) = extras.and_then(|extras| extras.player_spawn) else {
+            continue;
+        };
+
+        let mut node = commands.entity(entity);
+        node.insert(PlayerSpawn).insert(RespawnPoint);
+        // The node name is only there for the modeling tool
+        match name.is_empty() {
+            true => node.remove::<Name>(),
+            false => node.insert(Name::new(name)),
+        };
+    }
+}
+
+fn generate_level_colliders(
+    mut commands: Commands,
+    pending: Query<(Entity, &PendingLevelCollider, &Handle<Mesh>)>,
+    meshes: Res<Assets<Mesh>>,
+) {
+    for (entity, PendingLevelCollider(collider), handle) in pending.iter() {
+        let Some(mesh) = meshes.get(handle) else {
+            continue;
+        };
+
+        let mut target = commands.entity(entity);
+        target.remove::<PendingLevelCollider>();
+        match Collider::from_bevy_mesh(mesh, &collider.shape()) {
+            Some(shape) => {
+                target.insert(shape);
+            }
+            None => warn!("Failed to generate a {collider:?} collider for level mesh {entity:?}"),
+        }
+    }
+}
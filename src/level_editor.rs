@@ -0,0 +1,345 @@
+use crate::prelude::*;
+use bevy::{ecs::system::Command, prelude::*};
+use bevy_rapier3d::prelude::*;
+use leafwing_input_manager::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// How far placing rays reach before objects are put in mid-air
+const PLACE_DISTANCE: f32 = 200.0;
+/// Distance along the cursor ray of objects placed in mid-air
+const PLACE_FALLBACK: f32 = 10.0;
+/// Grid the translation snaps to, in meters
+const SNAP_STEP: f32 = 0.5;
+/// Increment the rotation snaps to, in degrees
+const SNAP_ANGLE: f32 = 15.0;
+/// Radians turned and scale exponent per pixel dragged
+const ROTATE_SPEED: f32 = 0.01;
+const SCALE_SPEED: f32 = 0.005;
+
+/// Edits the level in the `Editor` camera state
+///
+/// `CameraAction::Select` picks, dragging the selection moves, turns or scales it depending on the
+/// `GizmoMode`, and `EditorAction`s place primitives under the cursor or delete the selection.
+/// With the `GizmoPlugin`, the handles it shows drag the selection along a single axis instead.
+/// Edits go straight to the entities, so rapier picks them up and the result is playable right
+/// away. Characters stay put while editing.
+pub struct LevelEditorPlugin {
+    map: InputMap<EditorAction>,
+}
+
+impl Default for LevelEditorPlugin {
+    fn default() -> Self {
+        LevelEditorPlugin {
+            map: InputMap::new([
+                (KeyCode::Key1, EditorAction::PlaceBox),
+                (KeyCode::Key2, EditorAction::PlaceRamp),
+                (KeyCode::Key3, EditorAction::PlacePlatform),
+                (KeyCode::Key4, EditorAction::PlaceWall),
+                (KeyCode::W, EditorAction::Translate),
+                (KeyCode::E, EditorAction::Rotate),
+                (KeyCode::R, EditorAction::Scale),
+                (KeyCode::Delete, EditorAction::Delete),
+                (KeyCode::Back, EditorAction::Delete),
+                (KeyCode::LControl, EditorAction::Snap),
+                (KeyCode::LAlt, EditorAction::Vertical),
+            ]),
+        }
+    }
+}
+
+impl LevelEditorPlugin {
+    pub fn with_map(mut self, map: InputMap<EditorAction>) -> Self {
+        self.map = map;
+        self
+    }
+}
+
+impl Plugin for LevelEditorPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GizmoMode>()
+            .insert_resource(self.map.clone())
+            .init_resource::<ActionState<EditorAction>>()
+            .add_plugin(InputManagerPlugin::<EditorAction>::default())
+            .add_system_set(SystemSet::on_enter(CameraState::Editor).with_system(enter_editor))
+            .add_system_set(SystemSet::on_exit(CameraState::Editor).with_system(exit_editor))
+            .add_system_set(
+                SystemSet::on_update(CameraState::Editor)
+                    .with_system(switch_gizmo_mode)
+                    .with_system(place_primitives.after(select_under_cursor))
+                    .with_system(drag_selection.after(select_under_cursor))
+                    .with_system(delete_selection.after(drag_selection)),
+            );
+    }
+}
+
+#[derive(Actionlike, Clone, Debug, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum EditorAction {
+    PlaceBox,
+    PlaceRamp,
+    PlacePlatform,
+    PlaceWall,
+    Translate,
+    Rotate,
+    Scale,
+    Delete,
+    /// Held to snap to the grid and angle increments
+    Snap,
+    /// Held to move up and down instead of along the ground
+    Vertical,
+}
+
+/// Object placed with the level editor
+#[derive(Component, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct EditorPlaced;
+
+/// Spawns an object and selects it
+struct PlacePrimitive(SpawnObject);
+
+impl Command for PlacePrimitive {
+    fn write(self, world: &mut World) {
+        let entity = self.0.spawn(world);
+        world.entity_mut(entity).insert(EditorPlaced);
+        world.resource_mut::<EditorSelection>().0 = Some(entity);
+    }
+}
+
+/// Selection being dragged since the select button went down
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct EditorDrag {
+    entity: Entity,
+    start: Transform,
+    cursor: Vec2,
+    /// Unsnapped translation, and where the cursor last hit the drag plane
+    position: Vec3,
+    last_hit: Option<(bool, Vec3)>,
+}
+
+fn enter_editor(context: Option<ResMut<State<InputContext>>>) {
+    let Some(mut context) = context else { return };
+    if *context.current() != InputContext::Editor {
+        if let Err(error) = context.push(InputContext::Editor) {
+            warn!("Failed to switch input context: {error:?}");
+        }
+    }
+}
+
+fn exit_editor(context: Option<ResMut<State<InputContext>>>) {
+    let Some(mut context) = context else { return };
+    if *context.current() == InputContext::Editor {
+        if let Err(error) = context.pop() {
+            warn!("Failed to switch input context: {error:?}");
+        }
+    }
+}
+
+fn switch_gizmo_mode(
+    cameras: Query<&ActionState<CameraAction>, With<ControlledCamera>>,
+    actions: Res<ActionState<EditorAction>>,
+    mut mode: ResMut<GizmoMode>,
+    mut messages: EventWriter<HudMessage>,
+) {
+    // The same keys fly the camera while it moves
+    if cameras
+        .iter()
+        .any(|camera| camera.pressed(CameraAction::MoveTrigger))
+    {
+        return;
+    }
+
+    let next = if actions.just_pressed(EditorAction::Translate) {
+        GizmoMode::Translate
+    } else if actions.just_pressed(EditorAction::Rotate) {
+        GizmoMode::Rotate
+    } else if actions.just_pressed(EditorAction::Scale) {
+        GizmoMode::Scale
+    } else {
+        return;
+    };
+    *mode = next;
+    messages.send(HudMessage::new(format!("{next:?}")));
+}
+
+fn place_primitives(
+    mut commands: Commands,
+    cameras: Query<(&Camera, &GlobalTransform), With<ControlledCamera>>,
+    actions: Res<ActionState<EditorAction>>,
+    windows: Res<Windows>,
+    rapier: Res<RapierContext>,
+) {
+    let shape = if actions.just_pressed(EditorAction::PlaceBox) {
+        PrimitiveShape::Box
+    } else if actions.just_pressed(EditorAction::PlaceRamp) {
+        PrimitiveShape::Ramp
+    } else if actions.just_pressed(EditorAction::PlacePlatform) {
+        PrimitiveShape::Box
+    } else if actions.just_pressed(EditorAction::PlaceWall) {
+        PrimitiveShape::Wall
+    } else {
+        return;
+    };
+    let Some(window) = windows.get_primary() else {
+        return;
+    };
+    let Ok((camera, camera_transform)) = cameras.get_single() else {
+        return;
+    };
+    let Some((origin, direction)) = cursor_ray(camera, camera_transform, window) else {
+        return;
+    };
+
+    let mut object = SpawnObject::in_front_of(shape, camera_transform, 0.0);
+    if actions.just_pressed(EditorAction::PlacePlatform) {
+        object = object
+            .with_size(Vec3::new(4.0, 0.5, 4.0))
+            .with_body(RigidBody::KinematicPositionBased)
+            .with_color(Color::rgb(0.5, 0.5, 0.6));
+    }
+
+    // On whatever is under the cursor, resting on it rather than halfway inside
+    let filter = QueryFilter::default().exclude_sensors();
+    let hit = rapier.cast_ray_and_get_normal(origin, direction, PLACE_DISTANCE, true, filter);
+    let (point, normal) = match hit {
+        Some((_, hit)) => (hit.point, hit.normal),
+        None => (origin + direction * PLACE_FALLBACK, Vec3::Y),
+    };
+    let lift = match shape {
+        // Ramps start at the height they're placed at
+        PrimitiveShape::Ramp => 0.0,
+        _ => object.size.y / 2.0,
+    };
+
+    let mut translation = point + normal * lift;
+    if actions.pressed(EditorAction::Snap) {
+        translation = snap(translation, SNAP_STEP);
+    }
+    object.transform.translation = translation;
+    commands.add(PlacePrimitive(object));
+}
+
+#[allow(clippy::too_many_arguments)]
+fn drag_selection(
+    mut objects: Query<(&mut Transform, Option<&mut Velocity>)>,
+    cameras: Query<(&Camera, &GlobalTransform, &ActionState<CameraAction>), With<ControlledCamera>>,
+    actions: Res<ActionState<EditorAction>>,
+    mode: Res<GizmoMode>,
+    selection: Res<EditorSelection>,
+    grab: Option<Res<GizmoGrab>>,
+    windows: Res<Windows>,
+    mut drag: Local<Option<EditorDrag>>,
+) {
+    let Some(window) = windows.get_primary() else {
+        return;
+    };
+    let Ok((camera, camera_transform, camera_actions)) = cameras.get_single() else {
+        return;
+    };
+    let Some(cursor) = window.cursor_position() else {
+        return;
+    };
+
+    // Gizmo handles drag the selection themselves
+    if grab.map_or(false, |grab| grab.grabbing()) {
+        *drag = None;
+        return;
+    }
+
+    // Selecting picks what's under the cursor first, so a press on an object grabs it
+    if camera_actions.just_pressed(CameraAction::Select) {
+        *drag = selection.0.and_then(|entity| {
+            let (transform, _) = objects.get(entity).ok()?;
+            Some(EditorDrag {
+                entity,
+                start: *transform,
+                cursor,
+                position: transform.translation,
+                last_hit: None,
+            })
+        });
+    }
+    if !camera_actions.pressed(CameraAction::Select) {
+        *drag = None;
+        return;
+    }
+
+    let Some(current) = drag.as_mut() else { return };
+    let Ok((mut transform, velocity)) = objects.get_mut(current.entity) else {
+        *drag = None;
+        return;
+    };
+    let snapping = actions.pressed(EditorAction::Snap);
+    let dragged = cursor.x - current.cursor.x;
+
+    // Without handles, turning is around the vertical axis and scaling is uniform
+    match *mode {
+        GizmoMode::Translate => {
+            let Some((origin, direction)) = cursor_ray(camera, camera_transform, window) else {
+                return;
+            };
+            let vertical = actions.pressed(EditorAction::Vertical);
+            let normal = match vertical {
+                true => (camera_transform.back() * Vec3::new(1.0, 0.0, 1.0)).normalize_or_zero(),
+                false => Vec3::Y,
+            };
+            let Some(hit) = ray_plane(origin, direction, current.position, normal) else {
+                return;
+            };
+
+            // Switching planes starts over from where the cursor is
+            if let Some((was_vertical, last)) = current.last_hit {
+                if was_vertical == vertical {
+                    current.position += hit - last;
+                }
+            }
+            current.last_hit = Some((vertical, hit));
+
+            transform.translation = match snapping {
+                true => snap(current.position, SNAP_STEP),
+                false => current.position,
+            };
+        }
+        GizmoMode::Rotate => {
+            let mut angle = -dragged * ROTATE_SPEED;
+            if snapping {
+                let step = SNAP_ANGLE.to_radians();
+                angle = (angle / step).round() * step;
+            }
+            transform.rotation = Quat::from_rotation_y(angle) * current.start.rotation;
+        }
+        GizmoMode::Scale => {
+            let mut factor = (dragged * SCALE_SPEED).exp();
+            if snapping {
+                factor = ((factor * 4.0).round() / 4.0).max(0.25);
+            }
+            transform.scale = (current.start.scale * factor).max(Vec3::splat(0.05));
+        }
+    }
+
+    // Dynamic bodies would keep the speed they had before being grabbed
+    if let Some(mut velocity) = velocity {
+        *velocity = Velocity::zero();
+    }
+}
+
+fn delete_selection(
+    mut commands: Commands,
+    characters: Query<(), With<CharacterMovementController>>,
+    actions: Res<ActionState<EditorAction>>,
+    mut selection: ResMut<EditorSelection>,
+    mut messages: EventWriter<HudMessage>,
+) {
+    if !actions.just_pressed(EditorAction::Delete) {
+        return;
+    }
+    let Some(entity) = selection.0 else { return };
+
+    if characters.contains(entity) {
+        messages.send(HudMessage::new("Characters can't be deleted"));
+        return;
+    }
+    commands.entity(entity).despawn_recursive();
+    selection.0 = None;
+}
+
+fn snap(position: Vec3, step: f32) -> Vec3 {
+    (position / step).round() * step
+}
@@ -0,0 +1,346 @@
+use crate::prelude::*;
+use bevy::{asset::LoadState, prelude::*, scene::SceneInstance, utils::HashMap};
+use bevy_rapier3d::prelude::*;
+
+/// Switches levels on `LoadLevel`, going through `LevelState::Loading` while the next one streams
+///
+/// The current level is despawned, the simulation paused and a loading screen shown until the new
+/// level is spawned. The simulation is left as it was before loading once the level is in, and
+/// stays paused after a failed load, as there's nothing to stand on. Players, characters without
+/// a `CharacterInputDriver`, are then moved to its spawn point. Names are looked up among the levels added with `with_level`, falling back to
+/// `levels/<name>.level.ron`, and paths not ending in `.level.ron` are loaded as glTF scenes.
+/// Needs the `LevelPlugin`.
+#[derive(Default)]
+pub struct LevelFlowPlugin {
+    levels: HashMap<String, String>,
+}
+
+impl LevelFlowPlugin {
+    /// Level loaded by name, e.g. `with_level("castle", "levels/castle.glb#Scene0")`
+    pub fn with_level(mut self, name: impl Into<String>, path: impl Into<String>) -> Self {
+        self.levels.insert(name.into(), path.into());
+        self
+    }
+}
+
+impl Plugin for LevelFlowPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_state(LevelState::Empty)
+            .insert_resource(LevelPaths(self.levels.clone()))
+            .init_resource::<CurrentLevel>()
+            .init_resource::<SelectedSpawn>()
+            .add_event::<LoadLevel>()
+            .add_event::<LevelLoaded>()
+            .add_system(start_loading_levels)
+            .add_system_set(
+                SystemSet::on_enter(LevelState::Loading).with_system(show_loading_screen),
+            )
+            .add_system_set(
+                SystemSet::on_update(LevelState::Loading)
+                    .with_system(finish_loading_levels.after(instantiate_levels)),
+            )
+            .add_system_set(
+                SystemSet::on_exit(LevelState::Loading).with_system(hide_loading_screen),
+            )
+            .add_system_set(SystemSet::on_enter(LevelState::Playing).with_system(place_players));
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LevelState {
+    /// No level was loaded through `LoadLevel` yet
+    #[default]
+    Empty,
+    Loading,
+    Playing,
+}
+
+/// Replaces the current level with the one of the given name
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LoadLevel {
+    pub name: String,
+    /// Player spawn to enter at, an unnamed one if not set
+    pub spawn: Option<String>,
+}
+
+impl LoadLevel {
+    pub fn new(name: impl Into<String>) -> Self {
+        LoadLevel {
+            name: name.into(),
+            spawn: None,
+        }
+    }
+
+    pub fn with_spawn(mut self, spawn: impl Into<String>) -> Self {
+        self.spawn = Some(spawn.into());
+        self
+    }
+}
+
+/// A level requested by `LoadLevel` is spawned and players entered it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LevelLoaded {
+    pub name: String,
+    pub root: Entity,
+}
+
+#[derive(Resource, Debug, Default, Clone, PartialEq, Eq)]
+struct LevelPaths(HashMap<String, String>);
+
+impl LevelPaths {
+    fn get(&self, name: &str) -> String {
+        match self.0.get(name) {
+            Some(path) => path.clone(),
+            None => format!("levels/{name}.level.ron"),
+        }
+    }
+}
+
+/// Level loaded through `LoadLevel`
+#[derive(Resource, Debug, Default, Clone)]
+pub struct CurrentLevel {
+    name: Option<String>,
+    root: Option<Entity>,
+    handle: Option<HandleUntyped>,
+    spawned: bool,
+}
+
+impl CurrentLevel {
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    pub fn root(&self) -> Option<Entity> {
+        self.root
+    }
+}
+
+/// Simulation state from before the first of a run of loads, until a level is in
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq)]
+struct LoadingHold {
+    paused: Option<bool>,
+    physics: bool,
+}
+
+/// Root of the loading screen UI
+#[derive(Component, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct LoadingScreen;
+
+fn start_loading_levels(
+    mut commands: Commands,
+    mut requests: EventReader<LoadLevel>,
+    mut current: ResMut<CurrentLevel>,
+    mut selected: ResMut<SelectedSpawn>,
+    mut state: ResMut<State<LevelState>>,
+    paths: Res<LevelPaths>,
+    asset_server: Res<AssetServer>,
+) {
+    let Some(request) = requests.iter().last() else {
+        return;
+    };
+
+    if let Some(root) = current.root.take() {
+        commands.entity(root).despawn_recursive();
+    }
+
+    let path = paths.get(&request.name);
+    info!("Loading level {} from {path}", request.name);
+    let (root, handle) = match path.ends_with(".level.ron") {
+        true => {
+            let handle: Handle<LevelDescription> = asset_server.load(&path);
+            let root = commands.spawn(RonLevelBundle::new(handle.clone())).id();
+            (root, handle.clone_untyped())
+        }
+        false => {
+            let handle: Handle<Scene> = asset_server.load(&path);
+            let root = commands.spawn(GltfLevelBundle::new(handle.clone())).id();
+            (root, handle.clone_untyped())
+        }
+    };
+
+    selected.0 = request.spawn.clone();
+    *current = CurrentLevel {
+        name: Some(request.name.clone()),
+        root: Some(root),
+        handle: Some(handle),
+        spawned: false,
+    };
+
+    // Already loading levels just wait for the new one instead
+    if *state.current() != LevelState::Loading {
+        if let Err(error) = state.set(LevelState::Loading) {
+            warn!("Failed to start loading level: {error:?}");
+        }
+    }
+}
+
+fn show_loading_screen(
+    mut commands: Commands,
+    current: Res<CurrentLevel>,
+    paused: Option<ResMut<Paused>>,
+    mut rapier: ResMut<RapierConfiguration>,
+    hold: Option<Res<LoadingHold>>,
+    asset_server: Res<AssetServer>,
+) {
+    // After a failed load the simulation is still held, what came before it is restored later
+    if hold.is_none() {
+        commands.insert_resource(LoadingHold {
+            paused: paused.as_deref().map(|paused| paused.0),
+            physics: rapier.physics_pipeline_active,
+        });
+    }
+
+    // Characters would fall through the level that isn't there yet
+    match paused {
+        Some(mut paused) => paused.0 = true,
+        None => rapier.physics_pipeline_active = false,
+    }
+
+    let style = TextStyle {
+        font: asset_server.load(HUD_FONT),
+        font_size: 32.0,
+        color: Color::WHITE,
+    };
+    let text = format!("Loading {}...", current.name().unwrap_or_default());
+
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                position_type: PositionType::Absolute,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..Default::default()
+            },
+            background_color: Color::BLACK.into(),
+            ..Default::default()
+        })
+        .insert(LoadingScreen)
+        .with_children(|screen| {
+            screen.spawn(TextBundle::from_section(text, style));
+        });
+}
+
+fn finish_loading_levels(
+    mut commands: Commands,
+    mut spawned: EventReader<LevelSpawned>,
+    mut current: ResMut<CurrentLevel>,
+    mut state: ResMut<State<LevelState>>,
+    mut loaded: EventWriter<LevelLoaded>,
+    instances: Query<&SceneInstance>,
+    scene_spawner: Res<SceneSpawner>,
+    asset_server: Res<AssetServer>,
+) {
+    let (Some(name), Some(root)) = (current.name.clone(), current.root) else {
+        return;
+    };
+
+    // Spawn points only get their global transform at the end of the frame they appear in
+    if current.spawned {
+        info!("Level {name} loaded");
+        loaded.send(LevelLoaded { name, root });
+        if let Err(error) = state.set(LevelState::Playing) {
+            warn!("Failed to enter loaded level: {error:?}");
+        }
+        return;
+    }
+
+    let failed = current.handle.as_ref().map_or(false, |handle| {
+        asset_server.get_load_state(handle.id) == LoadState::Failed
+    });
+    if failed {
+        error!("Failed to load level {name}");
+        commands.entity(root).despawn_recursive();
+        current.root = None;
+        if let Err(error) = state.set(LevelState::Empty) {
+            warn!("Failed to leave loading level: {error:?}");
+        }
+        return;
+    }
+
+    let ron_spawned = spawned.iter().any(|event| event.root == root);
+    let scene_spawned = instances.get(root).map_or(false, |instance| {
+        scene_spawner.instance_is_ready(**instance)
+    });
+    current.spawned = ron_spawned || scene_spawned;
+}
+
+fn hide_loading_screen(
+    mut commands: Commands,
+    screens: Query<Entity, With<LoadingScreen>>,
+    paused: Option<ResMut<Paused>>,
+    mut rapier: ResMut<RapierConfiguration>,
+    hold: Option<Res<LoadingHold>>,
+    current: Res<CurrentLevel>,
+) {
+    for screen in screens.iter() {
+        commands.entity(screen).despawn_recursive();
+    }
+
+    // Players stay where they are rather than falling into the void of a failed level
+    if !current.spawned {
+        return;
+    }
+    let Some(hold) = hold else { return };
+    commands.remove_resource::<LoadingHold>();
+
+    match (paused, hold.paused) {
+        (Some(mut paused), Some(was_paused)) => paused.0 = was_paused,
+        _ => rapier.physics_pipeline_active = hold.physics,
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn place_players(
+    mut commands: Commands,
+    mut players: Query<
+        (
+            Entity,
+            &mut Transform,
+            &mut Velocity,
+            &mut CharacterState,
+            &mut CharacterMovementController,
+            &mut CharacterInterpolation,
+            Option<&mut KinematicCharacterController>,
+        ),
+        Without<CharacterInputDriver>,
+    >,
+    spawns: PlayerSpawns,
+    current: Res<CurrentLevel>,
+) {
+    let Some(root) = current.root else { return };
+    let spawn = spawns.find_in(root, spawns.selected_name());
+    let Some(placed) = spawn.and_then(|spawn| spawns.transform(spawn)) else {
+        warn!(
+            "Level {} has no player spawn",
+            current.name().unwrap_or_default()
+        );
+        return;
+    };
+
+    for components in players.iter_mut() {
+        let (
+            entity,
+            mut transform,
+            mut velocity,
+            mut state,
+            mut character,
+            mut interpolation,
+            controller,
+        ) = components;
+
+        *transform = placed;
+        *velocity = Velocity::zero();
+        *state = CharacterState::Idle;
+        character.reset();
+        interpolation.reset(placed.translation);
+        if let Some(mut controller) = controller {
+            controller.translation = None;
+        }
+        // Checkpoints of the previous level are gone
+        commands
+            .entity(entity)
+            .remove::<RespawnTarget>()
+            .remove::<CheckpointProgress>();
+    }
+}
@@ -0,0 +1,400 @@
+use crate::prelude::*;
+use bevy::{ecs::system::Command, prelude::*, reflect::TypeUuid};
+use bevy_rapier3d::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Level loaded from a `.level.ron` file, instantiated below the entity holding its `LevelAsset`
+///
+/// Rotations are in degrees around `X`, `Y` and `Z`, applied yaw first.
+#[derive(TypeUuid, Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[uuid = "971f9569-21ba-456d-ab5f-6516b2833d7c"]
+#[serde(default)]
+pub struct LevelDescription {
+    /// Where characters respawn
+    pub spawn_points: Vec<Vec3>,
+    /// Where players enter the level, also respawn points
+    pub player_spawns: Vec<LevelSpawn>,
+    pub geometry: Vec<LevelPrimitive>,
+    pub platforms: Vec<LevelPlatform>,
+    pub triggers: Vec<LevelTrigger>,
+    pub lights: Vec<LevelLight>,
+    pub skybox: Option<Skybox>,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LevelBody {
+    #[default]
+    Fixed,
+    Dynamic,
+    Kinematic,
+}
+
+impl From<LevelBody> for RigidBody {
+    fn from(body: LevelBody) -> Self {
+        match body {
+            LevelBody::Fixed => RigidBody::Fixed,
+            LevelBody::Dynamic => RigidBody::Dynamic,
+            LevelBody::Kinematic => RigidBody::KinematicPositionBased,
+        }
+    }
+}
+
+/// Box, sphere, ramp or wall, see `SpawnObject`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LevelPrimitive {
+    pub shape: PrimitiveShape,
+    pub translation: Vec3,
+    pub rotation: Vec3,
+    /// Default size of the shape if not given
+    pub size: Option<Vec3>,
+    /// Default body of the shape if not given
+    pub body: Option<LevelBody>,
+    /// Linear RGB
+    pub color: [f32; 3],
+    pub surface: Option<Surface>,
+}
+
+impl Default for LevelPrimitive {
+    fn default() -> Self {
+        LevelPrimitive {
+            shape: PrimitiveShape::Box,
+            translation: Vec3::ZERO,
+            rotation: Vec3::ZERO,
+            size: None,
+            body: None,
+            color: [0.8, 0.7, 0.6],
+            surface: None,
+        }
+    }
+}
+
+/// Kinematic box following waypoints relative to `translation`, spinning if given a velocity
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LevelPlatform {
+    pub translation: Vec3,
+    pub size: Vec3,
+    pub waypoints: Vec<Waypoint>,
+    pub mode: PlatformMode,
+    pub speed: f32,
+    /// Radians per second around the axis
+    pub angular_velocity: Vec3,
+    pub color: [f32; 3],
+}
+
+impl Default for LevelPlatform {
+    fn default() -> Self {
+        LevelPlatform {
+            translation: Vec3::ZERO,
+            size: Vec3::new(4.0, 0.5, 4.0),
+            waypoints: Vec::new(),
+            mode: PlatformMode::default(),
+            speed: 3.0,
+            angular_velocity: Vec3::ZERO,
+            color: [0.5, 0.5, 0.6],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum LevelTriggerKind {
+    Trigger(TriggerFilter),
+    Kill,
+    /// Water of the given density
+    Water(f32),
+}
+
+/// Invisible box volume
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LevelTrigger {
+    /// Set as the `Name` of the volume, for finding it from code
+    pub name: Option<String>,
+    pub kind: LevelTriggerKind,
+    pub translation: Vec3,
+    pub rotation: Vec3,
+    pub size: Vec3,
+}
+
+impl Default for LevelTrigger {
+    fn default() -> Self {
+        LevelTrigger {
+            name: None,
+            kind: LevelTriggerKind::Trigger(TriggerFilter::default()),
+            translation: Vec3::ZERO,
+            rotation: Vec3::ZERO,
+            size: Vec3::ONE,
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LevelLightKind {
+    #[default]
+    Point,
+    Spot,
+    /// Sun, only `rotation` matters and `intensity` is the illuminance in lux
+    Directional,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LevelLight {
+    pub kind: LevelLightKind,
+    pub translation: Vec3,
+    pub rotation: Vec3,
+    /// Linear RGB
+    pub color: [f32; 3],
+    /// Lumens, or lux for directional lights
+    pub intensity: f32,
+    /// Meters, ignored by directional lights
+    pub range: f32,
+    pub shadows: bool,
+}
+
+impl Default for LevelLight {
+    fn default() -> Self {
+        LevelLight {
+            kind: LevelLightKind::Point,
+            translation: Vec3::ZERO,
+            rotation: Vec3::ZERO,
+            color: [1.0, 1.0, 1.0],
+            intensity: 800.0,
+            range: 20.0,
+            shadows: false,
+        }
+    }
+}
+
+fn transform(translation: Vec3, rotation: Vec3) -> Transform {
+    let [x, y, z] = rotation.to_array().map(f32::to_radians);
+    let rotation = Quat::from_euler(EulerRot::YXZ, y, x, z);
+    Transform::from_translation(translation).with_rotation(rotation)
+}
+
+fn color([red, green, blue]: [f32; 3]) -> Color {
+    Color::rgb_linear(red, green, blue)
+}
+
+/// Root of a level instantiated from a `.level.ron` file, respawned whenever the file changes
+#[derive(Component, Debug, Default, Clone, PartialEq, Eq, Deref, DerefMut)]
+pub struct LevelAsset(pub Handle<LevelDescription>);
+
+#[derive(Bundle, Default)]
+pub struct RonLevelBundle {
+    pub level: LevelAsset,
+    pub transform: TransformBundle,
+    pub visibility: VisibilityBundle,
+}
+
+impl RonLevelBundle {
+    /// Level from a handle, e.g. `asset_server.load("levels/test.level.ron")`
+    pub fn new(level: Handle<LevelDescription>) -> Self {
+        RonLevelBundle {
+            level: LevelAsset(level),
+            ..Default::default()
+        }
+    }
+
+    pub fn with_transform(mut self, transform: Transform) -> Self {
+        self.transform = TransformBundle::from(transform);
+        self
+    }
+}
+
+/// A level finished spawning below `root`, also sent when it was spawned again
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LevelSpawned {
+    pub root: Entity,
+}
+
+/// Spawns the contents of a level as children of `root`
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpawnLevel {
+    pub root: Entity,
+    pub description: LevelDescription,
+}
+
+impl Command for SpawnLevel {
+    fn write(self, world: &mut World) {
+        let level = self.description;
+        let mut children = Vec::new();
+
+        for point in level.spawn_points {
+            let transform = Transform::from_translation(point);
+            children.push(
+                world
+                    .spawn((RespawnPoint, TransformBundle::from(transform)))
+                    .id(),
+            );
+        }
+
+        for spawn in level.player_spawns {
+            let transform = TransformBundle::from(spawn.transform());
+            let mut entity = world.spawn((PlayerSpawn, RespawnPoint, transform));
+            if let Some(name) = spawn.name {
+                entity.insert(Name::new(name));
+            }
+            children.push(entity.id());
+        }
+
+        for primitive in level.geometry {
+            let mut object = SpawnObject::new(
+                primitive.shape,
+                transform(primitive.translation, primitive.rotation),
+            )
+            .with_color(color(primitive.color));
+            if let Some(size) = primitive.size {
+                object = object.with_size(size);
+            }
+            if let Some(body) = primitive.body {
+                object = object.with_body(body.into());
+            }
+
+            let entity = object.spawn(world);
+            if let Some(surface) = primitive.surface {
+                world.entity_mut(entity).insert(surface);
+            }
+            children.push(entity);
+        }
+
+        for platform in level.platforms {
+            let entity = SpawnObject::new(
+                PrimitiveShape::Box,
+                Transform::from_translation(platform.translation),
+            )
+            .with_size(platform.size)
+            .with_body(RigidBody::KinematicPositionBased)
+            .with_color(color(platform.color))
+            .spawn(world);
+
+            let mut entity = world.entity_mut(entity);
+            if !platform.waypoints.is_empty() {
+                let moving = MovingPlatform::new(platform.waypoints)
+                    .with_mode(platform.mode)
+                    .with_speed(platform.speed);
+                entity.insert(moving);
+            }
+            if platform.angular_velocity != Vec3::ZERO {
+                entity.insert(RotatingPlatform::new(platform.angular_velocity));
+            }
+            children.push(entity.id());
+        }
+
+        for trigger in level.triggers {
+            let half = trigger.size / 2.0;
+            let mut entity = world.spawn((
+                Collider::cuboid(half.x, half.y, half.z),
+                TransformBundle::from(transform(trigger.translation, trigger.rotation)),
+            ));
+            match trigger.kind {
+                LevelTriggerKind::Trigger(filter) => entity.insert(TriggerVolume::new(filter)),
+                LevelTriggerKind::Kill => entity.insert(KillVolume),
+                LevelTriggerKind::Water(density) => entity.insert(WaterVolume { density }),
+            };
+            if let Some(name) = trigger.name {
+                entity.insert(Name::new(name));
+            }
+            children.push(entity.id());
+        }
+
+        for light in level.lights {
+            let transform = transform(light.translation, light.rotation);
+            let color = color(light.color);
+            let entity = match light.kind {
+                LevelLightKind::Point => world.spawn(PointLightBundle {
+                    point_light: PointLight {
+                        color,
+                        intensity: light.intensity,
+                        range: light.range,
+                        shadows_enabled: light.shadows,
+                        ..Default::default()
+                    },
+                    transform,
+                    ..Default::default()
+                }),
+                LevelLightKind::Spot => world.spawn(SpotLightBundle {
+                    spot_light: SpotLight {
+                        color,
+                        intensity: light.intensity,
+                        range: light.range,
+                        shadows_enabled: light.shadows,
+                        ..Default::default()
+                    },
+                    transform,
+                    ..Default::default()
+                }),
+                LevelLightKind::Directional => world.spawn(DirectionalLightBundle {
+                    directional_light: DirectionalLight {
+                        color,
+                        illuminance: light.intensity,
+                        shadows_enabled: light.shadows,
+                        ..Default::default()
+                    },
+                    transform,
+                    ..Default::default()
+                }),
+            };
+            children.push(entity.id());
+        }
+
+        if let Some(skybox) = level.skybox {
+            children.push(world.spawn(skybox).id());
+        }
+
+        // The root may have been despawned while the command was queued
+        let Some(mut root) = world.get_entity_mut(self.root) else {
+            for child in children {
+                world.despawn(child);
+            }
+            return;
+        };
+        root.push_children(&children);
+
+        if let Some(mut events) = world.get_resource_mut::<Events<LevelSpawned>>() {
+            events.send(LevelSpawned { root: self.root });
+        }
+    }
+}
+
+/// Spawns levels once loaded and again whenever their file changes
+pub fn instantiate_levels(
+    mut commands: Commands,
+    mut events: EventReader<AssetEvent<LevelDescription>>,
+    roots: Query<(
+        Entity,
+        &LevelAsset,
+        ChangeTrackers<LevelAsset>,
+        Option<&Children>,
+    )>,
+    mut reloading: EventWriter<LevelReloading>,
+    levels: Res<Assets<LevelDescription>>,
+) {
+    let loaded: Vec<_> = events
+        .iter()
+        .filter_map(|event| match event {
+            AssetEvent::Created { handle } | AssetEvent::Modified { handle } => Some(handle),
+            AssetEvent::Removed { .. } => None,
+        })
+        .collect();
+
+    for (root, level, tracker, children) in roots.iter() {
+        if !tracker.is_changed() && !loaded.contains(&&level.0) {
+            continue;
+        }
+        let Some(description) = levels.get(&level.0) else {
+            continue;
+        };
+
+        if children.is_some() {
+            info!("Reloading level {root:?}");
+            reloading.send(LevelReloading { root });
+        }
+        commands.entity(root).despawn_descendants();
+        commands.add(SpawnLevel {
+            root,
+            description: description.clone(),
+        });
+    }
+}
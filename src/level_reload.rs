@@ -0,0 +1,157 @@
+use crate::prelude::*;
+use bevy::{
+    prelude::*,
+    scene::{InstanceId, SceneInstance},
+    utils::HashMap,
+};
+use bevy_rapier3d::prelude::*;
+
+/// Seconds characters are held for at most, in case a level never finishes loading
+const RELOAD_TIMEOUT: f32 = 10.0;
+
+/// Respawns glTF levels whose scene changed and holds characters still while levels reload
+///
+/// RON levels are respawned by the `LevelPlugin` itself. Characters aren't part of levels, but
+/// would fall through the gap while the new level loads, so they keep their transform, state and
+/// velocity from before the reload until every reloading level is back. Needs the asset server
+/// to watch for changes.
+pub struct LevelReloadPlugin;
+
+impl Plugin for LevelReloadPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<HeldCharacters>()
+            .add_event::<LevelReloading>()
+            .add_system(reload_gltf_levels)
+            .add_system(
+                hold_characters
+                    .after(reload_gltf_levels)
+                    .after(instantiate_levels),
+            );
+    }
+}
+
+/// A loaded level is about to be replaced because its file changed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LevelReloading {
+    pub root: Entity,
+}
+
+/// Characters as they were when the reload started, and the levels still reloading
+#[derive(Resource, Debug, Default)]
+pub struct HeldCharacters {
+    /// Scene instance of glTF levels before the reload, none for RON levels
+    pending: HashMap<Entity, Option<InstanceId>>,
+    characters: Vec<(Entity, Transform, CharacterState, Velocity)>,
+    elapsed: f32,
+}
+
+impl HeldCharacters {
+    pub fn holding(&self) -> bool {
+        !self.pending.is_empty()
+    }
+}
+
+fn reload_gltf_levels(
+    mut events: EventReader<AssetEvent<Scene>>,
+    mut roots: Query<(Entity, &mut Handle<Scene>), With<LevelColliders>>,
+    mut reloading: EventWriter<LevelReloading>,
+) {
+    for event in events.iter() {
+        let AssetEvent::Modified { handle } = event else {
+            continue;
+        };
+        for (root, mut scene) in roots.iter_mut() {
+            if *scene == *handle {
+                // The scene spawner replaces the instance of changed handles
+                scene.set_changed();
+                reloading.send(LevelReloading { root });
+            }
+        }
+    }
+}
+
+#[allow(clippy::type_complexity)]
+pub fn hold_characters(
+    mut reloading: EventReader<LevelReloading>,
+    mut spawned: EventReader<LevelSpawned>,
+    mut characters: Query<(
+        Entity,
+        &mut Transform,
+        &mut CharacterState,
+        &mut Velocity,
+        &mut CharacterMovementController,
+        &mut CharacterInterpolation,
+        Option<&mut KinematicCharacterController>,
+    )>,
+    instances: Query<&SceneInstance>,
+    scene_spawner: Res<SceneSpawner>,
+    mut held: ResMut<HeldCharacters>,
+    time: Res<Time>,
+) {
+    for LevelReloading { root } in reloading.iter() {
+        if !held.holding() {
+            held.elapsed = 0.0;
+            held.characters = characters
+                .iter()
+                .map(|(entity, transform, state, velocity, ..)| {
+                    (entity, *transform, *state, *velocity)
+                })
+                .collect();
+        }
+        let instance = instances.get(*root).ok().map(|instance| **instance);
+        held.pending.insert(*root, instance);
+    }
+
+    for LevelSpawned { root } in spawned.iter() {
+        held.pending.remove(root);
+    }
+    // glTF levels are back once their new scene instance is spawned
+    held.pending.retain(|root, previous| {
+        let Some(previous) = previous else {
+            return true;
+        };
+        match instances.get(*root) {
+            Ok(instance) => **instance == *previous || !scene_spawner.instance_is_ready(**instance),
+            Err(_) => false,
+        }
+    });
+
+    held.elapsed += time.delta_seconds();
+    if held.elapsed > RELOAD_TIMEOUT && held.holding() {
+        warn!("Levels took too long to reload, letting go of characters");
+        held.pending.clear();
+    }
+
+    for &(entity, saved_transform, saved_state, saved_velocity) in held.characters.iter() {
+        let Ok(components) = characters.get_mut(entity) else {
+            continue;
+        };
+        let (
+            _,
+            mut transform,
+            mut state,
+            mut velocity,
+            mut character,
+            mut interpolation,
+            controller,
+        ) = components;
+
+        *transform = saved_transform;
+        *state = saved_state;
+        *velocity = saved_velocity;
+        interpolation.reset(saved_transform.translation);
+        // Gravity would pile up while held in the air
+        character.reset();
+        if let Some(mut controller) = controller {
+            controller.translation = None;
+        }
+    }
+
+    if !held.holding() && !held.characters.is_empty() {
+        info!(
+            "Levels reloaded, released {} characters",
+            held.characters.len()
+        );
+        held.characters.clear();
+    }
+}
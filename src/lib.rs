@@ -2,8 +2,80 @@
 pub mod character_state;
 
 /// Player & editor cameras
+#[cfg(feature = "debug_camera")]
 pub mod camera;
 
+/// Switching between cameras
+#[cfg(feature = "debug_camera")]
+pub mod active;
+
+/// Camera rigs attached to characters
+#[cfg(feature = "debug_camera")]
+pub mod rig;
+
+/// Blending between camera states
+#[cfg(feature = "debug_camera")]
+pub mod blend;
+
+/// Camera settings
+#[cfg(feature = "debug_camera")]
+pub mod camera_config;
+
+/// Keyframed camera flights
+#[cfg(feature = "debug_camera")]
+pub mod camera_path;
+
+/// Cursor grabbing for mouse look
+#[cfg(feature = "debug_camera")]
+pub mod cursor;
+
+/// Editor selection and framing
+#[cfg(feature = "debug_camera")]
+pub mod editor;
+
+/// Building levels in the editor camera state
+#[cfg(feature = "debug_camera")]
+pub mod level_editor;
+
+/// Transform handles on the editor selection
+#[cfg(feature = "debug_camera")]
+pub mod gizmo;
+
+/// Field of view effects
+#[cfg(feature = "debug_camera")]
+pub mod fov;
+
+/// Mouse look processing
+#[cfg(feature = "debug_camera")]
+pub mod look;
+
+/// Top-down map of the surroundings
+#[cfg(feature = "debug_camera")]
+pub mod minimap;
+
+/// Secondary cameras shown in a screen corner
+#[cfg(feature = "debug_camera")]
+pub mod pip;
+
+/// Spectating without moving the player
+#[cfg(feature = "debug_camera")]
+pub mod spectator;
+
+/// Split-screen local multiplayer
+#[cfg(feature = "debug_camera")]
+pub mod split_screen;
+
+/// Keeping entities in view
+#[cfg(feature = "debug_camera")]
+pub mod tracking;
+
+/// Trauma based camera shake
+#[cfg(feature = "debug_camera")]
+pub mod shake;
+
+/// On-screen debug notices
+pub mod hud;
+
 /// Develper runtime console
 pub mod console;
 
@@ -13,11 +85,229 @@ pub mod debugger;
 /// Character controller
 pub mod character;
 
+/// Character abilities
+pub mod ability;
+
+/// Accessibility input assists
+pub mod accessibility;
+
+/// Input devices assigned to characters
+pub mod device;
+
+/// Buffered action presses
+pub mod buffer;
+
+/// Double taps and other input gestures
+pub mod gesture;
+
+/// Character input sources
+pub mod input;
+
+/// Input enabled by what has focus
+pub mod input_context;
+
+/// Player input preferences
+pub mod input_settings;
+
+/// Pressed inputs overlay
+pub mod input_display;
+
+/// On-screen touch controls
+#[cfg(feature = "touch")]
+pub mod touch;
+
+/// Changing bindings at runtime
+pub mod rebind;
+
+/// Bindings loaded from and saved to a file
+pub mod bindings;
+
+/// Action manifests for external configurators
+pub mod manifest;
+
+/// Character ground detection
+pub mod ground;
+
+/// Controller vibration requests, without a backend to play them
+pub mod rumble;
+
+/// Kill planes and respawning
+pub mod respawn;
+
+/// Out-of-bounds recovery
+pub mod bounds;
+
+/// Trigger volumes
+pub mod trigger;
+
+/// Checkpoints characters respawn at
+pub mod checkpoint;
+
+/// Interacting with objects
+pub mod interact;
+
+/// Picking up and carrying objects
+pub mod carry;
+
+/// Pressure plates and buttons
+pub mod switch;
+
+/// Hinged and sliding doors
+pub mod door;
+
+/// Moving platforms and elevators
+pub mod platform;
+
+/// Platforms solid only from above
+pub mod one_way;
+
+/// Destructible boxes
+pub mod breakable;
+
+/// Explosions and knockback
+pub mod explosion;
+
+/// Pushing and pulling boxes
+pub mod push;
+
+/// Hitscan weapons
+pub mod hitscan;
+
+/// Ragdolling characters
+pub mod ragdoll;
+
+/// Spawning primitive physics objects
+pub mod spawner;
+
+/// Surface types of colliders
+pub mod surface;
+
+/// Water volumes and buoyancy
+pub mod water;
+
+/// Level loading
+pub mod level;
+
+/// RON level files
+pub mod level_format;
+
+/// Where players enter levels
+pub mod player_spawn;
+
+/// Respawning levels when their files change
+pub mod level_reload;
+
+/// Switching between levels
+pub mod level_flow;
+
+/// Sun, ambient light and sky color presets
+pub mod lighting;
+
+/// Pausing the simulation
+pub mod pause;
+
+/// Skies drawn around the camera
+pub mod skybox;
+
+/// Controller test gym
+pub mod playground;
+
+/// Procedural level generation
+pub mod procgen;
+
+/// Slow motion and fast forward
+pub mod time_scale;
+
+/// Asset loaders
+pub mod assets;
+
 /// Boxxed prelude
 pub mod prelude {
+    pub use crate::ability::*;
+    pub use crate::accessibility::*;
+    #[cfg(feature = "debug_camera")]
+    pub use crate::active::*;
+    pub use crate::assets::*;
+    pub use crate::bindings::*;
+    #[cfg(feature = "debug_camera")]
+    pub use crate::blend::*;
+    pub use crate::bounds::*;
+    pub use crate::breakable::*;
+    pub use crate::buffer::*;
+    #[cfg(feature = "debug_camera")]
     pub use crate::camera::*;
+    #[cfg(feature = "debug_camera")]
+    pub use crate::camera_config::*;
+    #[cfg(feature = "debug_camera")]
+    pub use crate::camera_path::*;
+    pub use crate::carry::*;
     pub use crate::character::*;
     pub use crate::character_state::*;
+    pub use crate::checkpoint::*;
     pub use crate::console::*;
+    #[cfg(feature = "debug_camera")]
+    pub use crate::cursor::*;
     pub use crate::debugger::*;
+    pub use crate::device::*;
+    pub use crate::door::*;
+    #[cfg(feature = "debug_camera")]
+    pub use crate::editor::*;
+    pub use crate::explosion::*;
+    #[cfg(feature = "debug_camera")]
+    pub use crate::fov::*;
+    pub use crate::gesture::*;
+    #[cfg(feature = "debug_camera")]
+    pub use crate::gizmo::*;
+    pub use crate::ground::*;
+    pub use crate::hitscan::*;
+    pub use crate::hud::*;
+    pub use crate::input::*;
+    pub use crate::input_context::*;
+    pub use crate::input_display::*;
+    pub use crate::input_settings::*;
+    pub use crate::interact::*;
+    pub use crate::level::*;
+    #[cfg(feature = "debug_camera")]
+    pub use crate::level_editor::*;
+    pub use crate::level_flow::*;
+    pub use crate::level_format::*;
+    pub use crate::level_reload::*;
+    pub use crate::lighting::*;
+    #[cfg(feature = "debug_camera")]
+    pub use crate::look::*;
+    pub use crate::manifest::*;
+    #[cfg(feature = "debug_camera")]
+    pub use crate::minimap::*;
+    pub use crate::one_way::*;
+    pub use crate::pause::*;
+    #[cfg(feature = "debug_camera")]
+    pub use crate::pip::*;
+    pub use crate::platform::*;
+    pub use crate::player_spawn::*;
+    pub use crate::playground::*;
+    pub use crate::procgen::*;
+    pub use crate::push::*;
+    pub use crate::ragdoll::*;
+    pub use crate::rebind::*;
+    pub use crate::respawn::*;
+    #[cfg(feature = "debug_camera")]
+    pub use crate::rig::*;
+    pub use crate::rumble::*;
+    #[cfg(feature = "debug_camera")]
+    pub use crate::shake::*;
+    pub use crate::skybox::*;
+    pub use crate::spawner::*;
+    #[cfg(feature = "debug_camera")]
+    pub use crate::spectator::*;
+    #[cfg(feature = "debug_camera")]
+    pub use crate::split_screen::*;
+    pub use crate::surface::*;
+    pub use crate::switch::*;
+    pub use crate::time_scale::*;
+    #[cfg(feature = "touch")]
+    pub use crate::touch::*;
+    #[cfg(feature = "debug_camera")]
+    pub use crate::tracking::*;
+    pub use crate::trigger::*;
+    pub use crate::water::*;
 }
@@ -0,0 +1,268 @@
+use crate::prelude::*;
+use bevy::{prelude::*, reflect::TypeUuid};
+use serde::{Deserialize, Serialize};
+
+/// Keeps a sun, the ambient light and the clear color in line with the `LightingPreset` resource
+///
+/// Bevy 0.9 has no distance fog, so presets don't carry any.
+pub struct LightingPlugin {
+    preset: LightingPreset,
+    path: Option<String>,
+}
+
+impl Default for LightingPlugin {
+    fn default() -> Self {
+        LightingPlugin {
+            preset: LightingPreset::noon(),
+            path: None,
+        }
+    }
+}
+
+impl LightingPlugin {
+    /// Preset applied on startup, available as a resource
+    pub fn with_preset(mut self, preset: LightingPreset) -> Self {
+        self.preset = preset;
+        self
+    }
+
+    /// Load the preset from a `.lighting.ron` asset, reapplied whenever it changes
+    pub fn with_preset_asset(mut self, path: impl Into<String>) -> Self {
+        self.path = Some(path.into());
+        self
+    }
+}
+
+impl Plugin for LightingPlugin {
+    fn build(&self, app: &mut App) {
+        if let Some(path) = &self.path {
+            app.insert_resource(LightingPresetPath(path.clone()))
+                .add_startup_system(load_lighting_preset);
+        }
+
+        app.add_asset::<LightingPreset>()
+            .add_asset_loader(RonAssetLoader::<LightingPreset>::new(&["lighting.ron"]))
+            .insert_resource(self.preset.clone())
+            .add_system(apply_lighting_asset)
+            .add_system(apply_lighting.after(apply_lighting_asset));
+    }
+}
+
+/// Directional light lighting the whole level
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SunSettings {
+    /// Linear RGB
+    pub color: [f32; 3],
+    /// Lux
+    pub illuminance: f32,
+    /// Degrees above the horizon
+    pub elevation: f32,
+    /// Degrees clockwise from `-Z` seen from above
+    pub azimuth: f32,
+    pub shadows: bool,
+    /// Half the size of the area around the origin that gets shadows
+    pub shadow_extent: f32,
+}
+
+impl Default for SunSettings {
+    fn default() -> Self {
+        SunSettings {
+            color: [1.0, 1.0, 1.0],
+            illuminance: 32_000.0,
+            elevation: 60.0,
+            azimuth: 30.0,
+            shadows: true,
+            shadow_extent: 100.0,
+        }
+    }
+}
+
+impl SunSettings {
+    fn transform(&self) -> Transform {
+        let yaw = -self.azimuth.to_radians();
+        let pitch = -self.elevation.to_radians();
+        Transform::from_rotation(Quat::from_euler(EulerRot::YXZ, yaw, pitch, 0.0))
+    }
+
+    fn light(&self) -> DirectionalLight {
+        let extent = self.shadow_extent;
+        DirectionalLight {
+            color: color(self.color),
+            illuminance: self.illuminance,
+            shadows_enabled: self.shadows,
+            shadow_projection: OrthographicProjection {
+                left: -extent,
+                right: extent,
+                bottom: -extent,
+                top: extent,
+                near: -extent * 2.0,
+                far: extent * 2.0,
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+}
+
+/// Lighting of the world, also loadable as a `.lighting.ron` asset
+#[derive(Resource, TypeUuid, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[uuid = "4d0f6b2e-83a9-4c15-9e7d-1b52c6a8f3e0"]
+#[serde(default)]
+pub struct LightingPreset {
+    /// No sun if not set, e.g. indoors
+    pub sun: Option<SunSettings>,
+    /// Linear RGB
+    pub ambient_color: [f32; 3],
+    pub ambient_brightness: f32,
+    /// Linear RGB of the background, left as it is if not set
+    pub clear_color: Option<[f32; 3]>,
+}
+
+impl Default for LightingPreset {
+    fn default() -> Self {
+        LightingPreset::noon()
+    }
+}
+
+impl LightingPreset {
+    /// High white sun under a blue sky
+    pub fn noon() -> Self {
+        LightingPreset {
+            sun: Some(SunSettings::default()),
+            ambient_color: [0.8, 0.9, 1.0],
+            ambient_brightness: 0.3,
+            clear_color: Some([0.35, 0.6, 0.95]),
+        }
+    }
+
+    /// Low orange sun with long shadows
+    pub fn dusk() -> Self {
+        LightingPreset {
+            sun: Some(SunSettings {
+                color: [1.0, 0.55, 0.3],
+                illuminance: 8_000.0,
+                elevation: 8.0,
+                azimuth: 250.0,
+                ..Default::default()
+            }),
+            ambient_color: [0.5, 0.45, 0.7],
+            ambient_brightness: 0.12,
+            clear_color: Some([0.6, 0.3, 0.25]),
+        }
+    }
+
+    /// No sun, the level's own lights and a flat ambient term
+    pub fn indoor() -> Self {
+        LightingPreset {
+            sun: None,
+            ambient_color: [1.0, 0.95, 0.85],
+            ambient_brightness: 0.2,
+            clear_color: Some([0.02, 0.02, 0.02]),
+        }
+    }
+
+    /// Built-in preset by name, `noon`, `dusk` or `indoor`
+    pub fn builtin(name: &str) -> Option<Self> {
+        match name {
+            "noon" => Some(LightingPreset::noon()),
+            "dusk" => Some(LightingPreset::dusk()),
+            "indoor" => Some(LightingPreset::indoor()),
+            _ => None,
+        }
+    }
+}
+
+/// Directional light spawned for the sun of the `LightingPreset`
+#[derive(Component, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Sun;
+
+/// Handle to the preset asset the lighting plugin keeps in sync
+#[derive(Resource, Deref)]
+pub struct LightingPresetHandle(pub Handle<LightingPreset>);
+
+#[derive(Resource)]
+struct LightingPresetPath(String);
+
+fn load_lighting_preset(
+    mut commands: Commands,
+    path: Res<LightingPresetPath>,
+    asset_server: Res<AssetServer>,
+) {
+    commands.insert_resource(LightingPresetHandle(asset_server.load(path.0.as_str())));
+}
+
+fn apply_lighting_asset(
+    mut events: EventReader<AssetEvent<LightingPreset>>,
+    mut preset: ResMut<LightingPreset>,
+    assets: Res<Assets<LightingPreset>>,
+    handle: Option<Res<LightingPresetHandle>>,
+) {
+    let Some(handle) = handle else { return };
+
+    for event in events.iter() {
+        let loaded = match event {
+            AssetEvent::Created { handle } | AssetEvent::Modified { handle } => handle,
+            AssetEvent::Removed { .. } => continue,
+        };
+
+        if *loaded != handle.0 {
+            continue;
+        }
+
+        let Some(loaded) = assets.get(loaded) else {
+            continue;
+        };
+        info!("Applying lighting preset");
+        *preset = loaded.clone();
+    }
+}
+
+pub fn apply_lighting(
+    mut commands: Commands,
+    mut suns: Query<(Entity, &mut DirectionalLight, &mut Transform), With<Sun>>,
+    mut ambient: ResMut<AmbientLight>,
+    mut clear_color: ResMut<ClearColor>,
+    preset: Res<LightingPreset>,
+) {
+    if !preset.is_changed() {
+        return;
+    }
+
+    ambient.color = color(preset.ambient_color);
+    ambient.brightness = preset.ambient_brightness;
+    if let Some(clear) = preset.clear_color {
+        clear_color.0 = color(clear);
+    }
+
+    let Some(sun) = preset.sun else {
+        for (entity, ..) in suns.iter() {
+            commands.entity(entity).despawn_recursive();
+        }
+        return;
+    };
+
+    match suns.get_single_mut() {
+        Ok((_, mut light, mut transform)) => {
+            *light = sun.light();
+            *transform = sun.transform();
+        }
+        Err(_) => {
+            for (entity, ..) in suns.iter() {
+                commands.entity(entity).despawn_recursive();
+            }
+            commands.spawn((
+                Sun,
+                DirectionalLightBundle {
+                    directional_light: sun.light(),
+                    transform: sun.transform(),
+                    ..Default::default()
+                },
+            ));
+        }
+    }
+}
+
+fn color([red, green, blue]: [f32; 3]) -> Color {
+    Color::rgb_linear(red, green, blue)
+}
@@ -0,0 +1,78 @@
+use crate::prelude::*;
+use bevy::prelude::*;
+use leafwing_input_manager::prelude::*;
+use std::collections::VecDeque;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum LookSmoothing {
+    /// Mouse motion as reported by the device
+    #[default]
+    Raw,
+    /// Average over the last frames
+    Average(usize),
+    /// Exponential approach to the mouse velocity, per second
+    Exponential(f32),
+}
+
+/// Mouse motion the rotation systems turn the camera by, after smoothing and `InputSettings`
+#[derive(Component, Debug, Default, Clone, PartialEq)]
+pub struct LookInput {
+    pub smoothing: LookSmoothing,
+    history: VecDeque<Vec2>,
+    velocity: Vec2,
+    delta: Vec2,
+}
+
+impl LookInput {
+    pub fn new(smoothing: LookSmoothing) -> Self {
+        LookInput {
+            smoothing,
+            ..Default::default()
+        }
+    }
+
+    /// Motion of this frame, in pixels
+    pub fn delta(&self) -> Vec2 {
+        self.delta
+    }
+}
+
+pub fn update_look_input(
+    mut q: Query<(&mut LookInput, &ActionState<CameraAction>)>,
+    settings: Res<InputSettings>,
+    time: Res<Time>,
+) {
+    let dt = time.delta_seconds();
+
+    for (mut look, actions) in q.iter_mut() {
+        let mouse = actions
+            .axis_pair(CameraAction::Pan)
+            .map(|pan| pan.xy())
+            .unwrap_or_default();
+
+        // Sticks set a turn rate rather than a distance, and point up where the mouse points down
+        let stick = actions
+            .axis_pair(CameraAction::Look)
+            .map(|stick| settings.look_stick.apply(stick.xy()))
+            .unwrap_or_default();
+        let stick = Vec2::new(stick.x, -stick.y) * settings.stick_speed * dt;
+
+        let raw = settings.look(mouse, stick);
+
+        look.delta = match look.smoothing {
+            LookSmoothing::Raw => raw,
+            LookSmoothing::Average(frames) => {
+                look.history.push_front(raw);
+                look.history.truncate(frames.max(1));
+                look.history.iter().sum::<Vec2>() / look.history.len() as f32
+            }
+            // Smoothed as a velocity, so the feel doesn't depend on the frame rate
+            LookSmoothing::Exponential(rate) if dt > 0.0 => {
+                let t = 1.0 - (-rate * dt).exp();
+                look.velocity = look.velocity.lerp(raw / dt, t);
+                look.velocity * dt
+            }
+            LookSmoothing::Exponential(_) => Vec2::ZERO,
+        };
+    }
+}
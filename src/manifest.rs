@@ -0,0 +1,145 @@
+use crate::prelude::*;
+use bevy::prelude::*;
+use leafwing_input_manager::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::{fmt::Debug, path::Path};
+
+/// Every action with its category and default bindings, as JSON for external tools
+///
+/// Controller configurators like Steam Input or a web page read the exported manifest and hand
+/// back a remapped one, which `apply_to` turns into bindings again.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ActionManifest {
+    pub actions: Vec<ManifestAction>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ManifestAction {
+    /// Variant name, unique within its category
+    pub name: String,
+    /// Action set the action belongs to, named like the field of `Bindings`
+    pub category: String,
+    pub kind: ActionKind,
+    pub bindings: Vec<BindingInput>,
+}
+
+/// How an action is read, so tools offer fitting inputs
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ActionKind {
+    Button,
+    /// Read through an axis pair, like sticks or mouse motion
+    DualAxis,
+}
+
+impl ActionKind {
+    fn of(bindings: &[BindingInput]) -> Self {
+        let dual_axis = bindings.iter().any(|input| {
+            matches!(
+                input,
+                BindingInput::LeftStick
+                    | BindingInput::RightStick
+                    | BindingInput::MouseMotion
+                    | BindingInput::MouseWheel
+            )
+        });
+
+        match dual_axis {
+            true => ActionKind::DualAxis,
+            false => ActionKind::Button,
+        }
+    }
+}
+
+fn export<A: Actionlike + Debug>(
+    category: &'static str,
+    bindings: &[(A, BindingInput)],
+) -> impl Iterator<Item = ManifestAction> + '_ {
+    A::variants().map(move |action| {
+        let inputs: Vec<BindingInput> = bindings
+            .iter()
+            .filter(|(bound, _)| *bound == action)
+            .map(|(_, input)| input.clone())
+            .collect();
+
+        ManifestAction {
+            name: format!("{action:?}"),
+            category: category.to_string(),
+            kind: ActionKind::of(&inputs),
+            bindings: inputs,
+        }
+    })
+}
+
+impl ActionManifest {
+    pub fn from_bindings(bindings: &Bindings) -> Self {
+        let actions = export("character_movement", &bindings.character_movement)
+            .chain(export("character_actions", &bindings.character_actions));
+        #[cfg(feature = "debug_camera")]
+        let actions = actions
+            .chain(export("camera_actions", &bindings.camera_actions))
+            .chain(export("camera_movement", &bindings.camera_movement));
+
+        ActionManifest {
+            actions: actions.collect(),
+        }
+    }
+
+    /// Replaces the bindings of every action listed, those missing keep their bindings
+    pub fn apply_to(&self, bindings: &mut Bindings) {
+        self.import("character_movement", &mut bindings.character_movement);
+        self.import("character_actions", &mut bindings.character_actions);
+        #[cfg(feature = "debug_camera")]
+        {
+            self.import("camera_actions", &mut bindings.camera_actions);
+            self.import("camera_movement", &mut bindings.camera_movement);
+        }
+    }
+
+    fn import<A: Actionlike + Debug>(&self, category: &str, bindings: &mut Vec<(A, BindingInput)>) {
+        for entry in self
+            .actions
+            .iter()
+            .filter(|entry| entry.category == category)
+        {
+            let named = |action: &A| format!("{action:?}") == entry.name;
+            let Some(action) = A::variants().find(named) else {
+                warn!("Unknown action {} in {category}", entry.name);
+                continue;
+            };
+
+            bindings.retain(|(bound, _)| *bound != action);
+            let inputs = entry
+                .bindings
+                .iter()
+                .map(|input| (action.clone(), input.clone()));
+            bindings.extend(inputs);
+        }
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, anyhow::Error> {
+        let json = std::fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&json)?)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), anyhow::Error> {
+        let json = serde_json::to_string_pretty(self)?;
+        std::fs::write(path, json)?;
+        Ok(())
+    }
+}
+
+/// Writes the manifest of the current `Bindings` to a file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExportActionManifest(pub String);
+
+pub fn export_action_manifest(
+    mut events: EventReader<ExportActionManifest>,
+    bindings: Res<Bindings>,
+) {
+    for ExportActionManifest(path) in events.iter() {
+        match ActionManifest::from_bindings(&bindings).save(path) {
+            Ok(()) => info!("Exported action manifest to {path}"),
+            Err(error) => error!("Failed to export action manifest to {path}: {error}"),
+        }
+    }
+}
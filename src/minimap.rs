@@ -0,0 +1,209 @@
+use crate::prelude::*;
+use bevy::{
+    prelude::*,
+    render::{
+        camera::{Projection, ScalingMode},
+        view::RenderLayers,
+    },
+};
+
+/// Layer only the minimap sees, markers and level geometry are drawn on it
+pub const MINIMAP_LAYER: u8 = 1;
+
+/// Top-down orthographic view of the surroundings of the player, shown in a screen corner
+///
+/// The overlay is set up by `DebugCameraPlugin` like any other `PictureInPicture`. Meshes of
+/// levels spawned through the `LevelPlugin` are put on `MINIMAP_LAYER` too, anything else stays
+/// off the map unless given that layer.
+#[derive(Default)]
+pub struct MinimapPlugin {
+    settings: MinimapSettings,
+}
+
+impl MinimapPlugin {
+    pub fn with_settings(mut self, settings: MinimapSettings) -> Self {
+        self.settings = settings;
+        self
+    }
+}
+
+impl Plugin for MinimapPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(self.settings.clone())
+            .add_event::<LevelSpawned>()
+            .add_startup_system(spawn_minimap)
+            .add_system(tag_level_geometry)
+            .add_system(mark_characters)
+            .add_system(spawn_minimap_markers)
+            .add_system(update_minimap);
+    }
+}
+
+#[derive(Resource, Debug, Clone)]
+pub struct MinimapSettings {
+    /// Half the distance covered from top to bottom, in meters
+    pub zoom: f32,
+    /// Height above the player the map is rendered from
+    pub height: f32,
+    /// What shows up on the map, by default only `MINIMAP_LAYER` with the level and the markers
+    pub layers: RenderLayers,
+    pub size: UVec2,
+    pub corner: ScreenCorner,
+}
+
+impl Default for MinimapSettings {
+    fn default() -> Self {
+        MinimapSettings {
+            zoom: 25.0,
+            height: 100.0,
+            layers: RenderLayers::layer(MINIMAP_LAYER),
+            size: UVec2::new(200, 200),
+            corner: ScreenCorner::BottomRight,
+        }
+    }
+}
+
+#[derive(Component, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Minimap;
+
+/// Shows a flat square above the entity on the minimap only
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct MinimapMarker {
+    pub color: Color,
+    /// Side length, in meters
+    pub size: f32,
+}
+
+impl Default for MinimapMarker {
+    fn default() -> Self {
+        MinimapMarker {
+            color: Color::YELLOW,
+            size: 2.0,
+        }
+    }
+}
+
+fn spawn_minimap(mut commands: Commands, settings: Res<MinimapSettings>) {
+    commands
+        .spawn(Camera3dBundle {
+            transform: Transform::from_xyz(0.0, settings.height, 0.0)
+                .looking_at(Vec3::ZERO, Vec3::NEG_Z),
+            projection: Projection::Orthographic(OrthographicProjection {
+                scale: settings.zoom,
+                scaling_mode: ScalingMode::FixedVertical(2.0),
+                ..Default::default()
+            }),
+            ..Default::default()
+        })
+        .insert(PictureInPicture {
+            size: settings.size,
+            corner: settings.corner,
+            ..Default::default()
+        })
+        .insert(settings.layers)
+        .insert(Minimap);
+}
+
+fn mark_characters(
+    mut commands: Commands,
+    characters: Query<Entity, (Added<CharacterMovementController>, Without<MinimapMarker>)>,
+) {
+    for entity in characters.iter() {
+        commands.entity(entity).insert(MinimapMarker::default());
+    }
+}
+
+fn spawn_minimap_markers(
+    mut commands: Commands,
+    markers: Query<(Entity, &MinimapMarker), Added<MinimapMarker>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    for (entity, marker) in markers.iter() {
+        let child = commands
+            .spawn(PbrBundle {
+                mesh: meshes.add(shape::Plane { size: marker.size }.into()),
+                material: materials.add(StandardMaterial {
+                    base_color: marker.color,
+                    unlit: true,
+                    ..Default::default()
+                }),
+                // Above the entity, so its own geometry doesn't cover it
+                transform: Transform::from_xyz(0.0, 2.0, 0.0),
+                ..Default::default()
+            })
+            .insert(RenderLayers::layer(MINIMAP_LAYER))
+            .id();
+        commands.entity(entity).add_child(child);
+    }
+}
+
+/// Level geometry is seen by both the regular cameras and the minimap
+fn level_layers() -> RenderLayers {
+    RenderLayers::from_layers(&[0, MINIMAP_LAYER])
+}
+
+#[allow(clippy::type_complexity)]
+fn tag_level_geometry(
+    mut commands: Commands,
+    mut spawned: EventReader<LevelSpawned>,
+    added: Query<Entity, (Added<Handle<Mesh>>, Without<RenderLayers>)>,
+    meshes: Query<(), (With<Handle<Mesh>>, Without<RenderLayers>)>,
+    parents: Query<&Parent>,
+    children: Query<&Children>,
+    gltf_levels: Query<(), With<LevelColliders>>,
+) {
+    // Levels from descriptions are complete once spawned
+    for LevelSpawned { root } in spawned.iter() {
+        let mut stack = vec![*root];
+        while let Some(entity) = stack.pop() {
+            if meshes.contains(entity) {
+                commands.entity(entity).insert(level_layers());
+            }
+            if let Ok(descendants) = children.get(entity) {
+                stack.extend(descendants.iter().copied());
+            }
+        }
+    }
+
+    // glTF scenes spawn their meshes whenever the asset is ready
+    for entity in added.iter() {
+        let mut current = Some(entity);
+        while let Some(node) = current {
+            if gltf_levels.contains(node) {
+                commands.entity(entity).insert(level_layers());
+                break;
+            }
+            current = parents.get(node).ok().map(|parent| parent.get());
+        }
+    }
+}
+
+/// Follows the player and picks up settings changes
+fn update_minimap(
+    mut minimaps: Query<(&mut Transform, &mut Projection, &mut RenderLayers), With<Minimap>>,
+    players: Query<
+        &GlobalTransform,
+        (
+            With<CharacterMovementController>,
+            Without<CharacterInputDriver>,
+        ),
+    >,
+    settings: Res<MinimapSettings>,
+) {
+    let player = players.iter().next().map(|player| player.translation());
+
+    for (mut transform, mut projection, mut layers) in minimaps.iter_mut() {
+        if let Some(player) = player {
+            transform.translation = Vec3::new(player.x, player.y + settings.height, player.z);
+        }
+
+        if !settings.is_changed() {
+            continue;
+        }
+        if let Projection::Orthographic(orthographic) = projection.as_mut() {
+            orthographic.scale = settings.zoom;
+        }
+        *layers = settings.layers;
+    }
+}
@@ -0,0 +1,121 @@
+use crate::prelude::*;
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+/// Collision group of one-way platforms, characters stop colliding with it while passing through
+pub const ONE_WAY_GROUP: Group = Group::GROUP_30;
+
+/// Lets characters jump up through `OneWayPlatform`s and land on them from above
+///
+/// The character controller doesn't run rapier physics hooks, so every character's filter
+/// groups drop `ONE_WAY_GROUP` while it moves up or is still inside a platform.
+pub struct OneWayPlatformPlugin;
+
+impl Plugin for OneWayPlatformPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(setup_one_way_platforms)
+            .add_system(pass_one_way_platforms.before(update_grounded));
+    }
+}
+
+/// Collider only solid from above
+///
+/// The platform joins `ONE_WAY_GROUP` on top of the groups it already has. Characters only pass
+/// through when those other groups aren't in their filters, so by default it's in that one alone.
+#[derive(Component, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct OneWayPlatform;
+
+/// Whether a character currently passes through one-way platforms
+#[derive(Component, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct OneWayPassage {
+    passing: bool,
+}
+
+impl OneWayPassage {
+    pub fn passing(&self) -> bool {
+        self.passing
+    }
+}
+
+fn setup_one_way_platforms(
+    mut commands: Commands,
+    q: Query<(Entity, Option<&CollisionGroups>), Added<OneWayPlatform>>,
+) {
+    for (entity, groups) in q.iter() {
+        let groups = match groups {
+            Some(groups) => {
+                CollisionGroups::new(groups.memberships | ONE_WAY_GROUP, groups.filters)
+            }
+            None => CollisionGroups::new(ONE_WAY_GROUP, Group::ALL),
+        };
+        commands.entity(entity).insert(groups);
+    }
+}
+
+#[allow(clippy::type_complexity)]
+pub fn pass_one_way_platforms(
+    mut characters: Query<(
+        Entity,
+        &mut OneWayPassage,
+        Option<&mut KinematicCharacterController>,
+        &CharacterInterpolation,
+        &CharacterState,
+        &Transform,
+        &Collider,
+        Option<&PlatformRider>,
+        Option<&mut CollisionGroups>,
+    )>,
+    rapier: Res<RapierContext>,
+) {
+    for components in characters.iter_mut() {
+        let (
+            entity,
+            mut passage,
+            controller,
+            interpolation,
+            state,
+            transform,
+            collider,
+            rider,
+            groups,
+        ) = components;
+
+        // Relative to the platform carrying the character, so rising elevators stay solid
+        let carried = rider.map_or(Vec3::ZERO, |rider| rider.velocity());
+        let rising = interpolation.velocity().y - carried.y > f32::EPSILON;
+
+        // Keep passing until the capsule is clear, or it would be pushed out of the platform
+        let mut inside = false;
+        let filter = QueryFilter::default()
+            .exclude_rigid_body(entity)
+            .groups(CollisionGroups::new(Group::ALL, ONE_WAY_GROUP));
+        rapier.intersections_with_shape(
+            transform.translation,
+            transform.rotation,
+            collider,
+            filter,
+            |_| {
+                inside = true;
+                false
+            },
+        );
+
+        let passing = rising || *state == CharacterState::Jump || inside;
+        passage.passing = passing;
+
+        let toggle = |groups: CollisionGroups| match passing {
+            true => CollisionGroups::new(groups.memberships, groups.filters - ONE_WAY_GROUP),
+            false => CollisionGroups::new(groups.memberships, groups.filters | ONE_WAY_GROUP),
+        };
+        if let Some(mut controller) = controller {
+            let filter_groups = controller.filter_groups.unwrap_or_default();
+            if toggle(filter_groups) != filter_groups {
+                controller.filter_groups = Some(toggle(filter_groups));
+            }
+        }
+        // Dynamic characters collide through their own groups
+        if let Some(mut groups) = groups.filter(|groups| toggle(**groups) != **groups) {
+            *groups = toggle(*groups);
+        }
+    }
+}
@@ -0,0 +1,143 @@
+use crate::prelude::*;
+use bevy::{ecs::schedule::ShouldRun, prelude::*};
+use bevy_rapier3d::prelude::*;
+use leafwing_input_manager::{plugin::InputManagerSystem, prelude::*};
+use serde::{Deserialize, Serialize};
+
+/// Pauses and resumes the simulation with the `Pause` action
+///
+/// Characters and physics stop stepping while paused and the `Menu` input context is pushed,
+/// so systems outside of `simulation_running`, like menus, stay responsive.
+/// `SimulationTime` stands still alongside, so timers reading it don't run out during a pause.
+pub struct PausePlugin {
+    map: InputMap<PauseAction>,
+}
+
+impl Default for PausePlugin {
+    fn default() -> Self {
+        PausePlugin {
+            map: InputMap::new([
+                (KeyCode::P, PauseAction::Pause),
+                (KeyCode::Pause, PauseAction::Pause),
+            ])
+            .insert(GamepadButtonType::Start, PauseAction::Pause)
+            .build(),
+        }
+    }
+}
+
+impl PausePlugin {
+    pub fn with_map(mut self, map: InputMap<PauseAction>) -> Self {
+        self.map = map;
+        self
+    }
+}
+
+impl Plugin for PausePlugin {
+    fn build(&self, app: &mut App) {
+        init_simulation_time(app);
+        app.init_resource::<Paused>()
+            .insert_resource(self.map.clone())
+            .init_resource::<ActionState<PauseAction>>()
+            .add_plugin(InputManagerPlugin::<PauseAction>::default())
+            .add_system_to_stage(
+                CoreStage::PreUpdate,
+                toggle_pause.after(InputManagerSystem::Update),
+            )
+            .add_system(apply_pause);
+    }
+}
+
+/// Global actions, read from a resource rather than per character
+#[derive(Actionlike, Clone, Debug, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PauseAction {
+    Pause,
+}
+
+/// Whether the simulation is frozen
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq, Eq, Deref, DerefMut)]
+pub struct Paused(pub bool);
+
+/// Run criterion of systems that advance the simulation, they stop while `Paused`
+pub fn simulation_running(paused: Option<Res<Paused>>) -> ShouldRun {
+    match paused.map_or(false, |paused| paused.0) {
+        true => ShouldRun::No,
+        false => ShouldRun::Yes,
+    }
+}
+
+/// Seconds the simulation has run for, unlike `Time` standing still while `Paused`
+///
+/// Timers of the simulation, like how long a state lasted or when an action was pressed, should
+/// read this so they don't run out during a pause.
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq)]
+pub struct SimulationTime {
+    elapsed: f32,
+    delta: f32,
+}
+
+impl SimulationTime {
+    pub fn elapsed_seconds(&self) -> f32 {
+        self.elapsed
+    }
+
+    /// Seconds the simulation advanced this frame, zero while paused
+    pub fn delta_seconds(&self) -> f32 {
+        self.delta
+    }
+}
+
+/// Adds `SimulationTime` for a plugin reading it, only the first call does anything
+pub fn init_simulation_time(app: &mut App) {
+    if app.world.contains_resource::<SimulationTime>() {
+        return;
+    }
+
+    app.init_resource::<SimulationTime>().add_system_to_stage(
+        CoreStage::First,
+        advance_simulation_time.after(bevy::time::TimeSystem),
+    );
+}
+
+fn advance_simulation_time(
+    mut simulation: ResMut<SimulationTime>,
+    time: Res<Time>,
+    paused: Option<Res<Paused>>,
+) {
+    simulation.delta = match paused.map_or(false, |paused| paused.0) {
+        true => 0.0,
+        false => time.delta_seconds(),
+    };
+    simulation.elapsed += simulation.delta;
+}
+
+fn toggle_pause(mut paused: ResMut<Paused>, actions: Res<ActionState<PauseAction>>) {
+    if actions.just_pressed(PauseAction::Pause) {
+        paused.0 = !paused.0;
+        info!("{}", if paused.0 { "Paused" } else { "Resumed" });
+    }
+}
+
+fn apply_pause(
+    paused: Res<Paused>,
+    mut rapier: ResMut<RapierConfiguration>,
+    context: Option<ResMut<State<InputContext>>>,
+) {
+    if !paused.is_changed() {
+        return;
+    }
+
+    rapier.physics_pipeline_active = !paused.0;
+
+    // Held keys are released and the world stops listening until resumed
+    let Some(mut context) = context else { return };
+    let current = *context.current();
+    let result = match paused.0 {
+        true if current != InputContext::Menu => context.push(InputContext::Menu),
+        false if current == InputContext::Menu => context.pop(),
+        _ => Ok(()),
+    };
+    if let Err(error) = result {
+        warn!("Failed to switch input context: {error:?}");
+    }
+}
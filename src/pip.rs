@@ -0,0 +1,130 @@
+use bevy::{
+    prelude::*,
+    render::{
+        camera::RenderTarget,
+        render_resource::{
+            Extent3d, TextureDescriptor, TextureDimension, TextureFormat, TextureUsages,
+        },
+    },
+};
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ScreenCorner {
+    TopLeft,
+    #[default]
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+/// Renders a secondary camera into an image shown in a corner of the screen
+///
+/// Insert on any camera, e.g. a rear view parented to the player or a fixed security camera.
+/// It leaves the primary window alone, so camera switching and input never pick it up.
+#[derive(Component, Debug, Clone, PartialEq)]
+pub struct PictureInPicture {
+    /// Pixels of the image and the overlay showing it
+    pub size: UVec2,
+    pub corner: ScreenCorner,
+    /// Distance to the window edges, in pixels
+    pub margin: f32,
+    image: Option<Handle<Image>>,
+}
+
+impl Default for PictureInPicture {
+    fn default() -> Self {
+        PictureInPicture {
+            size: UVec2::new(320, 180),
+            corner: Default::default(),
+            margin: 16.0,
+            image: None,
+        }
+    }
+}
+
+impl PictureInPicture {
+    /// Image the camera renders to, once set up
+    pub fn image(&self) -> Option<&Handle<Image>> {
+        self.image.as_ref()
+    }
+}
+
+/// UI node showing the image of a `PictureInPicture` camera
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PictureInPictureOverlay {
+    pub camera: Entity,
+}
+
+pub fn setup_picture_in_picture(
+    mut commands: Commands,
+    mut cameras: Query<(Entity, &mut Camera, &mut PictureInPicture), Added<PictureInPicture>>,
+    mut images: ResMut<Assets<Image>>,
+) {
+    for (entity, mut camera, mut pip) in cameras.iter_mut() {
+        let size = Extent3d {
+            width: pip.size.x,
+            height: pip.size.y,
+            ..Default::default()
+        };
+        let mut image = Image {
+            texture_descriptor: TextureDescriptor {
+                label: None,
+                size,
+                dimension: TextureDimension::D2,
+                format: TextureFormat::Bgra8UnormSrgb,
+                mip_level_count: 1,
+                sample_count: 1,
+                usage: TextureUsages::TEXTURE_BINDING
+                    | TextureUsages::COPY_DST
+                    | TextureUsages::RENDER_ATTACHMENT,
+            },
+            ..Default::default()
+        };
+        image.resize(size);
+        let image = images.add(image);
+
+        // Rendered before the main view, which shows it the same frame
+        camera.target = RenderTarget::Image(image.clone());
+        camera.priority = -1;
+        pip.image = Some(image.clone());
+
+        // The overlay would otherwise show up inside itself
+        commands
+            .entity(entity)
+            .insert(UiCameraConfig { show_ui: false });
+
+        let (margin, auto) = (Val::Px(pip.margin), Val::Auto);
+        let position = match pip.corner {
+            ScreenCorner::TopLeft => UiRect::new(margin, auto, margin, auto),
+            ScreenCorner::TopRight => UiRect::new(auto, margin, margin, auto),
+            ScreenCorner::BottomLeft => UiRect::new(margin, auto, auto, margin),
+            ScreenCorner::BottomRight => UiRect::new(auto, margin, auto, margin),
+        };
+
+        commands
+            .spawn(ImageBundle {
+                image: image.into(),
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    position,
+                    size: Size::new(Val::Px(pip.size.x as f32), Val::Px(pip.size.y as f32)),
+                    ..Default::default()
+                },
+                ..Default::default()
+            })
+            .insert(PictureInPictureOverlay { camera: entity });
+    }
+}
+
+/// Removes overlays whose camera is gone
+pub fn cleanup_picture_in_picture(
+    mut commands: Commands,
+    overlays: Query<(Entity, &PictureInPictureOverlay)>,
+    cameras: Query<(), With<PictureInPicture>>,
+) {
+    for (overlay, pip) in overlays.iter() {
+        if !cameras.contains(pip.camera) {
+            commands.entity(overlay).despawn_recursive();
+        }
+    }
+}
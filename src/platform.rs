@@ -0,0 +1,367 @@
+use crate::prelude::*;
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Moves `MovingPlatform`s along their waypoints, spins `RotatingPlatform`s and carries the
+/// characters standing on them
+///
+/// Triggered platforms follow `Activated` from the `SwitchPlugin`.
+pub struct PlatformPlugin;
+
+impl Plugin for PlatformPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(setup_platforms).add_system_set(
+            SystemSet::new()
+                .with_run_criteria(simulation_running)
+                .with_system(trigger_platforms)
+                .with_system(move_platforms.after(trigger_platforms))
+                .with_system(rotate_platforms)
+                .with_system(
+                    ride_platforms
+                        .after(move_platforms)
+                        .after(rotate_platforms)
+                        .after(update_grounded),
+                ),
+        );
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PlatformEasing {
+    Linear,
+    EaseIn,
+    EaseOut,
+    #[default]
+    EaseInOut,
+}
+
+impl PlatformEasing {
+    pub fn apply(&self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            PlatformEasing::Linear => t,
+            PlatformEasing::EaseIn => t * t * t,
+            PlatformEasing::EaseOut => 1.0 - (1.0 - t).powi(3),
+            PlatformEasing::EaseInOut => t * t * (3.0 - 2.0 * t),
+        }
+    }
+}
+
+/// How a platform picks its next waypoint
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PlatformMode {
+    /// Returns from the last waypoint to the first one
+    #[default]
+    Loop,
+    /// Turns around at either end
+    PingPong,
+    /// Heads for the last waypoint while a linked switch is on and back to the first once off
+    Triggered,
+}
+
+/// Stop on a platform path, relative to where the platform was spawned
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Waypoint {
+    pub position: Vec3,
+    /// Seconds the platform rests here
+    pub wait: f32,
+    /// Applied to the leg leaving this waypoint
+    pub easing: PlatformEasing,
+}
+
+impl Waypoint {
+    pub fn new(position: Vec3) -> Self {
+        Waypoint {
+            position,
+            ..Default::default()
+        }
+    }
+
+    pub fn with_wait(mut self, wait: f32) -> Self {
+        self.wait = wait;
+        self
+    }
+
+    pub fn with_easing(mut self, easing: PlatformEasing) -> Self {
+        self.easing = easing;
+        self
+    }
+}
+
+/// Kinematic platform or elevator following its waypoints, starting at the first one
+#[derive(Component, Debug, Clone, PartialEq)]
+pub struct MovingPlatform {
+    pub waypoints: Vec<Waypoint>,
+    pub mode: PlatformMode,
+    /// Travel speed between waypoints, before easing, in meters per second
+    pub speed: f32,
+    /// Set by linked switches, only used by `Triggered` platforms
+    pub active: bool,
+    /// Waypoint of the current leg the platform left and the one it heads for
+    from: usize,
+    to: usize,
+    /// From `from` at `0` to `to` at `1`
+    progress: f32,
+    wait: f32,
+    forward: bool,
+    origin: Vec3,
+    velocity: Vec3,
+}
+
+impl MovingPlatform {
+    pub fn new(waypoints: Vec<Waypoint>) -> Self {
+        MovingPlatform {
+            waypoints,
+            mode: PlatformMode::default(),
+            speed: 3.0,
+            active: false,
+            from: 0,
+            to: 0,
+            progress: 0.0,
+            wait: 0.0,
+            forward: true,
+            origin: Vec3::ZERO,
+            velocity: Vec3::ZERO,
+        }
+    }
+
+    pub fn with_mode(mut self, mode: PlatformMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    pub fn with_speed(mut self, speed: f32) -> Self {
+        self.speed = speed;
+        self
+    }
+
+    /// World space velocity over the last frame, in meters per second
+    pub fn velocity(&self) -> Vec3 {
+        self.velocity
+    }
+
+    /// Whether the platform rests at a waypoint
+    pub fn stopped(&self) -> bool {
+        self.from == self.to
+    }
+
+    /// Waypoint to head for after resting at `from`, if any
+    fn next(&mut self) -> Option<usize> {
+        let last = self
+            .waypoints
+            .len()
+            .checked_sub(1)
+            .filter(|&last| last > 0)?;
+        let from = self.from;
+
+        match self.mode {
+            PlatformMode::Loop => Some(if from < last { from + 1 } else { 0 }),
+            PlatformMode::PingPong => {
+                if (self.forward && from == last) || (!self.forward && from == 0) {
+                    self.forward = !self.forward;
+                }
+                Some(if self.forward { from + 1 } else { from - 1 })
+            }
+            PlatformMode::Triggered => match self.active {
+                true => (from < last).then_some(from + 1),
+                false => (from > 0).then_some(from - 1),
+            },
+        }
+    }
+}
+
+/// Kinematic platform spinning at a constant rate, may also be a `MovingPlatform`
+#[derive(Component, Debug, Default, Clone, Copy, PartialEq)]
+pub struct RotatingPlatform {
+    /// World space axis scaled by the speed, in radians per second
+    pub angular_velocity: Vec3,
+}
+
+impl RotatingPlatform {
+    pub fn new(angular_velocity: Vec3) -> Self {
+        RotatingPlatform { angular_velocity }
+    }
+
+    /// Turntable spinning around `Y` at `speed` radians per second
+    pub fn around_y(speed: f32) -> Self {
+        RotatingPlatform::new(Vec3::Y * speed)
+    }
+}
+
+/// Motion a character picked up from the platform under it
+///
+/// Linear velocity goes into `ExternalForces`, kept to take it back off again. Rotation is
+/// applied by the character step, see `PlatformRider::orbit`.
+#[derive(Component, Debug, Default, Clone, Copy, PartialEq)]
+pub struct PlatformRider {
+    velocity: Vec3,
+    angular_velocity: Vec3,
+    pivot: Vec3,
+}
+
+impl PlatformRider {
+    pub fn velocity(&self) -> Vec3 {
+        self.velocity
+    }
+
+    pub fn angular_velocity(&self) -> Vec3 {
+        self.angular_velocity
+    }
+
+    /// Velocity carrying `position` around the platform over a step of `dt` and the turn of the
+    /// platform over that step
+    ///
+    /// The velocity follows the chord rather than the tangent, so riders stay on their circle.
+    pub fn orbit(&self, position: Vec3, dt: f32) -> (Vec3, Quat) {
+        if self.angular_velocity == Vec3::ZERO || dt <= 0.0 {
+            return (Vec3::ZERO, Quat::IDENTITY);
+        }
+
+        let turn = Quat::from_scaled_axis(self.angular_velocity * dt);
+        let offset = position - self.pivot;
+        ((turn * offset - offset) / dt, turn)
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn setup_platforms(
+    mut commands: Commands,
+    mut q: Query<
+        (
+            Entity,
+            Option<&mut MovingPlatform>,
+            &Transform,
+            Option<&RigidBody>,
+        ),
+        Or<(Added<MovingPlatform>, Added<RotatingPlatform>)>,
+    >,
+) {
+    for (entity, platform, transform, body) in q.iter_mut() {
+        if let Some(mut platform) = platform {
+            platform.origin = transform.translation;
+        }
+
+        // Rapier derives the body velocity from its moves, dynamic bodies ride along by friction
+        if body.is_none() {
+            commands
+                .entity(entity)
+                .insert(RigidBody::KinematicPositionBased);
+        }
+    }
+}
+
+fn trigger_platforms(
+    mut activations: EventReader<Activated>,
+    mut platforms: Query<&mut MovingPlatform>,
+) {
+    for Activated { target, active, .. } in activations.iter() {
+        let Ok(mut platform) = platforms.get_mut(*target) else {
+            continue;
+        };
+        platform.active = *active;
+
+        // Turn around halfway through a leg that now leads the wrong way
+        let forward = platform.to > platform.from;
+        if platform.mode == PlatformMode::Triggered && !platform.stopped() && forward != *active {
+            let (from, to) = (platform.from, platform.to);
+            platform.from = to;
+            platform.to = from;
+            platform.progress = 1.0 - platform.progress;
+        }
+    }
+}
+
+pub fn move_platforms(
+    mut platforms: Query<(&mut MovingPlatform, &mut Transform)>,
+    time: Res<Time>,
+) {
+    let dt = time.delta_seconds();
+
+    for (mut platform, mut transform) in platforms.iter_mut() {
+        platform.velocity = Vec3::ZERO;
+        if dt <= 0.0 || platform.waypoints.is_empty() {
+            continue;
+        }
+
+        if platform.stopped() {
+            if platform.wait > 0.0 {
+                platform.wait -= dt;
+                continue;
+            }
+            match platform.next() {
+                Some(next) => platform.to = next,
+                None => continue,
+            }
+        }
+
+        let from = platform.waypoints[platform.from];
+        let to = platform.waypoints[platform.to];
+        let length = from.position.distance(to.position);
+        let step = match length > 0.0 && platform.speed > 0.0 {
+            true => dt * platform.speed / length,
+            false => 1.0,
+        };
+        platform.progress = (platform.progress + step).min(1.0);
+
+        let t = from.easing.apply(platform.progress);
+        let translation = platform.origin + from.position.lerp(to.position, t);
+        platform.velocity = (translation - transform.translation) / dt;
+        transform.translation = translation;
+
+        if platform.progress >= 1.0 {
+            platform.from = platform.to;
+            platform.progress = 0.0;
+            platform.wait = to.wait;
+        }
+    }
+}
+
+pub fn rotate_platforms(
+    mut platforms: Query<(&RotatingPlatform, &mut Transform)>,
+    time: Res<Time>,
+) {
+    for (platform, mut transform) in platforms.iter_mut() {
+        let turn = Quat::from_scaled_axis(platform.angular_velocity * time.delta_seconds());
+        transform.rotation = (turn * transform.rotation).normalize();
+    }
+}
+
+/// Adds the velocity of the platform a character stands on to its `ExternalForces`
+///
+/// The kinematic character controller isn't moved by the bodies it touches, unlike dynamic ones.
+pub fn ride_platforms(
+    mut riders: Query<(&Grounded, &mut ExternalForces, &mut PlatformRider)>,
+    platforms: Query<(
+        Option<&MovingPlatform>,
+        Option<&RotatingPlatform>,
+        &GlobalTransform,
+    )>,
+    rapier: Res<RapierContext>,
+) {
+    for (grounded, mut external, mut rider) in riders.iter_mut() {
+        let platform = grounded
+            .ground()
+            .filter(|_| grounded.get())
+            // Colliders may be children of the platform body
+            .map(|collider| rapier.collider_parent(collider).unwrap_or(collider))
+            .and_then(|ground| platforms.get(ground).ok());
+
+        let (velocity, angular_velocity, pivot) = match platform {
+            Some((moving, rotating, transform)) => (
+                moving.map_or(Vec3::ZERO, |moving| moving.velocity()),
+                rotating.map_or(Vec3::ZERO, |rotating| rotating.angular_velocity),
+                transform.translation(),
+            ),
+            None => (Vec3::ZERO, Vec3::ZERO, Vec3::ZERO),
+        };
+        rider.angular_velocity = angular_velocity;
+        rider.pivot = pivot;
+
+        if velocity != rider.velocity {
+            external.0 += velocity - rider.velocity;
+            rider.velocity = velocity;
+        }
+    }
+}
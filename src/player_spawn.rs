@@ -0,0 +1,94 @@
+use bevy::{ecs::system::SystemParam, prelude::*};
+use serde::{Deserialize, Serialize};
+
+/// Where players enter a level, told apart by their `Name`
+///
+/// Levels spawn these from their data, see `LevelDescription::player_spawns` and the
+/// `{ "player_spawn": "name" }` glTF node extras.
+#[derive(Component, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct PlayerSpawn;
+
+/// Name of the spawn players enter at, an unnamed one if not set
+#[derive(Resource, Debug, Default, Clone, PartialEq, Eq, Deref, DerefMut)]
+pub struct SelectedSpawn(pub Option<String>);
+
+/// Player spawn in level data
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LevelSpawn {
+    pub name: Option<String>,
+    pub translation: Vec3,
+    /// Degrees around the vertical axis the player faces
+    pub yaw: f32,
+}
+
+impl LevelSpawn {
+    pub fn transform(&self) -> Transform {
+        Transform::from_translation(self.translation)
+            .with_rotation(Quat::from_rotation_y(self.yaw.to_radians()))
+    }
+}
+
+type SpawnItem = (Entity, &'static GlobalTransform, Option<&'static Name>);
+
+#[derive(SystemParam)]
+pub struct PlayerSpawns<'w, 's> {
+    spawns: Query<'w, 's, SpawnItem, With<PlayerSpawn>>,
+    parents: Query<'w, 's, &'static Parent>,
+    selected: Option<Res<'w, SelectedSpawn>>,
+}
+
+impl<'w, 's> PlayerSpawns<'w, 's> {
+    /// Spawn of the given name, or an unnamed one falling back to any
+    pub fn find(&self, name: Option<&str>) -> Option<Entity> {
+        self.find_where(name, |_| true)
+    }
+
+    /// Like `find`, among the spawns of the level below `root`
+    pub fn find_in(&self, root: Entity, name: Option<&str>) -> Option<Entity> {
+        self.find_where(name, |spawn| {
+            let mut current = self.parents.get(spawn).ok();
+            while let Some(parent) = current {
+                if parent.get() == root {
+                    return true;
+                }
+                current = self.parents.get(parent.get()).ok();
+            }
+            false
+        })
+    }
+
+    fn find_where(&self, name: Option<&str>, filter: impl Fn(Entity) -> bool) -> Option<Entity> {
+        let spawns = || self.spawns.iter().filter(|(entity, ..)| filter(*entity));
+        let named = |wanted: Option<&str>| {
+            spawns()
+                .find(|(_, _, name)| name.map(|name| name.as_str()) == wanted)
+                .map(|(entity, ..)| entity)
+        };
+
+        match name {
+            Some(_) => named(name),
+            None => named(None).or_else(|| spawns().next().map(|(entity, ..)| entity)),
+        }
+    }
+
+    /// Spawn picked by `SelectedSpawn`
+    pub fn selected(&self) -> Option<Entity> {
+        self.find(self.selected_name())
+    }
+
+    /// Name held by `SelectedSpawn`
+    pub fn selected_name(&self) -> Option<&str> {
+        self.selected
+            .as_ref()
+            .and_then(|selected| selected.0.as_deref())
+    }
+
+    /// Upright transform of a player entering at the spawn
+    pub fn transform(&self, spawn: Entity) -> Option<Transform> {
+        let (_, transform, _) = self.spawns.get(spawn).ok()?;
+        let (_, rotation, translation) = transform.to_scale_rotation_translation();
+        let (yaw, _, _) = rotation.to_euler(EulerRot::YXZ);
+        Some(Transform::from_translation(translation).with_rotation(Quat::from_rotation_y(yaw)))
+    }
+}
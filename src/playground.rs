@@ -0,0 +1,187 @@
+use crate::prelude::*;
+use bevy::{ecs::system::Command, prelude::*};
+use bevy_rapier3d::prelude::*;
+
+/// Spawns a gym for checking how the character controller feels after changes
+///
+/// Rows of stairs, ramps, gaps, platforms, an ice patch and low corridors run along `X`, each
+/// row going from small to large values, all below a root named `Test playground`.
+#[derive(Default)]
+pub struct TestPlaygroundPlugin {
+    origin: Vec3,
+}
+
+impl TestPlaygroundPlugin {
+    /// Places the middle of the playground floor at `origin`
+    pub fn at(origin: Vec3) -> Self {
+        TestPlaygroundPlugin { origin }
+    }
+}
+
+impl Plugin for TestPlaygroundPlugin {
+    fn build(&self, app: &mut App) {
+        let origin = self.origin;
+        app.add_startup_system(move |mut commands: Commands| {
+            commands.add(SpawnTestPlayground { origin });
+        });
+    }
+}
+
+/// Heights of the steps of each staircase, in meters
+const STEP_HEIGHTS: [f32; 6] = [0.1, 0.2, 0.3, 0.4, 0.5, 0.6];
+/// Degrees
+const RAMP_ANGLES: [f32; 7] = [10.0, 20.0, 30.0, 40.0, 45.0, 50.0, 60.0];
+/// Widths of the openings between wall pairs, in meters
+const GAP_WIDTHS: [f32; 6] = [1.5, 1.8, 2.0, 2.2, 2.5, 3.0];
+/// Corridor heights from floor to ceiling, in meters
+const CEILING_HEIGHTS: [f32; 5] = [2.5, 3.0, 3.5, 4.0, 4.5];
+
+/// Spawns the test playground around `origin`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SpawnTestPlayground {
+    pub origin: Vec3,
+}
+
+impl Command for SpawnTestPlayground {
+    fn write(self, world: &mut World) {
+        let origin = self.origin;
+        let mut children = Vec::new();
+
+        let floor = block(
+            world,
+            Vec3::new(80.0, 1.0, 80.0),
+            origin - Vec3::Y * 0.5,
+            Color::rgb(0.4, 0.4, 0.4),
+        );
+        world
+            .entity_mut(floor)
+            .insert(Surface::new(SurfaceKind::Stone));
+        children.push(floor);
+
+        // Staircases of five steps each, climbing towards -Z
+        for (i, height) in STEP_HEIGHTS.into_iter().enumerate() {
+            let x = -30.0 + i as f32 * 8.0;
+            for step in 0..5 {
+                let top = height * (step + 1) as f32;
+                let center = Vec3::new(x, top / 2.0, -25.0 - step as f32 * 0.6);
+                let color = Color::rgb(0.8, 0.6, 0.3);
+                children.push(block(
+                    world,
+                    Vec3::new(3.0, top, 0.6),
+                    origin + center,
+                    color,
+                ));
+            }
+        }
+
+        // Ramps rising over six meters towards -Z
+        for (i, angle) in RAMP_ANGLES.into_iter().enumerate() {
+            let run = 6.0;
+            let size = Vec3::new(3.0, run * angle.to_radians().tan(), run);
+            let translation = origin + Vec3::new(-30.0 + i as f32 * 6.0, 0.0, -10.0);
+            let transform = Transform::from_translation(translation);
+            let ramp = SpawnObject::new(PrimitiveShape::Ramp, transform)
+                .with_size(size)
+                .with_color(Color::rgb(0.3, 0.7, 0.4));
+            children.push(ramp.spawn(world));
+        }
+
+        // Openings between two walls, narrowest first
+        for (i, width) in GAP_WIDTHS.into_iter().enumerate() {
+            let x = -30.0 + i as f32 * 8.0;
+            for side in [-1.0, 1.0] {
+                let center = Vec3::new(x + side * (width / 2.0 + 1.0), 1.5, 5.0);
+                let color = Color::rgb(0.6, 0.3, 0.3);
+                children.push(block(
+                    world,
+                    Vec3::new(2.0, 3.0, 0.5),
+                    origin + center,
+                    color,
+                ));
+            }
+        }
+
+        // Elevator, shuttle and turntable
+        let platform = |world: &mut World, center: Vec3| {
+            SpawnObject::new(
+                PrimitiveShape::Box,
+                Transform::from_translation(origin + center),
+            )
+            .with_size(Vec3::new(4.0, 0.5, 4.0))
+            .with_body(RigidBody::KinematicPositionBased)
+            .with_color(Color::rgb(0.4, 0.4, 0.8))
+            .spawn(world)
+        };
+        let elevator = platform(world, Vec3::new(-30.0, 0.25, 18.0));
+        world.entity_mut(elevator).insert(
+            MovingPlatform::new(vec![
+                Waypoint::new(Vec3::ZERO).with_wait(1.0),
+                Waypoint::new(Vec3::Y * 5.0).with_wait(1.0),
+            ])
+            .with_mode(PlatformMode::PingPong),
+        );
+        let shuttle = platform(world, Vec3::new(-20.0, 1.0, 18.0));
+        world.entity_mut(shuttle).insert(
+            MovingPlatform::new(vec![
+                Waypoint::new(Vec3::ZERO).with_wait(0.5),
+                Waypoint::new(Vec3::X * 10.0).with_wait(0.5),
+            ])
+            .with_mode(PlatformMode::PingPong)
+            .with_speed(4.0),
+        );
+        let turntable = platform(world, Vec3::new(0.0, 0.25, 18.0));
+        world
+            .entity_mut(turntable)
+            .insert(RotatingPlatform::around_y(0.8));
+        children.extend([elevator, shuttle, turntable]);
+
+        let ice = block(
+            world,
+            Vec3::new(12.0, 0.1, 12.0),
+            origin + Vec3::new(18.0, 0.05, 18.0),
+            Color::rgb(0.7, 0.9, 1.0),
+        );
+        let slippery = Surface::new(SurfaceKind::Ice).with_friction(FrictionClass::Slippery);
+        world.entity_mut(ice).insert(slippery);
+        children.push(ice);
+
+        // Corridors with walls on both sides and a ceiling
+        for (i, height) in CEILING_HEIGHTS.into_iter().enumerate() {
+            let x = -30.0 + i as f32 * 8.0;
+            let color = Color::rgb(0.5, 0.5, 0.5);
+            for side in [-1.0, 1.0] {
+                let center = Vec3::new(x + side * 2.25, height / 2.0, 32.0);
+                children.push(block(
+                    world,
+                    Vec3::new(0.5, height, 8.0),
+                    origin + center,
+                    color,
+                ));
+            }
+            let ceiling = Vec3::new(x, height + 0.25, 32.0);
+            children.push(block(
+                world,
+                Vec3::new(5.0, 0.5, 8.0),
+                origin + ceiling,
+                color,
+            ));
+        }
+
+        world
+            .spawn((
+                Name::new("Test playground"),
+                TransformBundle::default(),
+                VisibilityBundle::default(),
+            ))
+            .push_children(&children);
+    }
+}
+
+/// Fixed box of `size` centered on `center`
+fn block(world: &mut World, size: Vec3, center: Vec3, color: Color) -> Entity {
+    SpawnObject::new(PrimitiveShape::Box, Transform::from_translation(center))
+        .with_size(size)
+        .with_body(RigidBody::Fixed)
+        .with_color(color)
+        .spawn(world)
+}
@@ -0,0 +1,300 @@
+use crate::prelude::*;
+use bevy::{ecs::system::Command, prelude::*, utils::HashSet};
+
+/// Distance between the centers of neighbouring rooms, in meters
+const CELL: f32 = 28.0;
+/// Smallest room side, so rooms keep a floor to stand on
+const MIN_ROOM: f32 = 4.0;
+/// Tallest height difference between corridor segments, below the default autostep
+const MAX_STEP: f32 = 0.4;
+
+/// Knobs of `generate_level`, the same settings always give the same level
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GeneratorSettings {
+    pub seed: u64,
+    /// Rooms laid out along the path, at least one
+    pub rooms: usize,
+    /// `0..=1`, narrows corridors and widens their gaps, raises stacks and height differences
+    pub difficulty: f32,
+    /// Smallest and largest room side, in meters, kept between 4 m and 24 m
+    pub room_size: (f32, f32),
+}
+
+impl Default for GeneratorSettings {
+    fn default() -> Self {
+        GeneratorSettings {
+            seed: 0,
+            rooms: 8,
+            difficulty: 0.3,
+            room_size: (10.0, 20.0),
+        }
+    }
+}
+
+impl GeneratorSettings {
+    pub fn new(seed: u64) -> Self {
+        GeneratorSettings {
+            seed,
+            ..Default::default()
+        }
+    }
+
+    pub fn with_rooms(mut self, rooms: usize) -> Self {
+        self.rooms = rooms;
+        self
+    }
+
+    pub fn with_difficulty(mut self, difficulty: f32) -> Self {
+        self.difficulty = difficulty;
+        self
+    }
+}
+
+/// SplitMix64, small and good enough for level layouts
+struct Rng(u64);
+
+impl Rng {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// `0..1`
+    fn unit(&mut self) -> f32 {
+        (self.next_u64() >> 40) as f32 / (1u64 << 24) as f32
+    }
+
+    fn range(&mut self, min: f32, max: f32) -> f32 {
+        min + (max - min) * self.unit()
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        (self.next_u64() % n.max(1) as u64) as usize
+    }
+}
+
+/// Room on the layout grid, its floor top at `center.y`
+struct Room {
+    cell: IVec2,
+    center: Vec3,
+    half: Vec2,
+}
+
+fn floor(center: Vec3, size: Vec3, color: [f32; 3]) -> LevelPrimitive {
+    LevelPrimitive {
+        translation: center - Vec3::Y * size.y / 2.0,
+        size: Some(size),
+        body: Some(LevelBody::Fixed),
+        color,
+        ..Default::default()
+    }
+}
+
+/// Lays out rooms on a random walk, joined by corridors and filled with box stacks
+pub fn generate_level(settings: &GeneratorSettings) -> LevelDescription {
+    let mut rng = Rng(settings.seed);
+    // `max` and `min` pick the number over NaN, unlike `clamp`, which also panics on bad bounds
+    let difficulty = settings.difficulty.max(0.0).min(1.0);
+    let (min_size, max_size) = settings.room_size;
+    let min_size = min_size.max(MIN_ROOM).min(CELL - 4.0);
+    let max_size = max_size.max(min_size).min(CELL - 4.0);
+
+    // Walk the grid without crossing itself, giving up early when boxed in
+    let mut rooms: Vec<Room> = Vec::new();
+    let mut visited = HashSet::default();
+    let mut cell = IVec2::ZERO;
+    let mut elevation = 0.0;
+    for _ in 0..settings.rooms.max(1) {
+        let half = Vec2::new(rng.range(min_size, max_size), rng.range(min_size, max_size)) / 2.0;
+        let center = Vec3::new(cell.x as f32 * CELL, elevation, cell.y as f32 * CELL);
+        visited.insert(cell);
+        rooms.push(Room { cell, center, half });
+
+        let free: Vec<_> = [IVec2::X, IVec2::NEG_X, IVec2::Y, IVec2::NEG_Y]
+            .into_iter()
+            .map(|direction| cell + direction)
+            .filter(|next| !visited.contains(next))
+            .collect();
+        if free.is_empty() {
+            break;
+        }
+        cell = free[rng.below(free.len())];
+        elevation += rng.range(-1.0, 1.0) * difficulty * 3.0;
+    }
+
+    let mut level = LevelDescription {
+        player_spawns: vec![LevelSpawn {
+            name: Some("start".to_string()),
+            translation: rooms[0].center + Vec3::Y * 3.0,
+            yaw: 0.0,
+        }],
+        ..Default::default()
+    };
+
+    for (i, room) in rooms.iter().enumerate() {
+        let size = Vec3::new(room.half.x * 2.0, 1.0, room.half.y * 2.0);
+        level
+            .geometry
+            .push(floor(room.center, size, [0.45, 0.45, 0.5]));
+
+        // Box stacks, taller and more of them the harder it gets
+        let stacks = 1 + rng.below(2 + (difficulty * 4.0) as usize);
+        for _ in 0..stacks {
+            let side = rng.range(1.0, 2.0);
+            let spread = (room.half - side).max(Vec2::ZERO);
+            let x = rng.range(-spread.x, spread.x);
+            let z = rng.range(-spread.y, spread.y);
+            let height = 1 + rng.below(1 + (difficulty * 3.0) as usize);
+            let dynamic = rng.unit() < 0.3;
+            for tier in 0..height {
+                let y = side * (tier as f32 + 0.5);
+                level.geometry.push(LevelPrimitive {
+                    translation: room.center + Vec3::new(x, y, z),
+                    size: Some(Vec3::splat(side)),
+                    body: Some(if dynamic {
+                        LevelBody::Dynamic
+                    } else {
+                        LevelBody::Fixed
+                    }),
+                    color: [0.7, 0.55, 0.35],
+                    ..Default::default()
+                });
+            }
+        }
+
+        let Some(next) = rooms.get(i + 1) else {
+            continue;
+        };
+        corridor(&mut level, &mut rng, difficulty, room, next);
+    }
+
+    // Goal in the last room, a kill volume below everything and a sun
+    let last = rooms.last().unwrap();
+    level.triggers.push(LevelTrigger {
+        name: Some("Goal".into()),
+        translation: last.center + Vec3::Y * 1.5,
+        size: Vec3::new(2.0, 3.0, 2.0),
+        ..Default::default()
+    });
+    let lowest = rooms
+        .iter()
+        .map(|room| room.center.y)
+        .fold(f32::INFINITY, f32::min);
+    let extent = rooms.len() as f32 * CELL * 2.0;
+    level.triggers.push(LevelTrigger {
+        kind: LevelTriggerKind::Kill,
+        translation: Vec3::new(0.0, lowest - 20.0, 0.0),
+        size: Vec3::new(extent, 1.0, extent),
+        ..Default::default()
+    });
+    level.lights.push(LevelLight {
+        kind: LevelLightKind::Directional,
+        rotation: Vec3::new(-50.0, 30.0, 0.0),
+        intensity: 10_000.0,
+        shadows: true,
+        ..Default::default()
+    });
+
+    level
+}
+
+/// Bridge of segments from `from` to the neighbouring room `to`, with gaps when difficult
+fn corridor(level: &mut LevelDescription, rng: &mut Rng, difficulty: f32, from: &Room, to: &Room) {
+    let direction = (to.cell - from.cell).as_vec2();
+    let axis = Vec3::new(direction.x, 0.0, direction.y);
+    let start = from.center + axis * from.half.dot(direction.abs());
+    let end = to.center - axis * to.half.dot(direction.abs());
+    let length = (end - start).dot(axis);
+
+    let width = 4.0 - difficulty * rng.range(1.0, 2.5);
+    let gap = match difficulty > 0.3 {
+        true => rng.range(0.0, difficulty * 2.5),
+        false => 0.0,
+    };
+    // Enough segments to keep height differences climbable
+    let rise = (end.y - start.y).abs();
+    let segments = ((length / 3.0).ceil() as usize).max((rise / MAX_STEP).ceil() as usize + 1);
+    let pitch = length / segments as f32;
+
+    for segment in 0..segments {
+        let t = (segment as f32 + 0.5) / segments as f32;
+        // Gaps only between segments, never at the room edges
+        let back = if segment > 0 { gap / 2.0 } else { 0.0 };
+        let front = if segment + 1 < segments {
+            gap / 2.0
+        } else {
+            0.0
+        };
+        let along = (pitch - back - front).max(0.5);
+        let center = start.lerp(end, t) + axis * (back - front) / 2.0;
+        let size = match direction.x != 0.0 {
+            true => Vec3::new(along, 1.0, width),
+            false => Vec3::new(width, 1.0, along),
+        };
+        level.geometry.push(floor(center, size, [0.5, 0.5, 0.4]));
+    }
+}
+
+/// Generates a level and spawns it as children of `root`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GenerateLevel {
+    pub root: Entity,
+    pub settings: GeneratorSettings,
+}
+
+impl Command for GenerateLevel {
+    fn write(self, world: &mut World) {
+        let description = generate_level(&self.settings);
+        info!(
+            "Generated level with seed {}: {} primitives",
+            self.settings.seed,
+            description.geometry.len()
+        );
+        SpawnLevel {
+            root: self.root,
+            description,
+        }
+        .write(world);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn same_settings_give_the_same_level() {
+        let settings = GeneratorSettings::new(42)
+            .with_rooms(12)
+            .with_difficulty(0.7);
+        assert_eq!(generate_level(&settings), generate_level(&settings));
+        assert_ne!(
+            generate_level(&settings),
+            generate_level(&GeneratorSettings {
+                seed: 43,
+                ..settings
+            })
+        );
+    }
+
+    #[test]
+    fn bad_settings_still_give_a_level() {
+        let settings = GeneratorSettings {
+            seed: 7,
+            rooms: 0,
+            difficulty: f32::NAN,
+            room_size: (30.0, 1.0),
+        };
+        let level = generate_level(&settings);
+
+        assert!(!level.geometry.is_empty());
+        assert!(level
+            .geometry
+            .iter()
+            .all(|primitive| primitive.translation.is_finite()));
+        assert!(level.player_spawns[0].translation.is_finite());
+    }
+}
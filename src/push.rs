@@ -0,0 +1,343 @@
+use crate::prelude::*;
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use leafwing_input_manager::prelude::*;
+
+/// Collision group of grabbed boxes, the character pushing them walks through them
+pub const PUSHED_GROUP: Group = Group::GROUP_31;
+/// Gap along the push axis at which a character loses its grip, in meters
+const PUSH_BREAK_DISTANCE: f32 = 0.5;
+
+/// Lets characters with a `Pusher` grab a face of a `Pushable` box by holding `Interact`
+///
+/// While grabbed, movement is limited to pushing and pulling along the face at crouch speed.
+pub struct PushPlugin;
+
+impl Plugin for PushPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_to_stage(
+            CoreStage::PreUpdate,
+            constrain_push_input
+                .after(read_device_input)
+                .after(drive_character_input),
+        )
+        .add_system(setup_pushables)
+        .add_system(grab_pushables.after(interact))
+        .add_system(push_boxes.after(grab_pushables))
+        .add_system(snap_pushables.after(push_boxes));
+    }
+}
+
+/// Box moved by characters pushing or pulling one of its faces
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct Pushable {
+    /// Cell size the box slides into once let go, for sokoban style puzzles
+    pub grid: Option<f32>,
+    /// Speed of sliding into a grid cell, in meters per second
+    pub snap_speed: f32,
+    snap: Option<Vec3>,
+    /// Body and groups of the box before it was grabbed
+    body: Option<RigidBody>,
+    groups: Option<CollisionGroups>,
+}
+
+impl Default for Pushable {
+    fn default() -> Self {
+        Pushable {
+            grid: None,
+            snap_speed: 2.0,
+            snap: None,
+            body: None,
+            groups: None,
+        }
+    }
+}
+
+impl Pushable {
+    pub fn with_grid(mut self, size: f32) -> Self {
+        self.grid = Some(size);
+        self
+    }
+}
+
+/// Character able to push and pull boxes
+#[derive(Component, Debug, Default, Clone, Copy, PartialEq)]
+pub struct Pusher {
+    grabbed: Option<Entity>,
+    /// Horizontal direction from the grabbed face into the box
+    axis: Vec3,
+    /// Distance along `axis` from the character to the middle of the box
+    offset: f32,
+    /// Whether the box couldn't be pushed any further
+    blocked: bool,
+    /// Groups of the character and its controller before grabbing
+    groups: Option<CollisionGroups>,
+    controller_groups: Option<CollisionGroups>,
+}
+
+impl Pusher {
+    pub fn grabbed(&self) -> Option<Entity> {
+        self.grabbed
+    }
+
+    /// Direction the grabbed box moves in when pushed
+    pub fn axis(&self) -> Vec3 {
+        self.axis
+    }
+}
+
+fn setup_pushables(
+    mut commands: Commands,
+    q: Query<(Entity, Option<&RigidBody>, Option<&Interactable>), Added<Pushable>>,
+) {
+    for (entity, body, interactable) in q.iter() {
+        if body.is_none() {
+            commands
+                .entity(entity)
+                .insert(RigidBody::KinematicPositionBased);
+        }
+        if interactable.is_none() {
+            commands.entity(entity).insert(Interactable::default());
+        }
+    }
+}
+
+/// Limits movement to the push axis at crouch speed, not moving into a stuck box
+pub fn constrain_push_input(
+    mut pushers: Query<(
+        &Pusher,
+        &mut CharacterInput,
+        &Transform,
+        &CharacterMovementController,
+        &CharacterSpeed,
+    )>,
+) {
+    for (pusher, mut input, transform, character, speed) in pushers.iter_mut() {
+        if pusher.grabbed.is_none() {
+            continue;
+        }
+
+        let axis = transform.rotation.inverse() * pusher.axis;
+        let mut amount = input.movement.dot(axis);
+        if pusher.blocked {
+            amount = amount.min(0.0);
+        }
+
+        let crouch = character.settings().speed.crouch;
+        let scale = match speed.get() > 0.0 {
+            true => (crouch / speed.get()).min(1.0),
+            false => 1.0,
+        };
+        input.movement = axis * amount * scale;
+    }
+}
+
+#[allow(clippy::type_complexity)]
+pub fn grab_pushables(
+    mut commands: Commands,
+    mut interactions: EventReader<Interacted>,
+    mut pushers: Query<(
+        &mut Pusher,
+        Option<&mut KinematicCharacterController>,
+        &Transform,
+        &Grounded,
+        Option<&CollisionGroups>,
+        Option<&Carrier>,
+    )>,
+    mut boxes: Query<(
+        &mut Pushable,
+        &Transform,
+        &RigidBody,
+        Option<&CollisionGroups>,
+    )>,
+) {
+    for Interacted { interactor, target } in interactions.iter() {
+        let Ok(character) = pushers.get_mut(*interactor) else {
+            continue;
+        };
+        let (mut pusher, mut controller, transform, grounded, groups, carrier) = character;
+        let Ok((mut pushable, box_transform, body, box_groups)) = boxes.get_mut(*target) else {
+            continue;
+        };
+        let carrying = carrier.and_then(|carrier| carrier.carried()).is_some();
+        if pusher.grabbed.is_some() || carrying || !grounded.get() {
+            continue;
+        }
+
+        // Grab the side face that looks most towards the character
+        let to_box = (box_transform.translation - transform.translation) * Vec3::new(1., 0., 1.);
+        let axis = [Vec3::X, Vec3::Z]
+            .map(|axis| (box_transform.rotation * axis) * Vec3::new(1., 0., 1.))
+            .into_iter()
+            .max_by(|a, b| a.dot(to_box).abs().total_cmp(&b.dot(to_box).abs()))
+            .unwrap();
+        let axis = (axis * axis.dot(to_box).signum()).normalize_or_zero();
+        if axis == Vec3::ZERO {
+            continue;
+        }
+
+        pushable.snap = None;
+        pushable.body = pushable.body.or(Some(*body));
+        pushable.groups = box_groups.copied();
+        commands
+            .entity(*target)
+            .insert(RigidBody::KinematicPositionBased)
+            .insert(CollisionGroups::new(PUSHED_GROUP, Group::ALL));
+
+        // Neither the character nor its controller may bump into what it moves
+        let ignore_pushed = |groups: Option<CollisionGroups>| {
+            let groups = groups.unwrap_or_default();
+            CollisionGroups::new(groups.memberships, groups.filters - PUSHED_GROUP)
+        };
+        pusher.groups = groups.copied();
+        if let Some(controller) = controller.as_deref_mut() {
+            pusher.controller_groups = controller.filter_groups;
+            controller.filter_groups = Some(ignore_pushed(controller.filter_groups));
+        }
+        commands
+            .entity(*interactor)
+            .insert(ignore_pushed(groups.copied()));
+
+        pusher.grabbed = Some(*target);
+        pusher.axis = axis;
+        pusher.offset = to_box.dot(axis);
+        pusher.blocked = false;
+    }
+}
+
+/// Lets go of the grabbed box, which slides into its grid cell if it has one
+fn release_pushed(
+    commands: &mut Commands,
+    pusher_entity: Entity,
+    pusher: &mut Pusher,
+    controller: Option<&mut KinematicCharacterController>,
+    pushed: Option<(Entity, &mut Pushable, &Transform)>,
+) {
+    let mut character = commands.entity(pusher_entity);
+    match pusher.groups.take() {
+        Some(groups) => character.insert(groups),
+        None => character.remove::<CollisionGroups>(),
+    };
+    if let Some(controller) = controller {
+        controller.filter_groups = pusher.controller_groups.take();
+    }
+    pusher.grabbed = None;
+    pusher.blocked = false;
+
+    let Some((entity, pushable, transform)) = pushed else {
+        return;
+    };
+    let mut object = commands.entity(entity);
+    match pushable.groups.take() {
+        Some(groups) => object.insert(groups),
+        None => object.remove::<CollisionGroups>(),
+    };
+
+    let Some(grid) = pushable.grid.filter(|grid| *grid > 0.0) else {
+        if let Some(body) = pushable.body.take() {
+            object.insert(body);
+        }
+        return;
+    };
+    let axis = pusher.axis;
+    let along = transform.translation.dot(axis);
+    let snapped = (along / grid).round() * grid;
+    pushable.snap = Some(transform.translation + axis * (snapped - along));
+}
+
+#[allow(clippy::type_complexity)]
+pub fn push_boxes(
+    mut commands: Commands,
+    mut pushers: Query<(
+        Entity,
+        &mut Pusher,
+        Option<&mut KinematicCharacterController>,
+        &ActionState<CharacterActions>,
+        &Transform,
+        &Grounded,
+    )>,
+    mut boxes: Query<(&mut Pushable, &mut Transform, &Collider), Without<Pusher>>,
+    rapier: Res<RapierContext>,
+) {
+    for (entity, mut pusher, mut controller, actions, transform, grounded) in pushers.iter_mut() {
+        let Some(object) = pusher.grabbed else {
+            continue;
+        };
+        let Ok((mut pushable, mut box_transform, collider)) = boxes.get_mut(object) else {
+            // Despawned while grabbed
+            release_pushed(
+                &mut commands,
+                entity,
+                &mut pusher,
+                controller.as_deref_mut(),
+                None,
+            );
+            continue;
+        };
+
+        let axis = pusher.axis;
+        let target = transform.translation.dot(axis) + pusher.offset;
+        let mut delta = target - box_transform.translation.dot(axis);
+
+        // Stop at walls and other bodies, kinematic bodies would go right through them
+        pusher.blocked = false;
+        if delta.abs() > f32::EPSILON {
+            let direction = axis * delta.signum();
+            let filter = QueryFilter::default()
+                .exclude_rigid_body(entity)
+                .exclude_collider(object)
+                .exclude_sensors();
+            let hit = rapier.cast_shape(
+                box_transform.translation,
+                box_transform.rotation,
+                direction,
+                collider,
+                delta.abs(),
+                filter,
+            );
+            if let Some((_, toi)) = hit {
+                pusher.blocked = delta > 0.0;
+                delta = (toi.toi - 0.01).max(0.0) * delta.signum();
+            }
+            box_transform.translation += axis * delta;
+        }
+
+        let released = !actions.pressed(CharacterActions::Interact) || !grounded.get();
+        let gap = (target - box_transform.translation.dot(axis)).abs();
+        if released || gap > PUSH_BREAK_DISTANCE {
+            let pushed = Some((object, &mut *pushable, &*box_transform));
+            release_pushed(
+                &mut commands,
+                entity,
+                &mut pusher,
+                controller.as_deref_mut(),
+                pushed,
+            );
+        }
+    }
+}
+
+pub fn snap_pushables(
+    mut commands: Commands,
+    mut boxes: Query<(Entity, &mut Pushable, &mut Transform)>,
+    time: Res<Time>,
+) {
+    for (entity, mut pushable, mut transform) in boxes.iter_mut() {
+        let Some(target) = pushable.snap else {
+            continue;
+        };
+
+        let gap = target - transform.translation;
+        let step = pushable.snap_speed * time.delta_seconds();
+        if gap.length() > step {
+            transform.translation += gap.normalize() * step;
+            continue;
+        }
+
+        transform.translation = target;
+        pushable.snap = None;
+        if let Some(body) = pushable.body.take() {
+            commands.entity(entity).insert(body);
+        }
+    }
+}
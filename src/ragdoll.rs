@@ -0,0 +1,194 @@
+use crate::prelude::*;
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+/// Turns characters into tumbling dynamic capsules on `RagdollRequest`s and back
+#[derive(Default)]
+pub struct RagdollPlugin {
+    settings: RagdollSettings,
+}
+
+impl RagdollPlugin {
+    pub fn with_settings(mut self, settings: RagdollSettings) -> Self {
+        self.settings = settings;
+        self
+    }
+
+    /// Ragdolls stay down until a `Recover` request
+    pub fn without_auto_recovery(mut self) -> Self {
+        self.settings.recover_after = None;
+        self
+    }
+}
+
+impl Plugin for RagdollPlugin {
+    fn build(&self, app: &mut App) {
+        init_simulation_time(app);
+        app.add_event::<RagdollRequest>()
+            .insert_resource(self.settings)
+            .add_system(settle_ragdolls.with_run_criteria(simulation_running))
+            .add_system(apply_ragdoll_requests.after(settle_ragdolls));
+    }
+}
+
+#[derive(Resource, Debug, Clone, Copy, PartialEq)]
+pub struct RagdollSettings {
+    /// Seconds a ragdoll lies still before the character gets up by itself
+    pub recover_after: Option<f32>,
+    /// Speed below which a ragdoll counts as lying still, in meters per second
+    pub rest_speed: f32,
+}
+
+impl Default for RagdollSettings {
+    fn default() -> Self {
+        RagdollSettings {
+            recover_after: Some(3.0),
+            rest_speed: 0.2,
+        }
+    }
+}
+
+/// Sent on death, by debug tools or anything else knocking a character over
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RagdollRequest {
+    Enter(Entity),
+    /// Stands the character back up where its ragdoll lies
+    Recover(Entity),
+    Toggle(Entity),
+}
+
+impl RagdollRequest {
+    pub fn entity(&self) -> Entity {
+        match *self {
+            RagdollRequest::Enter(entity)
+            | RagdollRequest::Recover(entity)
+            | RagdollRequest::Toggle(entity) => entity,
+        }
+    }
+}
+
+/// Character currently simulated as a dynamic body, holding what it had before
+#[derive(Component, Debug, Clone)]
+pub struct Ragdolled {
+    /// Only kinematic backends have one
+    controller: Option<KinematicCharacterController>,
+    body: RigidBody,
+    locked_axes: LockedAxes,
+    restitution: Option<Restitution>,
+    /// Seconds spent lying still
+    resting: f32,
+}
+
+fn settle_ragdolls(
+    mut ragdolls: Query<(Entity, &mut Ragdolled, &Velocity)>,
+    mut requests: EventWriter<RagdollRequest>,
+    settings: Res<RagdollSettings>,
+    time: Res<SimulationTime>,
+) {
+    let Some(recover_after) = settings.recover_after else {
+        return;
+    };
+
+    for (entity, mut ragdoll, velocity) in ragdolls.iter_mut() {
+        let still = velocity.linvel.length() < settings.rest_speed
+            && velocity.angvel.length() < settings.rest_speed;
+        ragdoll.resting = match still {
+            true => ragdoll.resting + time.delta_seconds(),
+            false => 0.0,
+        };
+
+        if ragdoll.resting >= recover_after {
+            ragdoll.resting = 0.0;
+            requests.send(RagdollRequest::Recover(entity));
+        }
+    }
+}
+
+#[allow(clippy::type_complexity)]
+pub fn apply_ragdoll_requests(
+    mut commands: Commands,
+    mut requests: EventReader<RagdollRequest>,
+    mut characters: Query<(
+        &mut Transform,
+        &mut CharacterMovementController,
+        &mut CharacterSpeed,
+        &CharacterHeight,
+        &CharacterInterpolation,
+        &RigidBody,
+        &LockedAxes,
+        Option<&KinematicCharacterController>,
+        Option<&Restitution>,
+        Option<&Ragdolled>,
+    )>,
+) {
+    for request in requests.iter() {
+        let entity = request.entity();
+        let Ok(components) = characters.get_mut(entity) else {
+            continue;
+        };
+        let (
+            mut transform,
+            mut character,
+            mut speed,
+            height,
+            interpolation,
+            body,
+            locked_axes,
+            controller,
+            restitution,
+            ragdoll,
+        ) = components;
+
+        let enter = match request {
+            RagdollRequest::Enter(_) => true,
+            RagdollRequest::Recover(_) => false,
+            RagdollRequest::Toggle(_) => ragdoll.is_none(),
+        };
+
+        match (enter, ragdoll, controller) {
+            (true, None, controller) => {
+                // Character systems leave ragdolls to rapier
+                commands
+                    .entity(entity)
+                    .remove::<KinematicCharacterController>()
+                    .insert(Ragdolled {
+                        controller: controller.cloned(),
+                        body: *body,
+                        locked_axes: *locked_axes,
+                        restitution: restitution.copied(),
+                        resting: 0.0,
+                    })
+                    .insert(RigidBody::Dynamic)
+                    .insert(LockedAxes::empty())
+                    .insert(Restitution::coefficient(0.0))
+                    .insert(Velocity::linear(interpolation.velocity()));
+            }
+            (false, Some(ragdoll), _) => {
+                let mut target = commands.entity(entity);
+                target
+                    .remove::<Ragdolled>()
+                    .insert(ragdoll.body)
+                    .insert(ragdoll.locked_axes)
+                    .insert(Velocity::zero())
+                    .insert(CharacterState::Idle);
+                if let Some(controller) = ragdoll.controller.clone() {
+                    target.insert(controller);
+                }
+                match ragdoll.restitution {
+                    Some(restitution) => target.insert(restitution),
+                    None => target.remove::<Restitution>(),
+                };
+
+                // Stand up facing the way the ragdoll lies, lifted so the capsule clears the floor
+                let (yaw, _, _) = transform.rotation.to_euler(EulerRot::YXZ);
+                let lift = character.settings().segment_half_height(height.current());
+                transform.rotation = Quat::from_rotation_y(yaw);
+                transform.translation.y += lift;
+
+                character.reset();
+                speed.0 = character.settings().speed.base;
+            }
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,260 @@
+use bevy::prelude::*;
+use leafwing_input_manager::{prelude::*, user_input::InputKind};
+use std::marker::PhantomData;
+
+/// Lets `Rebinding<A>` capture inputs and rewrite `InputMap<A>`s
+pub struct RebindPlugin<A: Actionlike> {
+    _marker: PhantomData<A>,
+}
+
+impl<A: Actionlike> Default for RebindPlugin<A> {
+    fn default() -> Self {
+        RebindPlugin {
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<A: Actionlike> Plugin for RebindPlugin<A> {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Rebinding<A>>()
+            .add_event::<RebindOutcome<A>>()
+            .add_system(capture_rebind::<A>);
+    }
+}
+
+/// Binding to change once the next input comes in
+#[derive(Debug, Clone, PartialEq)]
+pub struct RebindRequest<A: Actionlike> {
+    pub action: A,
+    /// Binding to swap out, the new one is added next to the others when `None`
+    pub replace: Option<UserInput>,
+    /// Input map to change, every `InputMap<A>` when `None`
+    pub entity: Option<Entity>,
+    /// Take the input away from actions already using it instead of refusing
+    pub steal: bool,
+}
+
+impl<A: Actionlike> RebindRequest<A> {
+    pub fn new(action: A) -> Self {
+        RebindRequest {
+            action,
+            replace: None,
+            entity: None,
+            steal: false,
+        }
+    }
+
+    pub fn replacing(mut self, input: impl Into<UserInput>) -> Self {
+        self.replace = Some(input.into());
+        self
+    }
+
+    pub fn for_entity(mut self, entity: Entity) -> Self {
+        self.entity = Some(entity);
+        self
+    }
+
+    pub fn stealing(mut self) -> Self {
+        self.steal = true;
+        self
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum RebindOutcome<A: Actionlike> {
+    Bound {
+        action: A,
+        input: UserInput,
+        /// Actions the input was taken away from
+        stolen_from: Vec<A>,
+    },
+    /// Nothing changed, the input is in use by `conflicts`
+    Conflict {
+        action: A,
+        input: UserInput,
+        conflicts: Vec<A>,
+    },
+    Cancelled {
+        action: A,
+    },
+}
+
+/// Listen mode for rebinding actions of type `A`
+///
+/// Escape cancels listening, so it can't be bound this way.
+#[derive(Resource)]
+pub struct Rebinding<A: Actionlike> {
+    request: Option<RebindRequest<A>>,
+    armed: bool,
+}
+
+impl<A: Actionlike> Default for Rebinding<A> {
+    fn default() -> Self {
+        Rebinding {
+            request: None,
+            armed: false,
+        }
+    }
+}
+
+impl<A: Actionlike> Rebinding<A> {
+    /// Binds the next key, mouse or gamepad button pressed, together with held modifiers
+    pub fn listen(&mut self, request: RebindRequest<A>) {
+        self.request = Some(request);
+        self.armed = false;
+    }
+
+    pub fn cancel(&mut self) {
+        self.request = None;
+    }
+
+    pub fn listening(&self) -> Option<&RebindRequest<A>> {
+        self.request.as_ref()
+    }
+}
+
+/// Actions other than `action` bound to `input`
+pub fn binding_conflicts<A: Actionlike>(
+    map: &InputMap<A>,
+    action: &A,
+    input: &UserInput,
+) -> Vec<A> {
+    A::variants()
+        .filter(|other| other != action)
+        .filter(|other| map.get(other.clone()).iter().any(|bound| bound == input))
+        .collect()
+}
+
+/// Removes `input` from `action`, keeping its other bindings
+pub fn unbind<A: Actionlike>(map: &mut InputMap<A>, action: &A, input: &UserInput) {
+    let kept: Vec<UserInput> = map
+        .get(action.clone())
+        .iter()
+        .filter(|bound| *bound != input)
+        .cloned()
+        .collect();
+
+    map.clear_action(action.clone());
+    for bound in kept {
+        map.insert(bound, action.clone());
+    }
+}
+
+/// Keys that start a chord when held while something else is pressed
+const MODIFIERS: [KeyCode; 6] = [
+    KeyCode::LShift,
+    KeyCode::RShift,
+    KeyCode::LControl,
+    KeyCode::RControl,
+    KeyCode::LAlt,
+    KeyCode::RAlt,
+];
+
+fn captured_input(
+    keys: &Input<KeyCode>,
+    mouse: &Input<MouseButton>,
+    gamepad: &Input<GamepadButton>,
+) -> Option<UserInput> {
+    let key = keys.get_just_pressed().find(|key| !MODIFIERS.contains(key));
+    let key = key.map(|key| InputKind::from(*key));
+    let button = || {
+        mouse
+            .get_just_pressed()
+            .next()
+            .map(|button| (*button).into())
+    };
+    let pad = || {
+        let pressed = gamepad.get_just_pressed().next();
+        pressed.map(|button| button.button_type.into())
+    };
+
+    let Some(pressed) = key.or_else(button).or_else(pad) else {
+        // A modifier on its own is bound once it's let go without pressing anything else
+        let released = keys.get_just_released().find(|key| MODIFIERS.contains(key));
+        return released.map(|key| (*key).into());
+    };
+
+    let held: Vec<InputKind> = MODIFIERS
+        .iter()
+        .filter(|modifier| keys.pressed(**modifier))
+        .map(|modifier| (*modifier).into())
+        .collect();
+
+    Some(match held.is_empty() {
+        true => UserInput::Single(pressed),
+        false => UserInput::chord(held.into_iter().chain([pressed])),
+    })
+}
+
+pub fn capture_rebind<A: Actionlike>(
+    mut rebinding: ResMut<Rebinding<A>>,
+    mut maps: Query<(Entity, &mut InputMap<A>)>,
+    mut outcomes: EventWriter<RebindOutcome<A>>,
+    keys: Res<Input<KeyCode>>,
+    mouse: Res<Input<MouseButton>>,
+    gamepad: Res<Input<GamepadButton>>,
+) {
+    let Some(request) = rebinding.request.clone() else {
+        return;
+    };
+
+    // Whatever started listening was pressed this frame and mustn't be captured
+    if !rebinding.armed {
+        rebinding.armed = true;
+        return;
+    }
+
+    if keys.just_pressed(KeyCode::Escape) {
+        rebinding.request = None;
+        outcomes.send(RebindOutcome::Cancelled {
+            action: request.action,
+        });
+        return;
+    }
+
+    let Some(input) = captured_input(&keys, &mouse, &gamepad) else {
+        return;
+    };
+    rebinding.request = None;
+
+    let mut maps: Vec<_> = maps
+        .iter_mut()
+        .filter(|(entity, _)| request.entity.map_or(true, |target| target == *entity))
+        .map(|(_, map)| map)
+        .collect();
+
+    let mut conflicts: Vec<A> = Vec::new();
+    for map in maps.iter() {
+        for conflict in binding_conflicts(map, &request.action, &input) {
+            if !conflicts.contains(&conflict) {
+                conflicts.push(conflict);
+            }
+        }
+    }
+
+    if !conflicts.is_empty() && !request.steal {
+        outcomes.send(RebindOutcome::Conflict {
+            action: request.action,
+            input,
+            conflicts,
+        });
+        return;
+    }
+
+    for map in maps.iter_mut() {
+        for conflict in conflicts.iter() {
+            unbind(map, conflict, &input);
+        }
+        if let Some(replace) = &request.replace {
+            unbind(map, &request.action, replace);
+        }
+        map.insert(input.clone(), request.action.clone());
+    }
+
+    outcomes.send(RebindOutcome::Bound {
+        action: request.action,
+        input,
+        stolen_from: conflicts,
+    });
+}
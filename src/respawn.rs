@@ -0,0 +1,142 @@
+use crate::prelude::*;
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+pub struct RespawnPlugin;
+impl Plugin for RespawnPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<CharacterRespawned>()
+            .add_system(setup_kill_volumes)
+            .add_system(respawn_characters);
+    }
+}
+
+/// Characters falling below `height` are respawned
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct KillPlane {
+    pub height: f32,
+}
+
+/// Sensor collider respawning any character that enters it
+#[derive(Component, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct KillVolume;
+
+/// Location characters are brought back to
+#[derive(Component, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct RespawnPoint;
+
+/// Respawn point a character returns to instead of the nearest one
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Deref, DerefMut)]
+pub struct RespawnTarget(pub Entity);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CharacterRespawned {
+    pub entity: Entity,
+    /// Respawn point used, if any existed
+    pub point: Option<Entity>,
+    pub translation: Vec3,
+}
+
+fn setup_kill_volumes(mut commands: Commands, q: Query<Entity, Added<KillVolume>>) {
+    for entity in q.iter() {
+        commands
+            .entity(entity)
+            .insert(Sensor)
+            .insert(ActiveCollisionTypes::default() | ActiveCollisionTypes::KINEMATIC_STATIC);
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn respawn_characters(
+    mut characters: Query<(
+        Entity,
+        &mut Transform,
+        &mut CharacterMovementController,
+        &mut CharacterSpeed,
+        &mut Velocity,
+        &mut CharacterState,
+        &mut CharacterInterpolation,
+        &mut Grounded,
+        Option<&mut KinematicCharacterController>,
+        Option<&RespawnTarget>,
+        Option<&CheckpointProgress>,
+    )>,
+    planes: Query<&KillPlane>,
+    volumes: Query<Entity, With<KillVolume>>,
+    points: Query<(Entity, &GlobalTransform), With<RespawnPoint>>,
+    mut events: EventWriter<CharacterRespawned>,
+    rapier: Res<RapierContext>,
+) {
+    for components in characters.iter_mut() {
+        let (
+            entity,
+            mut transform,
+            mut character,
+            mut speed,
+            mut velocity,
+            mut state,
+            mut interpolation,
+            mut grounded,
+            controller,
+            target,
+            progress,
+        ) = components;
+        let position = transform.translation;
+        let below_plane = planes.iter().any(|plane| position.y < plane.height);
+        let in_volume = volumes
+            .iter()
+            .any(|volume| rapier.intersection_pair(entity, volume) == Some(true));
+
+        if !below_plane && !in_volume {
+            continue;
+        }
+
+        let point = target
+            .and_then(|target| points.get(target.0).ok())
+            .or_else(|| {
+                points.iter().min_by(|(_, a), (_, b)| {
+                    let a = a.translation().distance_squared(position);
+                    let b = b.translation().distance_squared(position);
+                    a.total_cmp(&b)
+                })
+            });
+
+        // Checkpoints bring characters back how they were when crossing them
+        let checkpoint = progress
+            .filter(|progress| point.map_or(false, |(point, _)| point == progress.checkpoint));
+
+        match checkpoint {
+            Some(progress) => {
+                transform.translation = progress.transform.translation;
+                transform.rotation = progress.transform.rotation;
+            }
+            None => {
+                transform.translation = point
+                    .map(|(_, point)| point.translation())
+                    .unwrap_or(Vec3::ZERO);
+            }
+        }
+        character.reset();
+        speed.0 = character.settings().speed.base;
+        *velocity = Velocity::zero();
+        interpolation.reset(transform.translation);
+        // The ground and moves of where the character fell don't apply at the respawn point
+        *grounded = Grounded::default();
+        if let Some(mut controller) = controller {
+            controller.translation = None;
+        }
+
+        // Airborne states would have the character fall from a standstill
+        *state = match checkpoint.map(|progress| progress.state) {
+            Some(recorded) if recorded.group() == CharacterStateGroup::Grounded => recorded,
+            _ => CharacterState::Idle,
+        };
+
+        info!("Respawning {:?} at {}", entity, transform.translation);
+        events.send(CharacterRespawned {
+            entity,
+            point: point.map(|(point, _)| point),
+            translation: transform.translation,
+        });
+    }
+}
@@ -0,0 +1,393 @@
+use crate::prelude::*;
+use bevy::prelude::*;
+use leafwing_input_manager::prelude::*;
+
+/// Parents the debug camera to a character at eye height while in `CameraState::Fps`
+///
+/// Horizontal look turns the character, vertical look only pitches the camera.
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct FpsCameraRig {
+    /// Character to attach to, defaults to the first one driven by devices
+    pub target: Option<Entity>,
+    /// Radians
+    pub pitch: f32,
+    /// Degrees
+    pub max_pitch: f32,
+    attached: Option<Entity>,
+}
+
+impl Default for FpsCameraRig {
+    fn default() -> Self {
+        FpsCameraRig {
+            target: None,
+            pitch: 0.0,
+            max_pitch: 89.0,
+            attached: None,
+        }
+    }
+}
+
+impl FpsCameraRig {
+    /// Character the camera currently sits in
+    pub fn attached(&self) -> Option<Entity> {
+        self.attached
+    }
+}
+
+/// Bobs the first person camera while the character walks, on top of its eye position
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct HeadBob {
+    /// Vertical offset at base speed, in meters
+    pub amplitude: f32,
+    /// Steps per second at base speed
+    pub frequency: f32,
+    /// How fast the bob fades in and out, per second
+    pub blend_speed: f32,
+    phase: f32,
+    weight: f32,
+    applied: Vec3,
+}
+
+impl Default for HeadBob {
+    fn default() -> Self {
+        HeadBob {
+            amplitude: 0.05,
+            frequency: 1.8,
+            blend_speed: 6.0,
+            phase: 0.0,
+            weight: 0.0,
+            applied: Vec3::ZERO,
+        }
+    }
+}
+
+/// Global head bob switch, for players prone to motion sickness
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HeadBobSettings {
+    pub enabled: bool,
+}
+
+impl Default for HeadBobSettings {
+    fn default() -> Self {
+        HeadBobSettings { enabled: true }
+    }
+}
+
+/// Orbits a character from behind while in `CameraState::ThirdPerson`
+///
+/// The orbit center and distance live in `DebugCamera::focus` and `DebugCamera::radius`.
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct ThirdPersonCameraRig {
+    /// Character to follow, defaults to the first one driven by devices
+    pub target: Option<Entity>,
+    /// Orbit center above the character origin
+    pub height: f32,
+    /// Sideways offset of the camera, positive over the right shoulder
+    pub shoulder: f32,
+    pub min_radius: f32,
+    pub max_radius: f32,
+    /// How fast the orbit center catches up with the character, per second
+    pub follow_speed: f32,
+    /// Radians
+    pub yaw: f32,
+    /// Radians
+    pub pitch: f32,
+    /// Degrees
+    pub max_pitch: f32,
+    attached: Option<Entity>,
+}
+
+impl Default for ThirdPersonCameraRig {
+    fn default() -> Self {
+        ThirdPersonCameraRig {
+            target: None,
+            height: 1.5,
+            shoulder: 0.75,
+            min_radius: 2.0,
+            max_radius: 15.0,
+            follow_speed: 12.0,
+            yaw: 0.0,
+            pitch: 0.0,
+            max_pitch: 80.0,
+            attached: None,
+        }
+    }
+}
+
+impl ThirdPersonCameraRig {
+    /// Character the camera currently follows
+    pub fn attached(&self) -> Option<Entity> {
+        self.attached
+    }
+
+    fn rotation(&self) -> Quat {
+        Quat::from_euler(EulerRot::YXZ, self.yaw, self.pitch, 0.0)
+    }
+}
+
+/// Preferred character if it exists, otherwise the first candidate
+fn rig_target(
+    preferred: Option<Entity>,
+    mut characters: impl Iterator<Item = Entity>,
+) -> Option<Entity> {
+    match preferred {
+        Some(preferred) => characters.find(|character| *character == preferred),
+        None => characters.next(),
+    }
+}
+
+pub fn attach_fps_rig(
+    mut commands: Commands,
+    mut cameras: Query<(Entity, &mut FpsCameraRig, &GlobalTransform), With<ControlledCamera>>,
+    mut characters: Query<
+        (
+            Entity,
+            &mut Transform,
+            &CharacterMovementController,
+            &CharacterHeight,
+        ),
+        Without<CharacterInputDriver>,
+    >,
+) {
+    for (camera, mut rig, global) in cameras.iter_mut() {
+        let target = rig_target(rig.target, characters.iter().map(|(entity, ..)| entity));
+        let Some(target) = target else {
+            warn!("No character to attach the fps camera to");
+            continue;
+        };
+        let (_, mut body, character, height) = characters.get_mut(target).unwrap();
+
+        // Carry the current view over so entering first person doesn't snap
+        let (yaw, pitch, _) = global.compute_transform().rotation.to_euler(EulerRot::YXZ);
+        let max_pitch = rig.max_pitch.to_radians();
+        body.rotation = Quat::from_rotation_y(yaw);
+        rig.pitch = pitch.clamp(-max_pitch, max_pitch);
+        rig.attached = Some(target);
+
+        let eye = Vec3::Y * character.settings().eye_height(height.current());
+        let rotation = Quat::from_rotation_x(rig.pitch);
+        commands.entity(target).add_child(camera);
+        commands
+            .entity(camera)
+            .insert(Transform::from_translation(eye).with_rotation(rotation))
+            .insert(CameraGoal(
+                Transform::from_translation(eye).with_rotation(rotation),
+            ))
+            .insert(CharacterVisual { offset: eye });
+    }
+}
+
+pub fn update_fps_rig(
+    mut cameras: Query<
+        (
+            &mut FpsCameraRig,
+            &mut CameraGoal,
+            &mut CharacterVisual,
+            &DebugCamera,
+            &LookInput,
+        ),
+        With<ControlledCamera>,
+    >,
+    mut characters: Query<
+        (
+            &mut Transform,
+            &CharacterMovementController,
+            &CharacterHeight,
+        ),
+        Without<FpsCameraRig>,
+    >,
+) {
+    for (mut rig, mut goal, mut visual, camera, look) in cameras.iter_mut() {
+        let Some(target) = rig.attached else { continue };
+        let Ok((mut body, character, height)) = characters.get_mut(target) else {
+            continue;
+        };
+        let motion = look.delta();
+
+        body.rotation = Quat::from_rotation_y(-motion.x * camera.look_sens()) * body.rotation;
+
+        let max_pitch = rig.max_pitch.to_radians();
+        rig.pitch = (rig.pitch - motion.y * camera.look_sens()).clamp(-max_pitch, max_pitch);
+        goal.rotation = Quat::from_rotation_x(rig.pitch);
+
+        // Follow the capsule down while crouching
+        visual.offset = Vec3::Y * character.settings().eye_height(height.current());
+    }
+}
+
+pub fn detach_fps_rig(
+    mut commands: Commands,
+    mut cameras: Query<(
+        Entity,
+        &mut FpsCameraRig,
+        &mut DebugCamera,
+        &GlobalTransform,
+    )>,
+) {
+    for (entity, mut rig, mut camera, global) in cameras.iter_mut() {
+        let Some(target) = rig.attached.take() else {
+            continue;
+        };
+
+        if let Some(mut character) = commands.get_entity(target) {
+            character.remove_children(&[entity]);
+        }
+        let transform = global.compute_transform();
+        camera.set_rotation(transform.rotation);
+        commands
+            .entity(entity)
+            .remove::<CharacterVisual>()
+            .insert(transform)
+            .insert(CameraGoal(transform));
+    }
+}
+
+pub fn attach_third_person_rig(
+    mut cameras: Query<
+        (
+            &mut ThirdPersonCameraRig,
+            &mut DebugCamera,
+            &GlobalTransform,
+        ),
+        With<ControlledCamera>,
+    >,
+    characters: Query<
+        (Entity, &GlobalTransform),
+        (
+            With<CharacterMovementController>,
+            Without<CharacterInputDriver>,
+        ),
+    >,
+) {
+    for (mut rig, mut camera, global) in cameras.iter_mut() {
+        let target = rig_target(rig.target, characters.iter().map(|(entity, _)| entity));
+        let Some(target) = target else {
+            warn!("No character for the third person camera to follow");
+            continue;
+        };
+        let (_, character) = characters.get(target).unwrap();
+
+        // Keep looking the same way the camera did
+        let (yaw, pitch, _) = global.compute_transform().rotation.to_euler(EulerRot::YXZ);
+        let max_pitch = rig.max_pitch.to_radians();
+        rig.yaw = yaw;
+        rig.pitch = pitch.clamp(-max_pitch, max_pitch);
+        rig.attached = Some(target);
+
+        camera.focus = character.translation() + Vec3::Y * rig.height;
+        camera.radius = camera.radius.clamp(rig.min_radius, rig.max_radius);
+    }
+}
+
+pub fn update_third_person_rig(
+    mut cameras: Query<
+        (
+            &mut ThirdPersonCameraRig,
+            &mut DebugCamera,
+            &mut CameraGoal,
+            &LookInput,
+            &ActionState<CameraAction>,
+        ),
+        With<ControlledCamera>,
+    >,
+    mut characters: Query<(&mut Transform, &GlobalTransform), With<CharacterMovementController>>,
+    time: Res<Time>,
+) {
+    for (mut rig, mut camera, mut goal, look, actions) in cameras.iter_mut() {
+        let Some(target) = rig.attached else { continue };
+        let Ok((mut body, character)) = characters.get_mut(target) else {
+            continue;
+        };
+        let motion = look.delta();
+        // Bindings without a scroll axis leave the zoom unset
+        let zoom = actions
+            .axis_pair(CameraAction::Zoom)
+            .map_or(0.0, |zoom| zoom.y());
+
+        let max_pitch = rig.max_pitch.to_radians();
+        rig.yaw -= motion.x * camera.look_sens();
+        rig.pitch = (rig.pitch - motion.y * camera.look_sens()).clamp(-max_pitch, max_pitch);
+
+        // Zoom by a fraction of the distance so it feels the same close up and far away
+        let radius = camera.radius * (1.0 - zoom * camera.zoom_sens());
+        camera.radius = radius.clamp(rig.min_radius, rig.max_radius);
+
+        // Move relative to where the camera looks
+        body.rotation = Quat::from_rotation_y(rig.yaw);
+
+        // Frame-rate independent exponential approach to the character
+        let t = 1.0 - (-rig.follow_speed * time.delta_seconds()).exp();
+        let center = character.translation() + Vec3::Y * rig.height;
+        camera.focus = camera.focus.lerp(center, t);
+
+        let rotation = rig.rotation();
+        goal.rotation = rotation;
+        goal.translation = camera.focus + rotation * Vec3::new(rig.shoulder, 0.0, camera.radius);
+    }
+}
+
+pub fn detach_third_person_rig(mut cameras: Query<(&mut ThirdPersonCameraRig, &mut DebugCamera)>) {
+    for (mut rig, mut camera) in cameras.iter_mut() {
+        rig.attached = None;
+        camera.yaw = rig.yaw;
+        camera.pitch = rig.pitch;
+    }
+}
+
+/// Takes last frame's bob back out so it never accumulates into the eye position
+pub fn remove_head_bob(mut q: Query<(&mut Transform, &mut HeadBob)>) {
+    for (mut transform, mut bob) in q.iter_mut() {
+        transform.translation -= bob.applied;
+        bob.applied = Vec3::ZERO;
+    }
+}
+
+pub fn apply_head_bob(
+    mut cameras: Query<(&mut Transform, &mut HeadBob, &FpsCameraRig)>,
+    characters: Query<(
+        &CharacterState,
+        &CharacterSpeed,
+        &CharacterMovementController,
+    )>,
+    settings: Res<HeadBobSettings>,
+    time: Res<SimulationTime>,
+) {
+    let dt = time.delta_seconds();
+
+    for (mut transform, mut bob, rig) in cameras.iter_mut() {
+        let character = rig
+            .attached()
+            .and_then(|target| characters.get(target).ok());
+
+        // Relative to base speed, so running bobs harder and faster than crouching
+        let intensity = match character {
+            Some((state, speed, character)) if settings.enabled => match state {
+                CharacterState::Walk | CharacterState::Run | CharacterState::Crouch => {
+                    speed.get() / character.settings().speed.base
+                }
+                _ => 0.0,
+            },
+            _ => 0.0,
+        };
+
+        let t = 1.0 - (-bob.blend_speed * dt).exp();
+        bob.weight += (intensity - bob.weight) * t;
+        if bob.weight < 0.001 {
+            bob.phase = 0.0;
+            continue;
+        }
+
+        bob.phase += bob.frequency * bob.weight * dt;
+        let angle = bob.phase * std::f32::consts::TAU;
+        let amplitude = bob.amplitude * bob.weight;
+
+        // Figure eight: two vertical dips per sideways sway
+        let offset = Vec3::new(
+            (angle / 2.).cos() * amplitude * 0.5,
+            angle.sin().abs() * -amplitude,
+            0.0,
+        );
+        transform.translation += offset;
+        bob.applied = offset;
+    }
+}
@@ -0,0 +1,144 @@
+use crate::prelude::*;
+use bevy::prelude::*;
+use leafwing_input_manager::prelude::*;
+
+/// Collects controller vibration requests from character events and `Rumble` events
+///
+/// This is only an event sink, nothing vibrates with the plugin alone: Bevy 0.9 can't drive
+/// force feedback motors. The strengths end up in `GamepadRumble`, which the app has to play
+/// through a platform backend of its own.
+pub struct RumblePlugin;
+
+impl Plugin for RumblePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RumbleSettings>()
+            .init_resource::<GamepadRumble>()
+            .add_event::<Rumble>()
+            .add_system(rumble_on_character_events)
+            .add_system(update_gamepad_rumble.after(rumble_on_character_events));
+    }
+}
+
+#[derive(Resource, Debug, Clone, Copy, PartialEq)]
+pub struct RumbleSettings {
+    pub enabled: bool,
+    /// Multiplier on every effect
+    pub intensity: f32,
+}
+
+impl Default for RumbleSettings {
+    fn default() -> Self {
+        RumbleSettings {
+            enabled: true,
+            intensity: 1.0,
+        }
+    }
+}
+
+/// Vibrates a gamepad for a while, every gamepad when `gamepad` is `None`
+///
+/// Sent for character events by the plugin. No other mechanic sends any, games can add their own.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rumble {
+    pub gamepad: Option<Gamepad>,
+    /// Low frequency motor, `0..=1`
+    pub strong: f32,
+    /// High frequency motor, `0..=1`
+    pub weak: f32,
+    /// Seconds
+    pub duration: f32,
+}
+
+impl Rumble {
+    pub fn new(strong: f32, weak: f32, duration: f32) -> Self {
+        Rumble {
+            gamepad: None,
+            strong,
+            weak,
+            duration,
+        }
+    }
+
+    pub fn for_gamepad(mut self, gamepad: Option<Gamepad>) -> Self {
+        self.gamepad = gamepad;
+        self
+    }
+}
+
+/// Effects that would be playing, strongest one wins per motor, read by a backend of the app
+#[derive(Resource, Debug, Default, Clone)]
+pub struct GamepadRumble {
+    effects: Vec<(Rumble, f32)>,
+}
+
+impl GamepadRumble {
+    /// Strong and weak motor strength `gamepad` should vibrate with right now
+    pub fn motors(&self, gamepad: Gamepad) -> (f32, f32) {
+        self.effects
+            .iter()
+            .filter(|(rumble, _)| rumble.gamepad.map_or(true, |target| target == gamepad))
+            .fold((0.0, 0.0), |(strong, weak), (rumble, _)| {
+                (strong.max(rumble.strong), weak.max(rumble.weak))
+            })
+    }
+}
+
+/// Landings, hard falls and slides of device controlled characters
+pub fn rumble_on_character_events(
+    mut changes: EventReader<CharacterStateChanged>,
+    mut rumble: EventWriter<Rumble>,
+    maps: Query<&InputMap<CharacterActions>>,
+) {
+    for change in changes.iter() {
+        // Scripted and AI characters have no input map and nobody holding a gamepad
+        let Ok(map) = maps.get(change.entity) else {
+            continue;
+        };
+        let gamepad = map.gamepad();
+
+        let grounded = change.to.group() == CharacterStateGroup::Grounded;
+        let effect = match (change.from, change.to) {
+            (CharacterState::Fall, _) if grounded && change.duration > 1.0 => {
+                Some(Rumble::new(0.8, 0.4, 0.35))
+            }
+            (CharacterState::Fall, _) if grounded && change.duration > 0.3 => {
+                Some(Rumble::new(0.3, 0.2, 0.15))
+            }
+            (_, CharacterState::Slide) => Some(Rumble::new(0.0, 0.35, 0.25)),
+            _ => None,
+        };
+
+        if let Some(effect) = effect {
+            rumble.send(effect.for_gamepad(gamepad));
+        }
+    }
+}
+
+pub fn update_gamepad_rumble(
+    mut events: EventReader<Rumble>,
+    mut rumble: ResMut<GamepadRumble>,
+    settings: Res<RumbleSettings>,
+    time: Res<Time>,
+) {
+    let dt = time.delta_seconds();
+    rumble.effects.retain_mut(|(_, remaining)| {
+        *remaining -= dt;
+        *remaining > 0.0
+    });
+
+    for event in events.iter() {
+        if !settings.enabled {
+            continue;
+        }
+        let scaled = Rumble {
+            strong: (event.strong * settings.intensity).clamp(0.0, 1.0),
+            weak: (event.weak * settings.intensity).clamp(0.0, 1.0),
+            ..*event
+        };
+        rumble.effects.push((scaled, event.duration));
+    }
+
+    if !settings.enabled {
+        rumble.effects.clear();
+    }
+}
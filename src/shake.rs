@@ -0,0 +1,146 @@
+use crate::prelude::*;
+use bevy::prelude::*;
+
+/// Adds trauma to a camera's shake, to every shaker when `camera` is `None`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CameraShake {
+    pub trauma: f32,
+    pub camera: Option<Entity>,
+}
+
+impl CameraShake {
+    pub fn new(trauma: f32) -> Self {
+        CameraShake {
+            trauma,
+            camera: None,
+        }
+    }
+}
+
+/// Trauma based camera shake, applied on top of the camera transform every frame
+///
+/// Shake strength is the square of the trauma, so small hits stay subtle while stacked ones
+/// ramp up quickly.
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct CameraShaker {
+    /// `0..=1`
+    pub trauma: f32,
+    /// Trauma lost per second
+    pub decay: f32,
+    /// Yaw, pitch and roll at full trauma, in degrees
+    pub max_angle: Vec3,
+    /// Local translation at full trauma
+    pub max_offset: Vec3,
+    /// Noise samples per second
+    pub frequency: f32,
+    time: f32,
+    applied: Option<(Vec3, Quat)>,
+}
+
+impl Default for CameraShaker {
+    fn default() -> Self {
+        CameraShaker {
+            trauma: 0.0,
+            decay: 1.0,
+            max_angle: Vec3::new(3.0, 3.0, 6.0),
+            max_offset: Vec3::new(0.1, 0.1, 0.0),
+            frequency: 15.0,
+            time: 0.0,
+            applied: None,
+        }
+    }
+}
+
+impl CameraShaker {
+    pub fn add_trauma(&mut self, trauma: f32) {
+        self.trauma = (self.trauma + trauma).clamp(0.0, 1.0);
+    }
+
+    pub fn shake(&self) -> f32 {
+        self.trauma * self.trauma
+    }
+}
+
+/// Smooth pseudo random signal in `-1..=1`, one channel per `seed`
+fn noise(seed: f32, t: f32) -> f32 {
+    let t = t + seed * 17.31;
+    t.sin() * 0.5 + (t * 2.17 + 1.3).sin() * 0.3 + (t * 4.73 + 2.9).sin() * 0.2
+}
+
+pub fn receive_camera_shake(
+    mut events: EventReader<CameraShake>,
+    mut q: Query<(Entity, &mut CameraShaker)>,
+) {
+    for event in events.iter() {
+        for (entity, mut shaker) in q.iter_mut() {
+            if event.camera.map_or(true, |camera| camera == entity) {
+                shaker.add_trauma(event.trauma);
+            }
+        }
+    }
+}
+
+/// Takes last frame's shake back out before anything reads or writes the transform
+pub fn remove_camera_shake(mut q: Query<(&mut Transform, &mut CameraShaker)>) {
+    for (mut transform, mut shaker) in q.iter_mut() {
+        let Some((translation, rotation)) = shaker.applied.take() else {
+            continue;
+        };
+        transform.translation -= translation;
+        transform.rotation *= rotation.inverse();
+    }
+}
+
+/// Shakes settle with the simulation, so a pause freezes them
+pub fn apply_camera_shake(
+    mut q: Query<(&mut Transform, &mut CameraShaker)>,
+    time: Res<SimulationTime>,
+) {
+    for (mut transform, mut shaker) in q.iter_mut() {
+        shaker.trauma = (shaker.trauma - shaker.decay * time.delta_seconds()).max(0.0);
+        if shaker.trauma == 0.0 {
+            continue;
+        }
+
+        shaker.time += time.delta_seconds() * shaker.frequency;
+        let (t, shake) = (shaker.time, shaker.shake());
+
+        let angle = shaker.max_angle * shake;
+        let rotation = Quat::from_euler(
+            EulerRot::YXZ,
+            (angle.x * noise(0.0, t)).to_radians(),
+            (angle.y * noise(1.0, t)).to_radians(),
+            (angle.z * noise(2.0, t)).to_radians(),
+        );
+        let offset = shaker.max_offset * shake;
+        let offset = Vec3::new(
+            offset.x * noise(3.0, t),
+            offset.y * noise(4.0, t),
+            offset.z * noise(5.0, t),
+        );
+        let translation = transform.rotation * offset;
+
+        transform.translation += translation;
+        transform.rotation *= rotation;
+        shaker.applied = Some((translation, rotation));
+    }
+}
+
+/// Shakes cameras when a character lands after a long fall
+pub fn shake_on_landing(
+    mut changes: EventReader<CharacterStateChanged>,
+    mut shakes: EventWriter<CameraShake>,
+) {
+    for change in changes.iter() {
+        let grounded = change.to.group() == CharacterStateGroup::Grounded;
+        if change.from != CharacterState::Fall || !grounded {
+            continue;
+        }
+
+        // Short hops shouldn't shake at all
+        let trauma = ((change.duration - 0.4) * 0.5).clamp(0.0, 0.6);
+        if trauma > 0.0 {
+            shakes.send(CameraShake::new(trauma));
+        }
+    }
+}
@@ -0,0 +1,247 @@
+use crate::prelude::*;
+use bevy::{
+    pbr::{NotShadowCaster, NotShadowReceiver},
+    prelude::*,
+    render::{render_resource::TextureFormat, view::NoFrustumCulling},
+    transform::TransformSystem,
+};
+use serde::{Deserialize, Serialize};
+use std::f32::consts::{FRAC_PI_2, PI};
+
+/// Distance of the sky from the camera, inside the default far plane
+const SKYBOX_RADIUS: f32 = 500.0;
+
+/// Draws `Skybox` entities around the active camera
+///
+/// Bevy 0.9 has neither a skybox nor image based lighting, so the sky is an unlit sphere or cube
+/// following the camera, and environment lighting tints the ambient light with its average color.
+pub struct SkyboxPlugin;
+
+impl Plugin for SkyboxPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(spawn_skyboxes)
+            .add_system(apply_environment_lighting)
+            .add_system_to_stage(
+                CoreStage::PostUpdate,
+                follow_camera.before(TransformSystem::TransformPropagate),
+            );
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SkyboxSource {
+    /// Equirectangular image, e.g. an `.hdr` panorama
+    Equirect(String),
+    /// Face images in `+X`, `-X`, `+Y`, `-Y`, `+Z`, `-Z` order, as seen from inside
+    Cubemap([String; 6]),
+}
+
+/// Sky drawn behind everything, put on an entity of its own or in level data
+#[derive(Component, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Skybox {
+    pub source: SkyboxSource,
+    /// Multiplies the sky color
+    #[serde(default = "default_brightness")]
+    pub brightness: f32,
+    /// Replaces the ambient light color with the average color of the sky
+    #[serde(default)]
+    pub environment_lighting: bool,
+}
+
+fn default_brightness() -> f32 {
+    1.0
+}
+
+impl Skybox {
+    pub fn equirect(path: impl Into<String>) -> Self {
+        Skybox {
+            source: SkyboxSource::Equirect(path.into()),
+            brightness: default_brightness(),
+            environment_lighting: false,
+        }
+    }
+
+    pub fn cubemap(faces: [String; 6]) -> Self {
+        Skybox {
+            source: SkyboxSource::Cubemap(faces),
+            brightness: default_brightness(),
+            environment_lighting: false,
+        }
+    }
+
+    pub fn with_brightness(mut self, brightness: f32) -> Self {
+        self.brightness = brightness;
+        self
+    }
+
+    pub fn with_environment_lighting(mut self, enabled: bool) -> Self {
+        self.environment_lighting = enabled;
+        self
+    }
+}
+
+/// Images of a spawned skybox, kept until they loaded for its environment lighting
+#[derive(Component, Debug, Default, Clone)]
+pub struct SkyboxImages {
+    images: Vec<Handle<Image>>,
+    lit: bool,
+}
+
+fn spawn_skyboxes(
+    mut commands: Commands,
+    skyboxes: Query<(Entity, &Skybox), Added<Skybox>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    asset_server: Res<AssetServer>,
+) {
+    for (entity, skybox) in skyboxes.iter() {
+        let brightness = skybox.brightness;
+        let mut material = |image: &Handle<Image>| {
+            materials.add(StandardMaterial {
+                base_color: Color::rgb_linear(brightness, brightness, brightness),
+                base_color_texture: Some(image.clone()),
+                unlit: true,
+                cull_mode: None,
+                ..Default::default()
+            })
+        };
+
+        // Faces of the sky, each a mesh with the image drawn on it
+        let faces: Vec<(Mesh, Handle<Image>, Transform)> = match &skybox.source {
+            SkyboxSource::Equirect(path) => {
+                let sphere = shape::UVSphere {
+                    radius: SKYBOX_RADIUS,
+                    sectors: 64,
+                    stacks: 32,
+                };
+                // Seen from inside the sphere the panorama would be mirrored
+                let transform = Transform::from_scale(Vec3::new(-1.0, 1.0, 1.0));
+                vec![(sphere.into(), asset_server.load(path.as_str()), transform)]
+            }
+            SkyboxSource::Cubemap(paths) => {
+                let r = SKYBOX_RADIUS;
+                let placements = [
+                    (Vec3::X * r, Quat::from_rotation_y(-FRAC_PI_2)),
+                    (Vec3::NEG_X * r, Quat::from_rotation_y(FRAC_PI_2)),
+                    (Vec3::Y * r, Quat::from_rotation_x(FRAC_PI_2)),
+                    (Vec3::NEG_Y * r, Quat::from_rotation_x(-FRAC_PI_2)),
+                    (Vec3::Z * r, Quat::from_rotation_y(PI)),
+                    (Vec3::NEG_Z * r, Quat::IDENTITY),
+                ];
+                paths
+                    .iter()
+                    .zip(placements)
+                    .map(|(path, (translation, rotation))| {
+                        let quad = shape::Quad::new(Vec2::splat(r * 2.0));
+                        let transform =
+                            Transform::from_translation(translation).with_rotation(rotation);
+                        (quad.into(), asset_server.load(path.as_str()), transform)
+                    })
+                    .collect()
+            }
+        };
+
+        let images = faces.iter().map(|(_, image, _)| image.clone()).collect();
+        commands
+            .entity(entity)
+            .insert(SpatialBundle::default())
+            .insert(SkyboxImages { images, lit: false })
+            .with_children(|sky| {
+                for (mesh, image, transform) in faces {
+                    sky.spawn((
+                        PbrBundle {
+                            mesh: meshes.add(mesh),
+                            material: material(&image),
+                            transform,
+                            ..Default::default()
+                        },
+                        NotShadowCaster,
+                        NotShadowReceiver,
+                        NoFrustumCulling,
+                    ));
+                }
+            });
+    }
+}
+
+/// Keeps skyboxes centered on the active camera, wherever they are in the hierarchy
+fn follow_camera(
+    mut skyboxes: Query<(&mut Transform, Option<&Parent>), With<Skybox>>,
+    cameras: Query<(&Camera, &GlobalTransform), With<Camera3d>>,
+    parents: Query<&GlobalTransform>,
+) {
+    let Some((_, camera)) = cameras.iter().find(|(camera, _)| is_main_camera(camera)) else {
+        return;
+    };
+    let center = Mat4::from_translation(camera.translation());
+
+    for (mut transform, parent) in skyboxes.iter_mut() {
+        let parent = parent.and_then(|parent| parents.get(parent.get()).ok());
+        *transform = match parent {
+            Some(parent) => Transform::from_matrix(parent.compute_matrix().inverse() * center),
+            None => Transform::from_matrix(center),
+        };
+    }
+}
+
+fn apply_environment_lighting(
+    mut skyboxes: Query<(&Skybox, &mut SkyboxImages)>,
+    mut ambient: ResMut<AmbientLight>,
+    images: Res<Assets<Image>>,
+) {
+    for (skybox, mut sky) in skyboxes.iter_mut() {
+        if sky.lit || !skybox.environment_lighting {
+            continue;
+        }
+
+        let loaded: Option<Vec<&Image>> =
+            sky.images.iter().map(|image| images.get(image)).collect();
+        let Some(loaded) = loaded else { continue };
+        sky.lit = true;
+
+        let colors: Vec<Vec3> = loaded.into_iter().filter_map(average_color).collect();
+        if colors.is_empty() {
+            warn!("Skybox images have no format environment lighting can read");
+            continue;
+        }
+
+        let average = colors.iter().sum::<Vec3>() / colors.len() as f32 * skybox.brightness;
+        ambient.color = Color::rgb_linear(average.x, average.y, average.z);
+    }
+}
+
+/// Average linear color of an image, sampling a few thousand pixels at most
+fn average_color(image: &Image) -> Option<Vec3> {
+    let pixel_size = match image.texture_descriptor.format {
+        TextureFormat::Rgba8UnormSrgb | TextureFormat::Rgba8Unorm => 4,
+        TextureFormat::Rgba32Float => 16,
+        _ => return None,
+    };
+    let pixels = image.data.len() / pixel_size;
+    let stride = (pixels / 4096).max(1);
+
+    let mut sum = Vec3::ZERO;
+    let mut count = 0;
+    for pixel in image.data.chunks_exact(pixel_size).step_by(stride) {
+        let color = match image.texture_descriptor.format {
+            TextureFormat::Rgba8UnormSrgb => {
+                let srgb = Color::rgb_u8(pixel[0], pixel[1], pixel[2]);
+                let [r, g, b, _] = srgb.as_linear_rgba_f32();
+                Vec3::new(r, g, b)
+            }
+            TextureFormat::Rgba8Unorm => {
+                Vec3::new(pixel[0] as f32, pixel[1] as f32, pixel[2] as f32) / 255.0
+            }
+            _ => {
+                let channel = |i: usize| {
+                    f32::from_le_bytes([pixel[i], pixel[i + 1], pixel[i + 2], pixel[i + 3]])
+                };
+                Vec3::new(channel(0), channel(4), channel(8))
+            }
+        };
+        sum += color;
+        count += 1;
+    }
+
+    (count > 0).then(|| sum / count as f32)
+}
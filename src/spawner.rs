@@ -0,0 +1,190 @@
+use bevy::{ecs::system::Command, prelude::*};
+use bevy_rapier3d::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Spawns primitive physics objects from `SpawnObject` events, e.g. for test scenarios
+///
+/// Without the plugin, `SpawnObject` can still be added to `Commands` directly.
+pub struct ObjectSpawnerPlugin;
+
+impl Plugin for ObjectSpawnerPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<SpawnObject>().add_system(spawn_objects);
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PrimitiveShape {
+    #[default]
+    Box,
+    /// Ball with a diameter of the size along `X`
+    Sphere,
+    /// Slab rising by the size along `Y` over the size along `Z`
+    Ramp,
+    /// Fixed box, thin along `Z`
+    Wall,
+}
+
+impl PrimitiveShape {
+    pub fn default_size(&self) -> Vec3 {
+        match self {
+            PrimitiveShape::Box | PrimitiveShape::Sphere => Vec3::ONE,
+            PrimitiveShape::Ramp => Vec3::new(2.0, 1.0, 4.0),
+            PrimitiveShape::Wall => Vec3::new(4.0, 3.0, 0.3),
+        }
+    }
+
+    pub fn default_body(&self) -> RigidBody {
+        match self {
+            PrimitiveShape::Box | PrimitiveShape::Sphere => RigidBody::Dynamic,
+            PrimitiveShape::Ramp | PrimitiveShape::Wall => RigidBody::Fixed,
+        }
+    }
+}
+
+/// Object spawned by a `SpawnObject`
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SpawnedObject(pub PrimitiveShape);
+
+/// Primitive to spawn at `transform`, sent as an event or added to `Commands`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SpawnObject {
+    pub shape: PrimitiveShape,
+    pub transform: Transform,
+    /// Extents in meters, see `PrimitiveShape` for how each shape uses them
+    pub size: Vec3,
+    pub body: RigidBody,
+    pub color: Color,
+    pub friction: f32,
+    pub restitution: f32,
+    /// Kilograms per cubic meter of collider
+    pub density: f32,
+}
+
+impl SpawnObject {
+    pub fn new(shape: PrimitiveShape, transform: Transform) -> Self {
+        SpawnObject {
+            shape,
+            transform,
+            size: shape.default_size(),
+            body: shape.default_body(),
+            color: Color::rgb(0.8, 0.7, 0.6),
+            friction: 0.5,
+            restitution: 0.0,
+            density: 1.0,
+        }
+    }
+
+    /// Spawns `distance` meters along the view of a camera, turned to its heading
+    pub fn in_front_of(shape: PrimitiveShape, camera: &GlobalTransform, distance: f32) -> Self {
+        let translation = camera.translation() + camera.forward() * distance;
+        let transform = Transform::from_translation(translation);
+        let forward = (camera.forward() * Vec3::new(1., 0., 1.)).normalize_or_zero();
+
+        match forward == Vec3::ZERO {
+            true => SpawnObject::new(shape, transform),
+            false => SpawnObject::new(shape, transform.looking_at(translation + forward, Vec3::Y)),
+        }
+    }
+
+    pub fn with_size(mut self, size: Vec3) -> Self {
+        self.size = size;
+        self
+    }
+
+    pub fn with_body(mut self, body: RigidBody) -> Self {
+        self.body = body;
+        self
+    }
+
+    pub fn with_color(mut self, color: Color) -> Self {
+        self.color = color;
+        self
+    }
+
+    pub fn with_friction(mut self, friction: f32) -> Self {
+        self.friction = friction;
+        self
+    }
+
+    pub fn with_restitution(mut self, restitution: f32) -> Self {
+        self.restitution = restitution;
+        self
+    }
+
+    pub fn with_density(mut self, density: f32) -> Self {
+        self.density = density;
+        self
+    }
+
+    /// Mesh, collider and local transform of the shape
+    fn geometry(&self) -> (Mesh, Collider, Transform) {
+        let size = self.size;
+        match self.shape {
+            PrimitiveShape::Box | PrimitiveShape::Wall => (
+                Mesh::from(shape::Box::new(size.x, size.y, size.z)),
+                Collider::cuboid(size.x / 2.0, size.y / 2.0, size.z / 2.0),
+                Transform::IDENTITY,
+            ),
+            PrimitiveShape::Sphere => (
+                Mesh::from(shape::UVSphere {
+                    radius: size.x / 2.0,
+                    ..Default::default()
+                }),
+                Collider::ball(size.x / 2.0),
+                Transform::IDENTITY,
+            ),
+            PrimitiveShape::Ramp => {
+                // Tilted to rise towards `-Z`, its low end at the height of the spawn point
+                const THICKNESS: f32 = 0.2;
+                let length = Vec2::new(size.y, size.z).length();
+                let angle = size.y.atan2(size.z);
+                let local = Transform::from_xyz(0.0, size.y / 2.0, 0.0)
+                    .with_rotation(Quat::from_rotation_x(angle));
+                (
+                    Mesh::from(shape::Box::new(size.x, THICKNESS, length)),
+                    Collider::cuboid(size.x / 2.0, THICKNESS / 2.0, length / 2.0),
+                    local,
+                )
+            }
+        }
+    }
+
+    /// Spawns the object right away, for other commands building on it
+    pub fn spawn(self, world: &mut World) -> Entity {
+        let (mesh, collider, local) = self.geometry();
+        let mesh = world.resource_mut::<Assets<Mesh>>().add(mesh);
+        let material = world
+            .resource_mut::<Assets<StandardMaterial>>()
+            .add(StandardMaterial::from(self.color));
+
+        world
+            .spawn((
+                PbrBundle {
+                    mesh,
+                    material,
+                    transform: self.transform * local,
+                    ..Default::default()
+                },
+                self.body,
+                collider,
+                Friction::coefficient(self.friction),
+                Restitution::coefficient(self.restitution),
+                ColliderMassProperties::Density(self.density),
+                SpawnedObject(self.shape),
+            ))
+            .id()
+    }
+}
+
+impl Command for SpawnObject {
+    fn write(self, world: &mut World) {
+        self.spawn(world);
+    }
+}
+
+fn spawn_objects(mut commands: Commands, mut events: EventReader<SpawnObject>) {
+    for event in events.iter() {
+        commands.add(*event);
+    }
+}
@@ -0,0 +1,106 @@
+use crate::prelude::*;
+use bevy::prelude::*;
+use leafwing_input_manager::{plugin::ToggleActions, prelude::*};
+
+/// View the camera had before spectating, returned to when toggled off
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq)]
+pub struct Spectating {
+    previous: Option<(CameraState, Transform)>,
+}
+
+impl Spectating {
+    pub fn active(&self) -> bool {
+        self.previous.is_some()
+    }
+}
+
+/// Toggles between the current view and a free camera that leaves the characters alone
+pub fn toggle_spectator(
+    mut q: Query<
+        (
+            &mut Transform,
+            &mut CameraGoal,
+            &mut DebugCamera,
+            &ActionState<CameraAction>,
+        ),
+        With<ControlledCamera>,
+    >,
+    mut spectating: ResMut<Spectating>,
+    mut state: ResMut<State<CameraState>>,
+) {
+    let Ok((mut transform, mut goal, mut camera, actions)) = q.get_single_mut() else {
+        return;
+    };
+    if !actions.just_pressed(CameraAction::SpectatorToggle) {
+        return;
+    }
+
+    match *state.current() {
+        CameraState::Spectator => {
+            let (previous, view) = spectating.previous.take().unwrap_or_default();
+
+            // Character views put the camera back on the character themselves
+            *transform = view;
+            goal.0 = view;
+            camera.set_rotation(view.rotation);
+            state.set(previous).unwrap_or_default();
+        }
+        CameraState::Cinematic => {}
+        current => {
+            spectating.previous = Some((current, goal.0));
+            state.set(CameraState::Spectator).unwrap_or_default();
+        }
+    }
+}
+
+/// Stops devices from moving characters, leaving scripted and AI characters running
+pub fn freeze_player_input(
+    mut characters: Query<
+        (
+            &mut ActionState<CharacterMovement>,
+            &mut ActionState<CharacterActions>,
+        ),
+        Without<CharacterInputDriver>,
+    >,
+    movement: Option<ResMut<ToggleActions<CharacterMovement>>>,
+    actions: Option<ResMut<ToggleActions<CharacterActions>>>,
+    mut messages: EventWriter<HudMessage>,
+) {
+    set_player_input(movement, actions, false);
+
+    // Keys held while toggling would otherwise stay pressed
+    for (mut movement, mut actions) in characters.iter_mut() {
+        movement.release_all();
+        actions.release_all();
+    }
+
+    messages.send(HudMessage::new("Spectating"));
+}
+
+pub fn unfreeze_player_input(
+    movement: Option<ResMut<ToggleActions<CharacterMovement>>>,
+    actions: Option<ResMut<ToggleActions<CharacterActions>>>,
+    mut spectating: ResMut<Spectating>,
+    context: Option<Res<State<InputContext>>>,
+) {
+    // A menu opened while spectating keeps the player frozen
+    let enabled = context.map_or(true, |context| context.current().character_input());
+    set_player_input(movement, actions, enabled);
+
+    // Left through another toggle, there is no view to return to anymore
+    spectating.previous = None;
+}
+
+/// Character input only exists alongside the character controller
+fn set_player_input(
+    movement: Option<ResMut<ToggleActions<CharacterMovement>>>,
+    actions: Option<ResMut<ToggleActions<CharacterActions>>>,
+    enabled: bool,
+) {
+    if let Some(mut movement) = movement {
+        movement.enabled = enabled;
+    }
+    if let Some(mut actions) = actions {
+        actions.enabled = enabled;
+    }
+}
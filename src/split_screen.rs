@@ -0,0 +1,188 @@
+use crate::prelude::*;
+use bevy::{core_pipeline::clear_color::ClearColorConfig, prelude::*, render::camera::Viewport};
+use leafwing_input_manager::prelude::*;
+
+/// Splits the primary window between local players, each with a character and keymap
+///
+/// A character is spawned per keymap, so the default player is usually disabled alongside.
+pub struct SplitScreenPlugin {
+    keymaps: Vec<CharacterKeymap>,
+    spacing: f32,
+}
+
+impl Default for SplitScreenPlugin {
+    fn default() -> Self {
+        SplitScreenPlugin {
+            keymaps: vec![CharacterKeymap::default(), CharacterKeymap::second_player()],
+            spacing: 4.0,
+        }
+    }
+}
+
+impl SplitScreenPlugin {
+    /// One player per keymap, from left to right
+    pub fn with_players(mut self, keymaps: Vec<CharacterKeymap>) -> Self {
+        self.keymaps = keymaps;
+        self
+    }
+
+    /// Distance between the players' spawn points
+    pub fn with_spacing(mut self, spacing: f32) -> Self {
+        self.spacing = spacing;
+        self
+    }
+}
+
+impl Plugin for SplitScreenPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(SplitScreen {
+            enabled: true,
+            keymaps: self.keymaps.clone(),
+            spacing: self.spacing,
+        })
+        .add_startup_system(spawn_split_screen)
+        .add_system_to_stage(CoreStage::PreUpdate, update_split_viewports)
+        .add_system(follow_split_screen);
+    }
+}
+
+/// Split-screen mode, while disabled the active camera renders as usual
+#[derive(Resource, Debug, Clone)]
+pub struct SplitScreen {
+    pub enabled: bool,
+    keymaps: Vec<CharacterKeymap>,
+    spacing: f32,
+}
+
+/// Camera showing one player's part of the window
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct SplitScreenView {
+    pub player: Entity,
+    /// Position from the left edge of the window
+    pub index: usize,
+    /// Camera position relative to the player
+    pub offset: Vec3,
+    /// How fast the camera catches up with the player, per second
+    pub follow_speed: f32,
+}
+
+impl CharacterKeymap {
+    /// Arrow keys and the right hand modifiers, sharing a keyboard with the default keymap
+    pub fn second_player() -> Self {
+        CharacterKeymap {
+            movement: InputMap::default()
+                .insert(KeyCode::Up, CharacterMovement::Forward)
+                .insert(KeyCode::Left, CharacterMovement::Left)
+                .insert(KeyCode::Down, CharacterMovement::Back)
+                .insert(KeyCode::Right, CharacterMovement::Right)
+                .build(),
+            actions: InputMap::default()
+                .insert(KeyCode::Return, CharacterActions::Jump)
+                .insert(KeyCode::RControl, CharacterActions::Crouch)
+                .insert(KeyCode::RShift, CharacterActions::Sprint)
+                .build(),
+        }
+    }
+}
+
+fn spawn_split_screen(
+    mut commands: Commands,
+    split: Res<SplitScreen>,
+    settings: Res<CharacterSettings>,
+) {
+    let count = split.keymaps.len();
+
+    for (index, keymap) in split.keymaps.iter().enumerate() {
+        let x = (index as f32 - (count - 1) as f32 / 2.) * split.spacing;
+        let spawn = Transform::from_xyz(x, 4.0, 0.0);
+        let player = commands
+            .spawn(CharacterBundle::new(&settings, keymap, spawn))
+            .id();
+
+        let offset = Vec3::new(0.0, 4.0, 8.0);
+        let transform = Transform::from_translation(spawn.translation + offset)
+            .looking_at(spawn.translation, Vec3::Y);
+
+        // Every view after the first draws over the ones before it
+        let clear_color = match index {
+            0 => ClearColorConfig::Default,
+            _ => ClearColorConfig::None,
+        };
+
+        commands
+            .spawn(Camera3dBundle {
+                transform,
+                camera: Camera {
+                    priority: index as isize + 1,
+                    is_active: false,
+                    ..Default::default()
+                },
+                camera_3d: Camera3d {
+                    clear_color,
+                    ..Default::default()
+                },
+                ..Default::default()
+            })
+            .insert(SplitScreenView {
+                player,
+                index,
+                offset,
+                follow_speed: 8.0,
+            });
+    }
+}
+
+/// Divides the window into side by side strips, following window resizes
+fn update_split_viewports(
+    mut q: Query<(&mut Camera, &SplitScreenView)>,
+    split: Res<SplitScreen>,
+    windows: Res<Windows>,
+) {
+    let Some(window) = windows.get_primary() else {
+        return;
+    };
+    let (width, height) = (window.physical_width(), window.physical_height());
+    let count = split.keymaps.len().max(1) as u32;
+
+    for (mut camera, view) in q.iter_mut() {
+        // Minimized windows have nothing to split
+        let active = split.enabled && width >= count && height > 0;
+        if camera.is_active != active {
+            camera.is_active = active;
+        }
+        if !active {
+            continue;
+        }
+
+        let strip = width / count;
+        let position = UVec2::new(strip * view.index as u32, 0);
+        let size = UVec2::new(strip, height);
+        let current = camera.viewport.as_ref();
+        if current.map(|viewport| (viewport.physical_position, viewport.physical_size))
+            != Some((position, size))
+        {
+            camera.viewport = Some(Viewport {
+                physical_position: position,
+                physical_size: size,
+                ..Default::default()
+            });
+        }
+    }
+}
+
+fn follow_split_screen(
+    mut cameras: Query<(&mut Transform, &SplitScreenView)>,
+    players: Query<&GlobalTransform, With<CharacterMovementController>>,
+    time: Res<Time>,
+) {
+    for (mut transform, view) in cameras.iter_mut() {
+        let Ok(player) = players.get(view.player) else {
+            continue;
+        };
+        let target = player.translation();
+
+        let t = 1.0 - (-view.follow_speed * time.delta_seconds()).exp();
+        transform.translation = transform.translation.lerp(target + view.offset, t);
+        transform.look_at(target, Vec3::Y);
+    }
+}
@@ -0,0 +1,149 @@
+use crate::prelude::*;
+use bevy::{ecs::system::SystemParam, prelude::*};
+use bevy_rapier3d::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::f32::consts::FRAC_PI_2;
+
+/// Applies `Surface` friction to colliders and keeps characters from sliding on `no_slide` ground
+///
+/// Bevy rapier already keeps the entity in the `user_data` of every collider, so surfaces are
+/// components, found through the collider entities that ground probes and contacts report.
+pub struct SurfacePlugin;
+
+impl Plugin for SurfacePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(setup_surfaces)
+            .add_system(apply_ground_surface.after(update_grounded));
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum SurfaceKind {
+    #[default]
+    Default,
+    Stone,
+    Wood,
+    Metal,
+    Dirt,
+    Grass,
+    Sand,
+    Ice,
+    Water,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum FrictionClass {
+    Slippery,
+    #[default]
+    Normal,
+    Grippy,
+}
+
+impl FrictionClass {
+    pub fn coefficient(&self) -> f32 {
+        match self {
+            FrictionClass::Slippery => 0.05,
+            FrictionClass::Normal => 0.5,
+            FrictionClass::Grippy => 1.0,
+        }
+    }
+}
+
+/// What a collider is made of, on the collider entity or on its rigid body
+#[derive(Component, Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Surface {
+    pub kind: SurfaceKind,
+    /// Asset path of the sound played when stepping on it, if not the default of its kind
+    pub footstep: Option<String>,
+    /// Sets the rapier `Friction` of the collider unless it has one
+    pub friction: FrictionClass,
+    /// Characters stand on any slope of it rather than sliding down
+    pub no_slide: bool,
+}
+
+impl Surface {
+    pub fn new(kind: SurfaceKind) -> Self {
+        Surface {
+            kind,
+            ..Default::default()
+        }
+    }
+
+    pub fn with_footstep(mut self, path: impl Into<String>) -> Self {
+        self.footstep = Some(path.into());
+        self
+    }
+
+    pub fn with_friction(mut self, friction: FrictionClass) -> Self {
+        self.friction = friction;
+        self
+    }
+
+    pub fn no_slide(mut self) -> Self {
+        self.no_slide = true;
+        self
+    }
+}
+
+/// Looks up the `Surface` of colliders hit by ray casts, shape casts and contacts
+#[derive(SystemParam)]
+pub struct Surfaces<'w, 's> {
+    surfaces: Query<'w, 's, &'static Surface>,
+    rapier: Res<'w, RapierContext>,
+}
+
+impl<'w, 's> Surfaces<'w, 's> {
+    /// Surface of the collider, falling back to the one of its rigid body
+    pub fn get(&self, collider: Entity) -> Option<&Surface> {
+        self.surfaces.get(collider).ok().or_else(|| {
+            let body = self.rapier.collider_parent(collider)?;
+            self.surfaces.get(body).ok()
+        })
+    }
+
+    /// Surface a character stands on
+    pub fn ground(&self, grounded: &Grounded) -> Option<&Surface> {
+        let ground = grounded.ground().filter(|_| grounded.get())?;
+        self.get(ground)
+    }
+
+    /// Kind of the collider's surface, `Default` without one
+    pub fn kind(&self, collider: Entity) -> SurfaceKind {
+        self.get(collider)
+            .map_or(SurfaceKind::Default, |surface| surface.kind)
+    }
+}
+
+fn setup_surfaces(
+    mut commands: Commands,
+    q: Query<(Entity, &Surface), (Added<Surface>, Without<Friction>)>,
+) {
+    for (entity, surface) in q.iter() {
+        let friction = Friction::coefficient(surface.friction.coefficient());
+        commands.entity(entity).insert(friction);
+    }
+}
+
+pub fn apply_ground_surface(
+    mut characters: Query<(
+        &CharacterMovementController,
+        &mut KinematicCharacterController,
+        &Grounded,
+    )>,
+    surfaces: Surfaces,
+) {
+    for (character, mut controller, grounded) in characters.iter_mut() {
+        let no_slide = surfaces
+            .ground(grounded)
+            .map_or(false, |surface| surface.no_slide);
+        let angle = match no_slide {
+            true => FRAC_PI_2,
+            false => character.settings().min_slope_slide_angle.to_radians(),
+        };
+
+        if controller.min_slope_slide_angle != angle {
+            controller.min_slope_slide_angle = angle;
+        }
+    }
+}
@@ -0,0 +1,196 @@
+use crate::prelude::*;
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+/// Drives `Switch`es from pressure plates and buttons, telling their targets with `Activated`
+///
+/// Plates need the `TriggerPlugin` and buttons the `InteractionPlugin`.
+pub struct SwitchPlugin;
+
+impl Plugin for SwitchPlugin {
+    fn build(&self, app: &mut App) {
+        init_simulation_time(app);
+        app.add_event::<Activated>()
+            .add_system(setup_switches)
+            .add_system(update_pressure_plates.after(update_trigger_volumes))
+            .add_system(press_buttons.after(interact))
+            .add_system(release_buttons.after(press_buttons));
+    }
+}
+
+/// On or off state shared by plates and buttons, with the entities it drives
+#[derive(Component, Debug, Default, Clone, PartialEq, Eq)]
+pub struct Switch {
+    pub targets: Vec<Entity>,
+    active: bool,
+}
+
+impl Switch {
+    pub fn with_target(mut self, target: Entity) -> Self {
+        self.targets.push(target);
+        self
+    }
+
+    pub fn active(&self) -> bool {
+        self.active
+    }
+}
+
+/// A switch linked to `target` turned on or off
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Activated {
+    pub source: Entity,
+    pub target: Entity,
+    pub active: bool,
+}
+
+/// Sensor that is on while enough weight rests on it, from characters or other bodies
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct PressurePlate {
+    /// Combined mass that presses it, in kilograms
+    pub min_mass: f32,
+}
+
+impl Default for PressurePlate {
+    fn default() -> Self {
+        PressurePlate { min_mass: 10.0 }
+    }
+}
+
+/// Switch used with the `Interact` action
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct PushButton {
+    /// Flips on every use instead of turning itself off again
+    pub toggle: bool,
+    /// Seconds a non-toggle button stays on
+    pub duration: f32,
+    remaining: f32,
+}
+
+impl Default for PushButton {
+    fn default() -> Self {
+        PushButton {
+            toggle: false,
+            duration: 1.0,
+            remaining: 0.0,
+        }
+    }
+}
+
+impl PushButton {
+    pub fn toggle() -> Self {
+        PushButton {
+            toggle: true,
+            ..Default::default()
+        }
+    }
+}
+
+fn set_active(
+    source: Entity,
+    switch: &mut Switch,
+    active: bool,
+    events: &mut EventWriter<Activated>,
+) {
+    if switch.active == active {
+        return;
+    }
+    switch.active = active;
+
+    for &target in switch.targets.iter() {
+        events.send(Activated {
+            source,
+            target,
+            active,
+        });
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn setup_switches(
+    mut commands: Commands,
+    plates: Query<(Entity, Option<&Switch>, Option<&TriggerVolume>), Added<PressurePlate>>,
+    buttons: Query<(Entity, Option<&Switch>, Option<&Interactable>), Added<PushButton>>,
+) {
+    for (entity, switch, volume) in plates.iter() {
+        if switch.is_none() {
+            commands.entity(entity).insert(Switch::default());
+        }
+        if volume.is_none() {
+            let volume = TriggerVolume::new(TriggerFilter::RigidBodies);
+            commands.entity(entity).insert(volume);
+        }
+    }
+
+    for (entity, switch, interactable) in buttons.iter() {
+        if switch.is_none() {
+            commands.entity(entity).insert(Switch::default());
+        }
+        if interactable.is_none() {
+            commands.entity(entity).insert(Interactable::default());
+        }
+    }
+}
+
+pub fn update_pressure_plates(
+    mut plates: Query<(Entity, &PressurePlate, &TriggerVolume, &mut Switch)>,
+    characters: Query<&CharacterMovementController>,
+    mut events: EventWriter<Activated>,
+    rapier: Res<RapierContext>,
+) {
+    for (entity, plate, volume, mut switch) in plates.iter_mut() {
+        // Kinematic characters have no mass of their own in rapier
+        let weight: f32 = volume
+            .occupants()
+            .map(|occupant| match characters.get(occupant) {
+                Ok(character) => character.settings().mass,
+                Err(_) => rapier
+                    .entity2body()
+                    .get(&occupant)
+                    .and_then(|handle| rapier.bodies.get(*handle))
+                    .map_or(0.0, |body| body.mass()),
+            })
+            .sum();
+
+        let active = weight >= plate.min_mass;
+        if switch.active != active {
+            set_active(entity, &mut switch, active, &mut events);
+        }
+    }
+}
+
+pub fn press_buttons(
+    mut interactions: EventReader<Interacted>,
+    mut buttons: Query<(&mut PushButton, &mut Switch)>,
+    mut events: EventWriter<Activated>,
+) {
+    for Interacted { target, .. } in interactions.iter() {
+        let Ok((mut button, mut switch)) = buttons.get_mut(*target) else {
+            continue;
+        };
+
+        let active = match button.toggle {
+            true => !switch.active,
+            false => true,
+        };
+        button.remaining = button.duration;
+        set_active(*target, &mut switch, active, &mut events);
+    }
+}
+
+pub fn release_buttons(
+    mut buttons: Query<(Entity, &mut PushButton, &mut Switch)>,
+    mut events: EventWriter<Activated>,
+    time: Res<SimulationTime>,
+) {
+    for (entity, mut button, mut switch) in buttons.iter_mut() {
+        if button.toggle || !switch.active {
+            continue;
+        }
+
+        button.remaining -= time.delta_seconds();
+        if button.remaining <= 0.0 {
+            set_active(entity, &mut switch, false, &mut events);
+        }
+    }
+}
@@ -0,0 +1,138 @@
+use crate::prelude::*;
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use leafwing_input_manager::{plugin::InputManagerSystem, prelude::*};
+use serde::{Deserialize, Serialize};
+
+/// Slowest and fastest the simulation can be set to
+const MIN_SCALE: f32 = 1.0 / 16.0;
+const MAX_SCALE: f32 = 4.0;
+
+/// Speeds the whole game up or down through `TimeScale`, halved and doubled with `TimeScaleAction`s
+///
+/// The scale is applied to `Time`, so physics, character steps and camera motion all follow. With
+/// the `ConsolePlugin` it's also set with `timescale <scale>` or `timescale reset`.
+pub struct TimeScalePlugin {
+    map: InputMap<TimeScaleAction>,
+}
+
+impl Default for TimeScalePlugin {
+    fn default() -> Self {
+        TimeScalePlugin {
+            map: InputMap::new([
+                (KeyCode::LBracket, TimeScaleAction::SlowDown),
+                (KeyCode::RBracket, TimeScaleAction::SpeedUp),
+                (KeyCode::Backslash, TimeScaleAction::Reset),
+            ]),
+        }
+    }
+}
+
+impl TimeScalePlugin {
+    pub fn with_map(mut self, map: InputMap<TimeScaleAction>) -> Self {
+        self.map = map;
+        self
+    }
+}
+
+impl Plugin for TimeScalePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TimeScale>()
+            .insert_resource(self.map.clone())
+            .init_resource::<ActionState<TimeScaleAction>>()
+            .add_plugin(InputManagerPlugin::<TimeScaleAction>::default())
+            .add_system_to_stage(
+                CoreStage::PreUpdate,
+                change_time_scale.after(InputManagerSystem::Update),
+            )
+            .add_event::<ConsoleCommand>()
+            .add_system(time_scale_command)
+            .add_system_to_stage(
+                CoreStage::First,
+                apply_time_scale.after(bevy::time::TimeSystem),
+            );
+    }
+}
+
+#[derive(Actionlike, Clone, Debug, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TimeScaleAction {
+    /// Halves the time scale
+    SlowDown,
+    /// Doubles the time scale
+    SpeedUp,
+    /// Back to real time
+    Reset,
+}
+
+/// Rate at which the game runs compared to real time, below one for slow motion
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Deref, DerefMut)]
+pub struct TimeScale(pub f32);
+
+impl Default for TimeScale {
+    fn default() -> Self {
+        TimeScale(1.0)
+    }
+}
+
+fn change_time_scale(mut scale: ResMut<TimeScale>, actions: Res<ActionState<TimeScaleAction>>) {
+    let target = if actions.just_pressed(TimeScaleAction::SlowDown) {
+        scale.0 / 2.0
+    } else if actions.just_pressed(TimeScaleAction::SpeedUp) {
+        scale.0 * 2.0
+    } else if actions.just_pressed(TimeScaleAction::Reset) {
+        1.0
+    } else {
+        return;
+    };
+
+    scale.0 = target.clamp(MIN_SCALE, MAX_SCALE);
+    info!("Time scale {}", scale.0);
+}
+
+fn time_scale_command(mut commands: EventReader<ConsoleCommand>, mut scale: ResMut<TimeScale>) {
+    for command in commands.iter() {
+        if command.name != "timescale" {
+            continue;
+        }
+
+        let target = match command.args.first().map(String::as_str) {
+            Some("reset") => 1.0,
+            Some(arg) => match arg.parse::<f32>() {
+                Ok(target) if target.is_finite() => target,
+                _ => {
+                    warn!("Not a time scale: {arg}");
+                    continue;
+                }
+            },
+            None => {
+                info!("Time scale {}", scale.0);
+                continue;
+            }
+        };
+
+        scale.0 = target.clamp(MIN_SCALE, MAX_SCALE);
+        info!("Time scale {}", scale.0);
+    }
+}
+
+/// Scales `Time` and the length of fixed rapier steps, which don't follow it
+pub fn apply_time_scale(
+    scale: Res<TimeScale>,
+    mut time: ResMut<Time>,
+    mut rapier: ResMut<RapierConfiguration>,
+) {
+    let target = match scale.0.is_finite() {
+        true => scale.0.clamp(MIN_SCALE, MAX_SCALE),
+        false => 1.0,
+    };
+    let previous = time.relative_speed();
+    if target == previous {
+        return;
+    }
+
+    time.set_relative_speed(target);
+
+    if let TimestepMode::Fixed { dt, .. } = &mut rapier.timestep_mode {
+        *dt = *dt / previous * target;
+    }
+}
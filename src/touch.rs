@@ -0,0 +1,290 @@
+use crate::prelude::*;
+use bevy::{input::touch::Touch, prelude::*};
+use leafwing_input_manager::{
+    axislike::DualAxisData, buttonlike::ButtonState, plugin::InputManagerSystem, prelude::*,
+};
+
+/// On-screen controls for touch screens, feeding the same action states as other devices
+///
+/// Dragging on the left half of the screen moves with a stick centered on the first touch,
+/// dragging on the right half looks around, and the buttons in the bottom right corner jump
+/// and crouch.
+#[derive(Default)]
+pub struct TouchControlsPlugin {
+    controls: TouchControls,
+}
+
+impl TouchControlsPlugin {
+    pub fn with_controls(mut self, controls: TouchControls) -> Self {
+        self.controls = controls;
+        self
+    }
+}
+
+impl Plugin for TouchControlsPlugin {
+    fn build(&self, app: &mut App) {
+        let read = read_touch_controls
+            .after(InputManagerSystem::Update)
+            .before(apply_input_assists);
+        #[cfg(feature = "debug_camera")]
+        let read = read.before(update_look_input);
+
+        app.insert_resource(self.controls)
+            .add_startup_system(spawn_touch_controls)
+            .add_system_to_stage(CoreStage::PreUpdate, read)
+            .add_system(update_touch_controls);
+    }
+}
+
+#[derive(Resource, Debug, Clone, Copy, PartialEq)]
+pub struct TouchControls {
+    /// Drag distance that fully tilts the stick, in pixels
+    pub stick_radius: f32,
+    /// Multiplier on dragging to look, one pixel dragged turns like one pixel of mouse motion
+    pub look_speed: f32,
+    /// Side length of the buttons, in pixels
+    pub button_size: f32,
+    /// Distance of the buttons to the screen edges and each other, in pixels
+    pub margin: f32,
+}
+
+impl Default for TouchControls {
+    fn default() -> Self {
+        TouchControls {
+            stick_radius: 75.0,
+            look_speed: 1.0,
+            button_size: 90.0,
+            margin: 24.0,
+        }
+    }
+}
+
+const TOUCH_BUTTONS: [CharacterActions; 2] = [CharacterActions::Jump, CharacterActions::Crouch];
+
+impl TouchControls {
+    /// Bottom left corner of a button, counted from the bottom left of the window
+    fn button_origin(&self, index: usize, window: Vec2) -> Vec2 {
+        let step = self.button_size + self.margin;
+        Vec2::new(window.x - step * (index + 1) as f32, self.margin)
+    }
+
+    fn button_at(&self, position: Vec2, window: Vec2) -> Option<CharacterActions> {
+        TOUCH_BUTTONS
+            .iter()
+            .enumerate()
+            .find_map(|(index, action)| {
+                let min = self.button_origin(index, window);
+                let max = min + Vec2::splat(self.button_size);
+                let inside = position.cmpge(min).all() && position.cmple(max).all();
+                inside.then_some(*action)
+            })
+    }
+
+    /// Stick tilt of a touch, `None` unless it started on the left half
+    fn stick(&self, touch: &Touch, window: Vec2) -> Option<Vec2> {
+        if touch.start_position().x >= window.x / 2.0 {
+            return None;
+        }
+        let offset = touch.position() - touch.start_position();
+        Some((offset / self.stick_radius).clamp_length_max(1.0))
+    }
+
+    /// Drag of a touch since the last frame, `None` unless it started on the right half
+    #[cfg(feature = "debug_camera")]
+    fn look(&self, touch: &Touch, window: Vec2) -> Option<Vec2> {
+        let start = touch.start_position();
+        if start.x < window.x / 2.0 || self.button_at(start, window).is_some() {
+            return None;
+        }
+        Some(touch.delta() * self.look_speed)
+    }
+}
+
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+enum TouchWidget {
+    StickBase,
+    StickKnob,
+    Button(usize),
+}
+
+fn spawn_touch_controls(mut commands: Commands, controls: Res<TouchControls>) {
+    let node = |size: f32, color: Color| NodeBundle {
+        style: Style {
+            position_type: PositionType::Absolute,
+            size: Size::new(Val::Px(size), Val::Px(size)),
+            ..Default::default()
+        },
+        background_color: color.into(),
+        visibility: Visibility::INVISIBLE,
+        ..Default::default()
+    };
+
+    let stick = controls.stick_radius * 2.0;
+    commands
+        .spawn(node(stick, Color::rgba(1.0, 1.0, 1.0, 0.15)))
+        .insert(TouchWidget::StickBase);
+    commands
+        .spawn(node(stick / 3.0, Color::rgba(1.0, 1.0, 1.0, 0.4)))
+        .insert(TouchWidget::StickKnob);
+
+    for index in 0..TOUCH_BUTTONS.len() {
+        let mut button = node(controls.button_size, Color::rgba(1.0, 1.0, 1.0, 0.25));
+        button.visibility = Visibility::VISIBLE;
+        commands.spawn(button).insert(TouchWidget::Button(index));
+    }
+}
+
+/// What the touch controls held last frame, to tell new presses from holds
+#[derive(Debug, Default)]
+struct TouchHeld {
+    stick: bool,
+    buttons: Vec<CharacterActions>,
+}
+
+/// Overrides the device state of `action` while a touch holds it or just let go of it
+///
+/// Leafwing releases actions nothing it knows about holds, so pressing them again every frame
+/// would make them `just_pressed` every frame.
+fn set_touched<A: Actionlike>(actions: &mut ActionState<A>, action: A, held: bool, was_held: bool) {
+    let mut data = actions.action_data(action.clone()).clone();
+    data.state = match (held, was_held) {
+        (true, true) => ButtonState::Pressed,
+        (true, false) => ButtonState::JustPressed,
+        (false, true) => ButtonState::JustReleased,
+        // Left to the device
+        (false, false) => return,
+    };
+    actions.set_action_data(action, data);
+}
+
+fn read_touch_controls(
+    mut characters: Query<
+        (
+            &mut ActionState<CharacterMovement>,
+            &mut ActionState<CharacterActions>,
+        ),
+        Without<CharacterInputDriver>,
+    >,
+    #[cfg(feature = "debug_camera")] mut cameras: Query<
+        &mut ActionState<CameraAction>,
+        With<ControlledCamera>,
+    >,
+    controls: Res<TouchControls>,
+    touches: Res<Touches>,
+    windows: Res<Windows>,
+    mut held: Local<TouchHeld>,
+) {
+    let Some(window) = windows.get_primary() else {
+        return;
+    };
+    let window = Vec2::new(window.width(), window.height());
+
+    let stick = touches
+        .iter()
+        .find_map(|touch| controls.stick(touch, window));
+    let pressed: Vec<CharacterActions> = touches
+        .iter()
+        .filter_map(|touch| controls.button_at(touch.position(), window))
+        .collect();
+
+    for (mut movement, mut actions) in characters.iter_mut() {
+        if let Some(stick) = stick {
+            let mut data = movement.action_data(CharacterMovement::Move).clone();
+            data.axis_pair = Some(DualAxisData::new(stick.x, stick.y));
+            movement.set_action_data(CharacterMovement::Move, data);
+        }
+        set_touched(
+            &mut movement,
+            CharacterMovement::Move,
+            stick.is_some(),
+            held.stick,
+        );
+        for action in TOUCH_BUTTONS {
+            let was_held = held.buttons.contains(&action);
+            set_touched(&mut actions, action, pressed.contains(&action), was_held);
+        }
+    }
+    held.stick = stick.is_some();
+    held.buttons = pressed;
+
+    #[cfg(feature = "debug_camera")]
+    {
+        let look: Vec2 = touches
+            .iter()
+            .filter_map(|touch| controls.look(touch, window))
+            .sum();
+        if look == Vec2::ZERO {
+            return;
+        }
+
+        for mut camera in cameras.iter_mut() {
+            let mut data = camera.action_data(CameraAction::Pan).clone();
+            let pan = data.axis_pair.map(|pan| pan.xy()).unwrap_or_default();
+            // Touch positions count upwards, mouse motion downwards
+            let pan = pan + Vec2::new(look.x, -look.y);
+            data.axis_pair = Some(DualAxisData::new(pan.x, pan.y));
+            camera.set_action_data(CameraAction::Pan, data);
+        }
+    }
+}
+
+/// Moves the stick under the finger and highlights held buttons
+fn update_touch_controls(
+    mut widgets: Query<(
+        &TouchWidget,
+        &mut Style,
+        &mut Visibility,
+        &mut BackgroundColor,
+    )>,
+    controls: Res<TouchControls>,
+    touches: Res<Touches>,
+    windows: Res<Windows>,
+) {
+    let Some(window) = windows.get_primary() else {
+        return;
+    };
+    let window = Vec2::new(window.width(), window.height());
+
+    let stick = touches.iter().find_map(|touch| {
+        let tilt = controls.stick(touch, window)?;
+        Some((touch.start_position(), tilt))
+    });
+    let pressed: Vec<CharacterActions> = touches
+        .iter()
+        .filter_map(|touch| controls.button_at(touch.position(), window))
+        .collect();
+
+    for (widget, mut style, mut visibility, mut color) in widgets.iter_mut() {
+        // Centered on `center`, both counted from the bottom left of the window
+        let mut place = |center: Vec2, size: f32| {
+            style.position = UiRect {
+                left: Val::Px(center.x - size / 2.0),
+                bottom: Val::Px(center.y - size / 2.0),
+                ..Default::default()
+            };
+        };
+
+        match widget {
+            TouchWidget::StickBase | TouchWidget::StickKnob => {
+                visibility.is_visible = stick.is_some();
+                let Some((start, tilt)) = stick else { continue };
+                match widget {
+                    TouchWidget::StickBase => place(start, controls.stick_radius * 2.0),
+                    _ => place(
+                        start + tilt * controls.stick_radius,
+                        controls.stick_radius / 1.5,
+                    ),
+                }
+            }
+            TouchWidget::Button(index) => {
+                let size = controls.button_size;
+                place(controls.button_origin(*index, window) + size / 2.0, size);
+                let alpha = match pressed.contains(&TOUCH_BUTTONS[*index]) {
+                    true => 0.5,
+                    false => 0.25,
+                };
+                *color = Color::rgba(1.0, 1.0, 1.0, alpha).into();
+            }
+        }
+    }
+}
@@ -0,0 +1,97 @@
+use crate::prelude::*;
+use bevy::prelude::*;
+use leafwing_input_manager::prelude::*;
+
+/// Keeps an entity centered while the camera is moved around as usual
+///
+/// Set `target` directly, or toggle with `CameraAction::TrackToggle` to track the editor
+/// selection or whatever is under the cursor.
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct CameraTracking {
+    pub target: Option<Entity>,
+    /// How fast the view turns towards the target, per second
+    pub damping: f32,
+}
+
+impl Default for CameraTracking {
+    fn default() -> Self {
+        CameraTracking {
+            target: None,
+            damping: 8.0,
+        }
+    }
+}
+
+pub fn toggle_camera_tracking(
+    mut cameras: Query<
+        (
+            &Camera,
+            &GlobalTransform,
+            &mut CameraTracking,
+            &ActionState<CameraAction>,
+        ),
+        With<ControlledCamera>,
+    >,
+    targets: Query<(), With<GlobalTransform>>,
+    selection: Res<EditorSelection>,
+    windows: Res<Windows>,
+    picking: Picking,
+    mut messages: EventWriter<HudMessage>,
+) {
+    for (view, transform, mut tracking, actions) in cameras.iter_mut() {
+        if !actions.just_pressed(CameraAction::TrackToggle) {
+            continue;
+        }
+
+        if tracking.target.take().is_some() {
+            messages.send(HudMessage::new("Tracking off"));
+            continue;
+        }
+
+        let picked = || {
+            let window = windows.get_primary()?;
+            picking.under_cursor(view, transform, window)
+        };
+        tracking.target = selection
+            .0
+            .filter(|target| targets.contains(*target))
+            .or_else(picked);
+
+        if let Some(target) = tracking.target {
+            messages.send(HudMessage::new(format!("Tracking {:?}", target)));
+        }
+    }
+}
+
+pub fn update_camera_tracking(
+    mut cameras: Query<(&mut CameraTracking, &mut CameraGoal, &mut DebugCamera)>,
+    targets: Query<&GlobalTransform>,
+    state: Res<State<CameraState>>,
+    time: Res<Time>,
+) {
+    for (mut tracking, mut goal, mut camera) in cameras.iter_mut() {
+        let Some(target) = tracking.target else {
+            continue;
+        };
+        let Ok(target) = targets.get(target) else {
+            // Despawned targets end the tracking
+            tracking.target = None;
+            continue;
+        };
+        let target = target.translation();
+        let t = 1.0 - (-tracking.damping * time.delta_seconds()).exp();
+
+        match state.current() {
+            // Orbiting keeps the focus point centered already
+            CameraState::Orbit => camera.focus = camera.focus.lerp(target, t),
+            CameraState::Fps | CameraState::ThirdPerson | CameraState::Cinematic => {}
+            _ => {
+                let look = goal.looking_at(target, Vec3::Y).rotation;
+                goal.rotation = goal.rotation.slerp(look, t);
+
+                // So mouse look continues from here once tracking stops
+                camera.set_rotation(goal.rotation);
+            }
+        }
+    }
+}
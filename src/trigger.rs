@@ -0,0 +1,109 @@
+use crate::prelude::*;
+use bevy::{prelude::*, utils::HashSet};
+use bevy_rapier3d::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Sends `TriggerEntered` and `TriggerExited` for every `TriggerVolume`
+pub struct TriggerPlugin;
+
+impl Plugin for TriggerPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<TriggerEntered>()
+            .add_event::<TriggerExited>()
+            .add_system(setup_trigger_volumes)
+            .add_system(update_trigger_volumes.after(setup_trigger_volumes));
+    }
+}
+
+/// What a trigger volume reacts to
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TriggerFilter {
+    #[default]
+    Characters,
+    /// Any rigid body, characters included
+    RigidBodies,
+}
+
+/// Sensor collider reporting what enters and leaves it, e.g. for checkpoints or music zones
+#[derive(Component, Debug, Default, Clone, PartialEq)]
+pub struct TriggerVolume {
+    pub filter: TriggerFilter,
+    occupants: HashSet<Entity>,
+}
+
+impl TriggerVolume {
+    pub fn new(filter: TriggerFilter) -> Self {
+        TriggerVolume {
+            filter,
+            occupants: HashSet::default(),
+        }
+    }
+
+    pub fn contains(&self, entity: Entity) -> bool {
+        self.occupants.contains(&entity)
+    }
+
+    /// Entities inside the volume as of the last update
+    pub fn occupants(&self) -> impl Iterator<Item = Entity> + '_ {
+        self.occupants.iter().copied()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TriggerEntered {
+    pub trigger: Entity,
+    pub entity: Entity,
+}
+
+/// Also sent for occupants that were despawned
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TriggerExited {
+    pub trigger: Entity,
+    pub entity: Entity,
+}
+
+fn setup_trigger_volumes(mut commands: Commands, q: Query<Entity, Added<TriggerVolume>>) {
+    for entity in q.iter() {
+        // Kinematic characters and platforms don't touch static sensors by default
+        commands.entity(entity).insert(Sensor).insert(
+            ActiveCollisionTypes::default()
+                | ActiveCollisionTypes::KINEMATIC_STATIC
+                | ActiveCollisionTypes::KINEMATIC_KINEMATIC,
+        );
+    }
+}
+
+pub fn update_trigger_volumes(
+    mut volumes: Query<(Entity, &mut TriggerVolume)>,
+    characters: Query<(), With<CharacterMovementController>>,
+    bodies: Query<(), With<RigidBody>>,
+    mut entered: EventWriter<TriggerEntered>,
+    mut exited: EventWriter<TriggerExited>,
+    rapier: Res<RapierContext>,
+) {
+    for (trigger, mut volume) in volumes.iter_mut() {
+        let inside: HashSet<Entity> = rapier
+            .intersections_with(trigger)
+            .filter(|(_, _, intersecting)| *intersecting)
+            .map(|(a, b, _)| if a == trigger { b } else { a })
+            // Colliders may be children of the body they belong to
+            .map(|collider| rapier.collider_parent(collider).unwrap_or(collider))
+            .filter(|entity| match volume.filter {
+                TriggerFilter::Characters => characters.contains(*entity),
+                TriggerFilter::RigidBodies => bodies.contains(*entity),
+            })
+            .collect();
+
+        if inside == volume.occupants {
+            continue;
+        }
+
+        for &entity in inside.difference(&volume.occupants) {
+            entered.send(TriggerEntered { trigger, entity });
+        }
+        for &entity in volume.occupants.difference(&inside) {
+            exited.send(TriggerExited { trigger, entity });
+        }
+        volume.occupants = inside;
+    }
+}
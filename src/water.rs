@@ -0,0 +1,118 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+/// Floats `Buoyant` dynamic bodies in `WaterVolume`s
+pub struct WaterPlugin;
+
+impl Plugin for WaterPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(setup_water_volumes)
+            .add_system(setup_buoyant)
+            .add_system(apply_buoyancy.after(setup_buoyant));
+    }
+}
+
+/// Sensor collider filled with water up to its top
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct WaterVolume {
+    /// Mass of a cubic meter of water, compared against the density of `Buoyant` bodies
+    pub density: f32,
+}
+
+impl Default for WaterVolume {
+    fn default() -> Self {
+        WaterVolume { density: 1.0 }
+    }
+}
+
+/// Dynamic body pushed up by water it's submerged in, floating when lighter than the water
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct Buoyant {
+    /// Mass of a cubic meter of the body, replacing its collider mass properties
+    pub density: f32,
+    /// Fraction of linear velocity lost per second while fully submerged
+    pub linear_drag: f32,
+    /// Fraction of angular velocity lost per second while fully submerged
+    pub angular_drag: f32,
+}
+
+impl Default for Buoyant {
+    fn default() -> Self {
+        Buoyant {
+            density: 0.5,
+            linear_drag: 1.0,
+            angular_drag: 1.0,
+        }
+    }
+}
+
+impl Buoyant {
+    pub fn with_density(mut self, density: f32) -> Self {
+        self.density = density;
+        self
+    }
+
+    pub fn with_drag(mut self, linear: f32, angular: f32) -> Self {
+        self.linear_drag = linear;
+        self.angular_drag = angular;
+        self
+    }
+}
+
+fn setup_water_volumes(mut commands: Commands, q: Query<Entity, Added<WaterVolume>>) {
+    for entity in q.iter() {
+        commands.entity(entity).insert(Sensor);
+    }
+}
+
+fn setup_buoyant(mut commands: Commands, q: Query<(Entity, &Buoyant), Changed<Buoyant>>) {
+    for (entity, buoyant) in q.iter() {
+        commands
+            .entity(entity)
+            .insert(ColliderMassProperties::Density(buoyant.density))
+            .insert(ExternalForce::default());
+    }
+}
+
+pub fn apply_buoyancy(
+    mut bodies: Query<(
+        Entity,
+        &Buoyant,
+        &Collider,
+        &GlobalTransform,
+        &Velocity,
+        &mut ExternalForce,
+    )>,
+    waters: Query<(Entity, &WaterVolume, &Collider, &GlobalTransform)>,
+    rapier: Res<RapierContext>,
+    config: Res<RapierConfiguration>,
+) {
+    for (entity, buoyant, collider, transform, velocity, mut force) in bodies.iter_mut() {
+        // Submerged share of the body's height, treating it as upright
+        let aabb = collider.raw.compute_local_aabb();
+        let bottom = transform.translation().y + aabb.mins.y;
+        let height = (aabb.maxs.y - aabb.mins.y).max(f32::EPSILON);
+
+        let water = waters
+            .iter()
+            .filter(|(water, ..)| rapier.intersection_pair(entity, *water) == Some(true))
+            .map(|(_, water, collider, transform)| {
+                let surface = transform.translation().y + collider.raw.compute_local_aabb().maxs.y;
+                (water, ((surface - bottom) / height).clamp(0.0, 1.0))
+            })
+            .max_by(|(_, a), (_, b)| a.total_cmp(b));
+
+        let Some((water, submerged)) = water else {
+            force.force = Vec3::ZERO;
+            force.torque = Vec3::ZERO;
+            continue;
+        };
+
+        let volume = collider.raw.mass_properties(1.0).mass();
+        let mass = volume * buoyant.density;
+        let lift = -config.gravity * water.density * volume * submerged;
+
+        force.force = lift - velocity.linvel * buoyant.linear_drag * mass * submerged;
+        force.torque = -velocity.angvel * buoyant.angular_drag * mass * submerged;
+    }
+}